@@ -0,0 +1,6 @@
+DROP TABLE IF EXISTS measurements;
+DROP TABLE IF EXISTS goals;
+DROP TABLE IF EXISTS workout_exercises;
+DROP TABLE IF EXISTS exercises;
+DROP TABLE IF EXISTS workouts;
+DROP TABLE IF EXISTS users;
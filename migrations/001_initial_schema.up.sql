@@ -81,9 +81,9 @@ CREATE TABLE IF NOT EXISTS measurements (
 );
 
 -- Create indexes for performance
-CREATE INDEX idx_workouts_user_id ON workouts(user_id);
-CREATE INDEX idx_workouts_date ON workouts(date);
-CREATE INDEX idx_workout_exercises_workout_id ON workout_exercises(workout_id);
-CREATE INDEX idx_goals_user_id ON goals(user_id);
-CREATE INDEX idx_measurements_user_id ON measurements(user_id);
-CREATE INDEX idx_measurements_date ON measurements(date);
+CREATE INDEX IF NOT EXISTS idx_workouts_user_id ON workouts(user_id);
+CREATE INDEX IF NOT EXISTS idx_workouts_date ON workouts(date);
+CREATE INDEX IF NOT EXISTS idx_workout_exercises_workout_id ON workout_exercises(workout_id);
+CREATE INDEX IF NOT EXISTS idx_goals_user_id ON goals(user_id);
+CREATE INDEX IF NOT EXISTS idx_measurements_user_id ON measurements(user_id);
+CREATE INDEX IF NOT EXISTS idx_measurements_date ON measurements(date);
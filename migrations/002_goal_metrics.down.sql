@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS idx_goals_metric;
+
+ALTER TABLE goals DROP COLUMN IF EXISTS start_value;
+ALTER TABLE goals DROP COLUMN IF EXISTS exercise_id;
+ALTER TABLE goals DROP COLUMN IF EXISTS metric;
@@ -0,0 +1,7 @@
+-- Link goals to a metric source so progress can be tracked automatically.
+-- Goals without a metric keep a manually entered current_value.
+ALTER TABLE goals ADD COLUMN IF NOT EXISTS metric VARCHAR(50); -- e.g., "body_weight", "exercise_distance"
+ALTER TABLE goals ADD COLUMN IF NOT EXISTS exercise_id UUID REFERENCES exercises(id) ON DELETE SET NULL;
+ALTER TABLE goals ADD COLUMN IF NOT EXISTS start_value DECIMAL(10, 2); -- baseline used for progress, e.g. starting bodyweight
+
+CREATE INDEX IF NOT EXISTS idx_goals_metric ON goals(user_id) WHERE metric IS NOT NULL;
@@ -0,0 +1,9 @@
+DROP INDEX IF EXISTS idx_exercises_lower_name;
+DROP INDEX IF EXISTS idx_exercises_category;
+DROP INDEX IF EXISTS idx_exercises_owner_id;
+
+-- Custom exercises would otherwise become visible to everyone. This fails while
+-- any of them is still used in a workout.
+DELETE FROM exercises WHERE owner_id IS NOT NULL;
+
+ALTER TABLE exercises DROP COLUMN IF EXISTS owner_id;
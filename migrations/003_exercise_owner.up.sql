@@ -0,0 +1,7 @@
+-- Custom exercises are owned by the user who created them and are only
+-- visible to that user. Exercises without an owner form the shared catalog.
+ALTER TABLE exercises ADD COLUMN IF NOT EXISTS owner_id UUID REFERENCES users(id) ON DELETE CASCADE;
+
+CREATE INDEX IF NOT EXISTS idx_exercises_owner_id ON exercises(owner_id);
+CREATE INDEX IF NOT EXISTS idx_exercises_category ON exercises(category);
+CREATE INDEX IF NOT EXISTS idx_exercises_lower_name ON exercises(LOWER(name));
@@ -0,0 +1,11 @@
+DROP TABLE IF EXISTS seed_versions;
+
+DROP INDEX IF EXISTS idx_exercises_equipment;
+DROP INDEX IF EXISTS idx_exercises_primary_muscles;
+
+ALTER TABLE exercises DROP COLUMN IF EXISTS measurement_type;
+ALTER TABLE exercises DROP COLUMN IF EXISTS mechanics;
+ALTER TABLE exercises DROP COLUMN IF EXISTS equipment;
+ALTER TABLE exercises DROP COLUMN IF EXISTS secondary_muscles;
+ALTER TABLE exercises DROP COLUMN IF EXISTS primary_muscles;
+ALTER TABLE exercises DROP COLUMN IF EXISTS slug;
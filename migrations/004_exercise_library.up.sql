@@ -0,0 +1,17 @@
+-- Structured metadata for exercises
+ALTER TABLE exercises ADD COLUMN IF NOT EXISTS slug VARCHAR(100) UNIQUE; -- stable key for built-in library exercises
+ALTER TABLE exercises ADD COLUMN IF NOT EXISTS primary_muscles TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE exercises ADD COLUMN IF NOT EXISTS secondary_muscles TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE exercises ADD COLUMN IF NOT EXISTS equipment VARCHAR(50); -- e.g., "barbell", "dumbbell", "machine"
+ALTER TABLE exercises ADD COLUMN IF NOT EXISTS mechanics VARCHAR(20); -- "compound" or "isolation"
+ALTER TABLE exercises ADD COLUMN IF NOT EXISTS measurement_type VARCHAR(20) NOT NULL DEFAULT 'weight_reps'; -- "weight_reps", "reps", "time", "distance"
+
+CREATE INDEX IF NOT EXISTS idx_exercises_primary_muscles ON exercises USING GIN (primary_muscles);
+CREATE INDEX IF NOT EXISTS idx_exercises_equipment ON exercises(equipment);
+
+-- Versions of data seeded by the application (e.g., the built-in exercise library)
+CREATE TABLE IF NOT EXISTS seed_versions (
+    name VARCHAR(100) PRIMARY KEY,
+    version INTEGER NOT NULL,
+    applied_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,16 @@
+-- Order of exercises within a workout
+ALTER TABLE workout_exercises ADD COLUMN IF NOT EXISTS position INTEGER NOT NULL DEFAULT 0;
+
+-- Number existing entries in the order they were created. Workouts that
+-- already have an order are left alone, so this is safe to run again.
+UPDATE workout_exercises we
+SET position = ordered.position
+FROM (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY workout_id ORDER BY created_at, id) - 1 AS position
+    FROM workout_exercises
+) ordered
+WHERE we.id = ordered.id
+  AND NOT EXISTS (
+      SELECT 1 FROM workout_exercises other
+      WHERE other.workout_id = we.workout_id AND other.position <> 0
+  );
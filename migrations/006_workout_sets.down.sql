@@ -0,0 +1,3 @@
+-- The aggregate sets/reps/weight columns of workout_exercises are kept up to
+-- date alongside individual sets, so they remain as the summary of each entry
+DROP TABLE IF EXISTS workout_sets;
@@ -0,0 +1,26 @@
+-- Individual sets of an exercise entry in a workout
+CREATE TABLE IF NOT EXISTS workout_sets (
+    id UUID PRIMARY KEY,
+    workout_exercise_id UUID NOT NULL REFERENCES workout_exercises(id) ON DELETE CASCADE,
+    set_index INTEGER NOT NULL, -- order within the entry, starting at 0
+    reps INTEGER,
+    weight DECIMAL(6, 2), -- in kg
+    duration INTEGER, -- in seconds
+    distance DECIMAL(6, 2), -- in km
+    rpe DECIMAL(3, 1), -- rate of perceived exertion, 1 to 10
+    rir INTEGER, -- reps in reserve
+    set_type VARCHAR(20) NOT NULL DEFAULT 'working', -- "warmup", "working", "drop" or "failure"
+    completed BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (workout_exercise_id, set_index)
+);
+
+-- Expand the aggregate sets/reps/weight of existing entries into individual working sets
+INSERT INTO workout_sets (id, workout_exercise_id, set_index, reps, weight, duration, distance, created_at, updated_at)
+SELECT uuid_generate_v4(), we.id, s.set_index, we.reps, we.weight, we.duration, we.distance, we.created_at, we.updated_at
+FROM workout_exercises we
+CROSS JOIN LATERAL generate_series(0, COALESCE(we.sets, 1) - 1) AS s(set_index)
+WHERE (we.sets IS NOT NULL OR we.reps IS NOT NULL OR we.weight IS NOT NULL
+       OR we.duration IS NOT NULL OR we.distance IS NOT NULL)
+  AND NOT EXISTS (SELECT 1 FROM workout_sets ws WHERE ws.workout_exercise_id = we.id);
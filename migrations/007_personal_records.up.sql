@@ -0,0 +1,19 @@
+-- Personal record history, one row per record set
+CREATE TABLE IF NOT EXISTS personal_records (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    exercise_id UUID NOT NULL REFERENCES exercises(id) ON DELETE CASCADE,
+    record_type VARCHAR(30) NOT NULL, -- e.g. "max_weight", "estimated_one_rep_max", "fastest_pace"
+    value DECIMAL(12, 2) NOT NULL,
+    weight DECIMAL(6, 2), -- in kg
+    reps INTEGER,
+    previous_value DECIMAL(12, 2),
+    workout_id UUID NOT NULL REFERENCES workouts(id) ON DELETE CASCADE,
+    workout_set_id UUID REFERENCES workout_sets(id) ON DELETE CASCADE,
+    achieved_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_personal_records_user_exercise
+    ON personal_records (user_id, exercise_id, record_type, achieved_at DESC);
+CREATE INDEX IF NOT EXISTS idx_personal_records_workout ON personal_records (workout_id);
@@ -0,0 +1,2 @@
+-- Keyset pagination of a user's workouts orders by (date, id)
+CREATE INDEX IF NOT EXISTS idx_workouts_user_date_id ON workouts (user_id, date DESC, id DESC);
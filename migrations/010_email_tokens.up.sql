@@ -0,0 +1,15 @@
+-- Users prove they own their email address by following a verification link
+ALTER TABLE users ADD COLUMN IF NOT EXISTS email_verified BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Single-use tokens sent by email for password resets and email verification
+CREATE TABLE IF NOT EXISTS email_tokens (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    purpose VARCHAR(30) NOT NULL, -- "password_reset" or "email_verification"
+    token_hash VARCHAR(64) NOT NULL UNIQUE, -- SHA-256 of the token, hex encoded
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_email_tokens_user_purpose ON email_tokens (user_id, purpose);
@@ -0,0 +1,11 @@
+-- Consecutive failed logins. After too many the account is locked for a while,
+-- for longer with every further failure.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS failed_login_attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS locked_until TIMESTAMPTZ;
+
+-- Token buckets of the Postgres rate limit store. Losing them on a crash is
+-- harmless, so the table skips the write-ahead log.
+CREATE UNLOGGED TABLE IF NOT EXISTS rate_limits (
+    key VARCHAR(255) PRIMARY KEY, -- limiter name and client, e.g. "auth:ip:203.0.113.7"
+    full_at TIMESTAMPTZ NOT NULL -- when the bucket will be full again
+);
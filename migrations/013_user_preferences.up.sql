@@ -0,0 +1,12 @@
+-- Display preferences. Weights are always stored in kg, distances in km and
+-- times in UTC; these only change how responses are presented.
+CREATE TABLE IF NOT EXISTS user_preferences (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    unit_system VARCHAR(20) NOT NULL DEFAULT 'metric', -- metric or imperial
+    timezone VARCHAR(64) NOT NULL DEFAULT 'UTC', -- IANA name
+    week_start VARCHAR(20) NOT NULL DEFAULT 'monday',
+    rest_timer_seconds INTEGER NOT NULL DEFAULT 90,
+    locale VARCHAR(35) NOT NULL DEFAULT 'en', -- BCP 47 language tag
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
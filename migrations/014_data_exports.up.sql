@@ -0,0 +1,18 @@
+-- Archives of all of a user's data, generated in the background. The archive
+-- is deleted once its download link expires.
+CREATE TABLE IF NOT EXISTS data_exports (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending', -- pending, running, completed or failed
+    archive BYTEA, -- ZIP file, set once completed
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ,
+    expires_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_data_exports_user_id ON data_exports (user_id);
+
+-- At most one export per user is in progress at a time
+CREATE UNIQUE INDEX IF NOT EXISTS idx_data_exports_in_progress
+    ON data_exports (user_id)
+    WHERE status IN ('pending', 'running');
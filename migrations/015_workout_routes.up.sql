@@ -0,0 +1,21 @@
+-- GPS routes of imported cardio workouts. Totals are computed from the full
+-- track; only a downsampled copy of its points is kept.
+CREATE TABLE IF NOT EXISTS workout_routes (
+    workout_id UUID PRIMARY KEY REFERENCES workouts(id) ON DELETE CASCADE,
+    workout_exercise_id UUID REFERENCES workout_exercises(id) ON DELETE SET NULL, -- the cardio entry
+    distance DECIMAL(8, 3) NOT NULL, -- in km
+    moving_time INTEGER NOT NULL, -- in seconds
+    elapsed_time INTEGER NOT NULL, -- in seconds
+    elevation_gain DECIMAL(7, 1), -- in m, unless the track has no elevation
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS route_points (
+    workout_id UUID NOT NULL REFERENCES workout_routes(workout_id) ON DELETE CASCADE,
+    point_index INTEGER NOT NULL, -- order along the route, starting at 0
+    latitude DOUBLE PRECISION NOT NULL,
+    longitude DOUBLE PRECISION NOT NULL,
+    elevation DOUBLE PRECISION, -- in m
+    recorded_at TIMESTAMPTZ,
+    PRIMARY KEY (workout_id, point_index)
+);
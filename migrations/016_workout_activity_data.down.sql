@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS workout_samples;
+DROP TABLE IF EXISTS workout_laps;
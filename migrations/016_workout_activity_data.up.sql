@@ -0,0 +1,27 @@
+-- Laps and sensor samples of workouts imported from device files
+CREATE TABLE IF NOT EXISTS workout_laps (
+    workout_id UUID NOT NULL REFERENCES workouts(id) ON DELETE CASCADE,
+    lap_index INTEGER NOT NULL, -- lap number, starting at 1
+    start_time TIMESTAMPTZ NOT NULL,
+    elapsed_time INTEGER NOT NULL, -- in seconds
+    moving_time INTEGER NOT NULL, -- in seconds
+    distance DECIMAL(8, 3), -- in km
+    average_heart_rate INTEGER,
+    max_heart_rate INTEGER,
+    average_power INTEGER, -- in watts
+    max_power INTEGER,
+    average_cadence INTEGER,
+    calories INTEGER,
+    elevation_gain DECIMAL(7, 1), -- in m
+    PRIMARY KEY (workout_id, lap_index)
+);
+
+CREATE TABLE IF NOT EXISTS workout_samples (
+    workout_id UUID NOT NULL REFERENCES workouts(id) ON DELETE CASCADE,
+    sample_index INTEGER NOT NULL, -- order in time, starting at 0
+    recorded_at TIMESTAMPTZ NOT NULL,
+    heart_rate INTEGER, -- in beats per minute
+    power INTEGER, -- in watts
+    cadence INTEGER,
+    PRIMARY KEY (workout_id, sample_index)
+);
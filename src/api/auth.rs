@@ -1,21 +1,27 @@
-use crate::models::{UserRegisterRequest, UserLoginRequest};
-use crate::services::UserService;
-use actix_web::{web, HttpResponse, Responder, post};
+use crate::error::AppError;
+use crate::models::{
+    UserRegisterRequest, UserLoginRequest, RefreshTokenRequest, SessionId,
+    ForgotPasswordRequest, ResetPasswordRequest, VerifyEmailRequest,
+};
+use crate::services::{AuthService, UserService};
+use actix_web::{web, HttpResponse, post};
+use uuid::Uuid;
 use validator::Validate;
-use utoipa::OpenApi;
 
 /// Register a new user
 ///
-/// Register a new user with email, username, and password
+/// Register a new user with email, username, and password. A verification link
+/// is sent to the email address.
 #[utoipa::path(
     post,
     path = "/auth/register",
     request_body = UserRegisterRequest,
     responses(
         (status = 201, description = "User successfully registered", body = UserProfileResponse),
-        (status = 400, description = "Invalid request data"),
-        (status = 409, description = "User with this email already exists"),
-        (status = 500, description = "Internal server error")
+        (status = 400, description = "Invalid request data", body = ErrorResponse),
+        (status = 409, description = "Email or username already taken", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     tag = "auth"
 )]
@@ -23,41 +29,29 @@ use utoipa::OpenApi;
 pub async fn register(
     user_service: web::Data<UserService>,
     req: web::Json<UserRegisterRequest>,
-) -> impl Responder {
+) -> Result<HttpResponse, AppError> {
     // Validate request
-    if let Err(errors) = req.validate() {
-        return HttpResponse::BadRequest().json(errors);
-    }
+    req.validate()?;
     
     // Register user
-    match user_service.register(req.into_inner()).await {
-        Ok(user) => HttpResponse::Created().json(user),
-        Err(e) => {
-            // Check for specific error types (e.g., email already exists)
-            if e.to_string().contains("already exists") {
-                return HttpResponse::Conflict().json(serde_json::json!({
-                    "error": e.to_string()
-                }));
-            }
-            
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to register user"
-            }))
-        }
-    }
+    let user = user_service.register(req.into_inner()).await?;
+    
+    Ok(HttpResponse::Created().json(user))
 }
 
 /// Login a user
 ///
-/// Authenticate a user with email and password
+/// Authenticate a user with email and password. Users with two-factor authentication
+/// get a challenge token to complete the login with `POST /auth/mfa/verify`.
 #[utoipa::path(
     post,
     path = "/auth/login",
     request_body = UserLoginRequest,
     responses(
-        (status = 200, description = "Login successful", body = LoginResponse),
-        (status = 401, description = "Invalid credentials"),
-        (status = 500, description = "Internal server error")
+        (status = 200, description = "Login successful, or a challenge when two-factor authentication is enabled", body = LoginResult),
+        (status = 401, description = "Invalid credentials", body = ErrorResponse),
+        (status = 429, description = "Too many attempts", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     tag = "auth"
 )]
@@ -65,31 +59,174 @@ pub async fn register(
 pub async fn login(
     user_service: web::Data<UserService>,
     req: web::Json<UserLoginRequest>,
-) -> impl Responder {
-    // Attempt to login
-    match user_service.login(req.into_inner()).await {
-        Ok((user, token)) => HttpResponse::Ok().json(serde_json::json!({
-            "user": user,
-            "token": token
-        })),
-        Err(e) => {
-            // For security reasons, be vague about specific errors
-            if e.to_string().contains("Invalid email or password") {
-                return HttpResponse::Unauthorized().json(serde_json::json!({
-                    "error": "Invalid email or password"
-                }));
-            }
-            
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to log in"
-            }))
-        }
-    }
+) -> Result<HttpResponse, AppError> {
+    // Attempt to login; unknown emails and wrong passwords get the same error
+    let result = user_service.login(req.into_inner()).await?;
+    
+    Ok(HttpResponse::Ok().json(result))
+}
+
+/// Refresh an access token
+///
+/// Exchange a refresh token for a new access token and refresh token. Each refresh
+/// token can only be used once; reusing one revokes the whole session.
+#[utoipa::path(
+    post,
+    path = "/auth/refresh",
+    request_body = RefreshTokenRequest,
+    responses(
+        (status = 200, description = "Tokens refreshed successfully", body = TokenResponse),
+        (status = 400, description = "Invalid request data", body = ErrorResponse),
+        (status = 401, description = "Invalid, expired, reused or revoked refresh token", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "auth"
+)]
+#[post("/refresh")]
+pub async fn refresh(
+    auth_service: web::Data<AuthService>,
+    req: web::Json<RefreshTokenRequest>,
+) -> Result<HttpResponse, AppError> {
+    // Validate request
+    req.validate()?;
+    
+    let tokens = auth_service.refresh(&req.refresh_token).await?;
+    
+    Ok(HttpResponse::Ok().json(tokens))
 }
 
-// Define a new type for login response for Swagger documentation
-#[derive(serde::Serialize, utoipa::ToSchema)]
-pub struct LoginResponse {
-    user: crate::models::UserProfileResponse,
-    token: String,
+/// Log out
+///
+/// Revoke the current session. Its access and refresh tokens stop working immediately.
+#[utoipa::path(
+    post,
+    path = "/auth/logout",
+    responses(
+        (status = 204, description = "Logged out successfully"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "auth",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[post("/logout")]
+pub async fn logout(
+    auth_service: web::Data<AuthService>,
+    user_id: web::ReqData<Uuid>,
+    session_id: web::ReqData<SessionId>,
+) -> Result<HttpResponse, AppError> {
+    auth_service.logout(user_id.into_inner(), session_id.into_inner().0).await?;
+    
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Log out everywhere
+///
+/// Revoke every session of the authenticated user, including the current one
+#[utoipa::path(
+    post,
+    path = "/auth/logout-all",
+    responses(
+        (status = 204, description = "Logged out of all sessions successfully"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "auth",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[post("/logout-all")]
+pub async fn logout_all(
+    auth_service: web::Data<AuthService>,
+    user_id: web::ReqData<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    auth_service.logout_all(user_id.into_inner()).await?;
+    
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Request a password reset
+///
+/// Email a link to reset the password. The response is the same whether or not
+/// an account exists for the address.
+#[utoipa::path(
+    post,
+    path = "/auth/forgot-password",
+    request_body = ForgotPasswordRequest,
+    responses(
+        (status = 204, description = "Reset link sent if the account exists"),
+        (status = 400, description = "Invalid request data", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "auth"
+)]
+#[post("/forgot-password")]
+pub async fn forgot_password(
+    user_service: web::Data<UserService>,
+    req: web::Json<ForgotPasswordRequest>,
+) -> Result<HttpResponse, AppError> {
+    // Validate request
+    req.validate()?;
+    
+    user_service.forgot_password(req.into_inner()).await?;
+    
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Reset the password
+///
+/// Set a new password with the token from a password reset email. The token can
+/// only be used once, and every session of the user is revoked.
+#[utoipa::path(
+    post,
+    path = "/auth/reset-password",
+    request_body = ResetPasswordRequest,
+    responses(
+        (status = 204, description = "Password reset successfully"),
+        (status = 400, description = "Invalid request data or invalid, used or expired token", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "auth"
+)]
+#[post("/reset-password")]
+pub async fn reset_password(
+    user_service: web::Data<UserService>,
+    req: web::Json<ResetPasswordRequest>,
+) -> Result<HttpResponse, AppError> {
+    // Validate request
+    req.validate()?;
+    
+    user_service.reset_password(req.into_inner()).await?;
+    
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Verify an email address
+///
+/// Confirm the email address with the token from the verification email
+#[utoipa::path(
+    post,
+    path = "/auth/verify-email",
+    request_body = VerifyEmailRequest,
+    responses(
+        (status = 204, description = "Email verified successfully"),
+        (status = 400, description = "Invalid request data or invalid, used or expired token", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "auth"
+)]
+#[post("/verify-email")]
+pub async fn verify_email(
+    user_service: web::Data<UserService>,
+    req: web::Json<VerifyEmailRequest>,
+) -> Result<HttpResponse, AppError> {
+    // Validate request
+    req.validate()?;
+    
+    user_service.verify_email(req.into_inner()).await?;
+    
+    Ok(HttpResponse::NoContent().finish())
 }
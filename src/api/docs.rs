@@ -1,13 +1,30 @@
+use crate::error::ErrorResponse;
 use crate::models::{
-    UserRegisterRequest, UserLoginRequest, UserProfileResponse,
-    CreateWorkoutRequest, WorkoutDetailsResponse, Workout
+    UserRegisterRequest, UserLoginRequest, UserProfileResponse, RefreshTokenRequest, TokenResponse,
+    ForgotPasswordRequest, ResetPasswordRequest, VerifyEmailRequest, LoginResponse, LoginResult,
+    MfaSetupResponse, MfaCodeRequest, MfaDisableRequest, MfaVerifyRequest, RecoveryCodesResponse,
+    MfaChallengeResponse, UpdateProfileRequest, ChangePasswordRequest, DeleteAccountRequest,
+    UserPreferences, UpdatePreferencesRequest, UnitSystem, WeekStart, UnitSelection,
+    ExportStatus, DataExportResponse,
+    CreateWorkoutRequest, WorkoutDetailsResponse, Workout,
+    WorkoutExerciseInput, WorkoutSetInput, WorkoutSet, SetType,
+    PatchWorkoutRequest, UpdateWorkoutExerciseRequest, ReorderWorkoutExercisesRequest, WorkoutSort,
+    WorkoutInclude, WorkoutList,
+    ImportRowError, ExerciseMatchAction, ExerciseMatch, ImportedWorkoutStatus, ImportedWorkoutPreview,
+    WorkoutImportResponse, RouteSplit, RouteSummary, RouteGeometry, RouteProperties, RouteFeature,
+    WorkoutLap, WorkoutSample,
+    CreateGoalRequest, UpdateGoalRequest, GoalResponse,
+    Measurement, CreateMeasurementRequest, UpdateMeasurementRequest,
+    Exercise, CreateExerciseRequest, ExerciseListResponse,
+    PersonalRecord, RecordType, ExerciseRecordsResponse,
+    OneRepMaxFormula, Bucket, ExerciseProgressionResponse, ProgressionPoint,
+    StatsSummary, CategoryBreakdown, MuscleGroupBreakdown,
+    StreakStats, ActivityCalendar, ActivityDay
 };
 use utoipa::{
     OpenApi, 
-    ToSchema, 
     openapi::security::{SecurityScheme, ApiKey, ApiKeyValue}
 };
-use utoipa::openapi::ComponentsBuilder;
 
 /// Generate the OpenAPI documentation
 #[derive(OpenApi)]
@@ -20,26 +37,149 @@ use utoipa::openapi::ComponentsBuilder;
     paths(
         crate::api::auth::register,
         crate::api::auth::login,
+        crate::api::auth::refresh,
+        crate::api::auth::logout,
+        crate::api::auth::logout_all,
+        crate::api::auth::forgot_password,
+        crate::api::auth::reset_password,
+        crate::api::auth::verify_email,
+        crate::api::mfa::setup,
+        crate::api::mfa::confirm,
+        crate::api::mfa::disable,
+        crate::api::mfa::regenerate_recovery_codes,
+        crate::api::mfa::verify,
         crate::api::user::get_profile,
+        crate::api::user::update_profile,
+        crate::api::user::change_password,
+        crate::api::user::delete_account,
+        crate::api::user::get_preferences,
+        crate::api::user::update_preferences,
+        crate::api::export::request_export,
+        crate::api::export::get_export,
+        crate::api::export::download_export,
         crate::api::workout::create_workout,
         crate::api::workout::get_workout,
         crate::api::workout::get_workouts,
-        crate::api::workout::delete_workout
+        crate::api::workout::delete_workout,
+        crate::api::workout::update_workout,
+        crate::api::workout::patch_workout,
+        crate::api::workout::add_workout_exercise,
+        crate::api::workout::update_workout_exercise,
+        crate::api::workout::reorder_workout_exercises,
+        crate::api::workout::remove_workout_exercise,
+        crate::api::workout::export_workouts_csv,
+        crate::api::workout::import_workouts,
+        crate::api::workout::get_workout_route,
+        crate::api::workout::get_workout_laps,
+        crate::api::workout::get_workout_samples,
+        crate::api::goal::create_goal,
+        crate::api::goal::get_goal,
+        crate::api::goal::get_goals,
+        crate::api::goal::update_goal,
+        crate::api::goal::delete_goal,
+        crate::api::measurement::create_measurement,
+        crate::api::measurement::get_measurement,
+        crate::api::measurement::get_measurements,
+        crate::api::measurement::update_measurement,
+        crate::api::measurement::delete_measurement,
+        crate::api::exercise::list_exercises,
+        crate::api::exercise::get_exercise,
+        crate::api::exercise::create_exercise,
+        crate::api::exercise::get_exercise_records,
+        crate::api::exercise::get_exercise_progression,
+        crate::api::record::get_records,
+        crate::api::stats::get_summary,
+        crate::api::stats::get_streaks,
+        crate::api::stats::get_activity
     ),
     components(
         schemas(
             UserRegisterRequest,
             UserLoginRequest,
             UserProfileResponse,
+            LoginResponse,
+            LoginResult,
+            MfaSetupResponse,
+            MfaCodeRequest,
+            MfaDisableRequest,
+            MfaVerifyRequest,
+            RecoveryCodesResponse,
+            MfaChallengeResponse,
+            RefreshTokenRequest,
+            TokenResponse,
+            ForgotPasswordRequest,
+            ResetPasswordRequest,
+            VerifyEmailRequest,
+            UpdateProfileRequest,
+            ChangePasswordRequest,
+            DeleteAccountRequest,
+            UserPreferences,
+            UpdatePreferencesRequest,
+            UnitSystem,
+            WeekStart,
+            UnitSelection,
+            ExportStatus,
+            DataExportResponse,
             CreateWorkoutRequest, 
             WorkoutDetailsResponse,
-            Workout
+            Workout,
+            WorkoutExerciseInput,
+            WorkoutSetInput,
+            WorkoutSet,
+            SetType,
+            PatchWorkoutRequest,
+            UpdateWorkoutExerciseRequest,
+            ReorderWorkoutExercisesRequest,
+            WorkoutSort,
+            WorkoutInclude,
+            WorkoutList,
+            ImportRowError,
+            ExerciseMatchAction,
+            ExerciseMatch,
+            ImportedWorkoutStatus,
+            ImportedWorkoutPreview,
+            WorkoutImportResponse,
+            RouteSplit,
+            RouteSummary,
+            RouteGeometry,
+            RouteProperties,
+            RouteFeature,
+            WorkoutLap,
+            WorkoutSample,
+            CreateGoalRequest,
+            UpdateGoalRequest,
+            GoalResponse,
+            Measurement,
+            CreateMeasurementRequest,
+            UpdateMeasurementRequest,
+            Exercise,
+            CreateExerciseRequest,
+            ExerciseListResponse,
+            PersonalRecord,
+            RecordType,
+            ExerciseRecordsResponse,
+            OneRepMaxFormula,
+            Bucket,
+            ExerciseProgressionResponse,
+            ProgressionPoint,
+            StatsSummary,
+            CategoryBreakdown,
+            MuscleGroupBreakdown,
+            StreakStats,
+            ActivityCalendar,
+            ActivityDay,
+            ErrorResponse
         ),
     ),
     tags(
         (name = "auth", description = "Authentication endpoints"),
         (name = "users", description = "User management endpoints"),
-        (name = "workouts", description = "Workout management endpoints")
+        (name = "workouts", description = "Workout management endpoints"),
+        (name = "goals", description = "Goal tracking endpoints"),
+        (name = "measurements", description = "Body measurement endpoints"),
+        (name = "exercises", description = "Exercise catalog endpoints"),
+        (name = "records", description = "Personal record endpoints"),
+        (name = "stats", description = "Training statistics endpoints")
     ),
     security(
         ("jwt_auth" = [])
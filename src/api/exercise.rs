@@ -0,0 +1,176 @@
+use crate::error::AppError;
+use crate::models::{CreateExerciseRequest, ExerciseFilter, ProgressionFilter};
+use crate::services::{ExerciseService, RecordService, StatsService};
+use actix_web::{web, HttpResponse, get, post};
+use uuid::Uuid;
+use validator::Validate;
+
+/// List exercises
+///
+/// List the shared exercise catalog together with the authenticated user's custom exercises
+#[utoipa::path(
+    get,
+    path = "/exercises",
+    params(
+        ExerciseFilter
+    ),
+    responses(
+        (status = 200, description = "Exercises retrieved successfully", body = ExerciseListResponse),
+        (status = 400, description = "Invalid query parameters", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "exercises",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[get("")]
+pub async fn list_exercises(
+    exercise_service: web::Data<ExerciseService>,
+    user_id: web::ReqData<Uuid>,
+    filter: web::Query<ExerciseFilter>,
+) -> Result<HttpResponse, AppError> {
+    // Validate query parameters
+    filter.validate()?;
+
+    let exercises = exercise_service.list_exercises(user_id.into_inner(), filter.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(exercises))
+}
+
+/// Get exercise details
+///
+/// Get a catalog exercise or one of the authenticated user's custom exercises
+#[utoipa::path(
+    get,
+    path = "/exercises/{exercise_id}",
+    params(
+        ("exercise_id" = Uuid, Path, description = "Exercise ID")
+    ),
+    responses(
+        (status = 200, description = "Exercise retrieved successfully", body = Exercise),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Exercise not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "exercises",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[get("/{exercise_id}")]
+pub async fn get_exercise(
+    exercise_service: web::Data<ExerciseService>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let exercise = exercise_service.get_exercise(user_id.into_inner(), path.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(exercise))
+}
+
+/// Create a custom exercise
+///
+/// Create an exercise that is only visible to the authenticated user
+#[utoipa::path(
+    post,
+    path = "/exercises",
+    request_body = CreateExerciseRequest,
+    responses(
+        (status = 201, description = "Exercise created successfully", body = Exercise),
+        (status = 400, description = "Invalid request data", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "exercises",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[post("")]
+pub async fn create_exercise(
+    exercise_service: web::Data<ExerciseService>,
+    user_id: web::ReqData<Uuid>,
+    req: web::Json<CreateExerciseRequest>,
+) -> Result<HttpResponse, AppError> {
+    // Validate request
+    req.validate()?;
+
+    let exercise = exercise_service.create_exercise(user_id.into_inner(), req.into_inner()).await?;
+
+    Ok(HttpResponse::Created().json(exercise))
+}
+
+/// Get exercise records
+///
+/// Get the authenticated user's current personal records and record history for an exercise
+#[utoipa::path(
+    get,
+    path = "/exercises/{exercise_id}/records",
+    params(
+        ("exercise_id" = Uuid, Path, description = "Exercise ID")
+    ),
+    responses(
+        (status = 200, description = "Records retrieved successfully", body = ExerciseRecordsResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Exercise not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "exercises",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[get("/{exercise_id}/records")]
+pub async fn get_exercise_records(
+    record_service: web::Data<RecordService>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let records = record_service
+        .get_exercise_records(user_id.into_inner(), path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(records))
+}
+
+/// Get exercise progression
+///
+/// Get a time series of estimated one-rep max, top set and volume for an exercise,
+/// grouped by day, week or month in the given timezone
+#[utoipa::path(
+    get,
+    path = "/exercises/{exercise_id}/progression",
+    params(
+        ("exercise_id" = Uuid, Path, description = "Exercise ID"),
+        ProgressionFilter
+    ),
+    responses(
+        (status = 200, description = "Progression retrieved successfully", body = ExerciseProgressionResponse),
+        (status = 400, description = "Invalid query parameters, range or unknown timezone", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Exercise not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "exercises",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[get("/{exercise_id}/progression")]
+pub async fn get_exercise_progression(
+    stats_service: web::Data<StatsService>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+    filter: web::Query<ProgressionFilter>,
+) -> Result<HttpResponse, AppError> {
+    // Validate query parameters
+    filter.validate()?;
+
+    let progression = stats_service
+        .exercise_progression(user_id.into_inner(), path.into_inner(), filter.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(progression))
+}
@@ -0,0 +1,101 @@
+use crate::error::AppError;
+use crate::models::ExportDownloadQuery;
+use crate::services::ExportService;
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{web, HttpResponse, get, post};
+use uuid::Uuid;
+
+/// Export all of the current user's data
+///
+/// Starts generating a ZIP archive with the profile, preferences, workouts,
+/// goals, measurements and custom exercises as JSON and CSV. Poll the export
+/// until it is completed to get the download link. If an export is already in
+/// progress, it is returned instead of starting another.
+#[utoipa::path(
+    post,
+    path = "/users/me/export",
+    responses(
+        (status = 202, description = "Export started", body = DataExportResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "users",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[post("/me/export")]
+pub async fn request_export(
+    export_service: web::Data<ExportService>,
+    user_id: web::ReqData<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let export = export_service.request_export(user_id.into_inner()).await?;
+
+    Ok(HttpResponse::Accepted().json(export))
+}
+
+/// Get the status of a data export
+#[utoipa::path(
+    get,
+    path = "/users/me/export/{export_id}",
+    params(
+        ("export_id" = Uuid, Path, description = "Export ID")
+    ),
+    responses(
+        (status = 200, description = "Export status", body = DataExportResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Export not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "users",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[get("/me/export/{export_id}")]
+pub async fn get_export(
+    export_service: web::Data<ExportService>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let export = export_service.get_export(user_id.into_inner(), path.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(export))
+}
+
+/// Download the archive of a data export
+///
+/// Uses the signed link from the export status, which works without
+/// authentication until the export expires.
+#[utoipa::path(
+    get,
+    path = "/exports/{export_id}/download",
+    params(
+        ("export_id" = Uuid, Path, description = "Export ID"),
+        ExportDownloadQuery
+    ),
+    responses(
+        (status = 200, description = "ZIP archive", content_type = "application/zip", body = Vec<u8>),
+        (status = 404, description = "Invalid or expired download link", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "users"
+)]
+#[get("/{export_id}/download")]
+pub async fn download_export(
+    export_service: web::Data<ExportService>,
+    path: web::Path<Uuid>,
+    query: web::Query<ExportDownloadQuery>,
+) -> Result<HttpResponse, AppError> {
+    let export_id = path.into_inner();
+    let archive = export_service.download(export_id, query.expires, &query.signature).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/zip")
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(format!("fitness-export-{}.zip", export_id))],
+        })
+        .body(archive))
+}
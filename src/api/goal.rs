@@ -0,0 +1,167 @@
+use crate::error::AppError;
+use crate::models::{CreateGoalRequest, UpdateGoalRequest};
+use crate::services::GoalService;
+use actix_web::{web, HttpResponse, get, post, put, delete};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Create a new goal
+///
+/// Create a new goal for the authenticated user
+#[utoipa::path(
+    post,
+    path = "/goals",
+    request_body = CreateGoalRequest,
+    responses(
+        (status = 201, description = "Goal created successfully", body = GoalResponse),
+        (status = 400, description = "Invalid request data", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 422, description = "Unknown exercise", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "goals",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[post("")]
+pub async fn create_goal(
+    goal_service: web::Data<GoalService>,
+    user_id: web::ReqData<Uuid>,
+    req: web::Json<CreateGoalRequest>,
+) -> Result<HttpResponse, AppError> {
+    // Validate request
+    req.validate()?;
+
+    let goal = goal_service.create_goal(user_id.into_inner(), req.into_inner()).await?;
+
+    Ok(HttpResponse::Created().json(goal))
+}
+
+/// Get goal details
+///
+/// Get details and progress of a specific goal
+#[utoipa::path(
+    get,
+    path = "/goals/{goal_id}",
+    params(
+        ("goal_id" = Uuid, Path, description = "Goal ID")
+    ),
+    responses(
+        (status = 200, description = "Goal retrieved successfully", body = GoalResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Goal not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "goals",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[get("/{goal_id}")]
+pub async fn get_goal(
+    goal_service: web::Data<GoalService>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let goal = goal_service.get_goal(user_id.into_inner(), path.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(goal))
+}
+
+/// Get all goals
+///
+/// Get all goals for the authenticated user
+#[utoipa::path(
+    get,
+    path = "/goals",
+    responses(
+        (status = 200, description = "Goals retrieved successfully", body = [GoalResponse]),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "goals",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[get("")]
+pub async fn get_goals(
+    goal_service: web::Data<GoalService>,
+    user_id: web::ReqData<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let goals = goal_service.get_goals(user_id.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(goals))
+}
+
+/// Update a goal
+///
+/// Update a specific goal. Fields that are omitted are left unchanged.
+#[utoipa::path(
+    put,
+    path = "/goals/{goal_id}",
+    params(
+        ("goal_id" = Uuid, Path, description = "Goal ID")
+    ),
+    request_body = UpdateGoalRequest,
+    responses(
+        (status = 200, description = "Goal updated successfully", body = GoalResponse),
+        (status = 400, description = "Invalid request data", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Goal not found", body = ErrorResponse),
+        (status = 422, description = "Unknown exercise", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "goals",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[put("/{goal_id}")]
+pub async fn update_goal(
+    goal_service: web::Data<GoalService>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+    req: web::Json<UpdateGoalRequest>,
+) -> Result<HttpResponse, AppError> {
+    // Validate request
+    req.validate()?;
+
+    let goal = goal_service
+        .update_goal(user_id.into_inner(), path.into_inner(), req.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(goal))
+}
+
+/// Delete a goal
+///
+/// Delete a specific goal
+#[utoipa::path(
+    delete,
+    path = "/goals/{goal_id}",
+    params(
+        ("goal_id" = Uuid, Path, description = "Goal ID")
+    ),
+    responses(
+        (status = 204, description = "Goal deleted successfully"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Goal not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "goals",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[delete("/{goal_id}")]
+pub async fn delete_goal(
+    goal_service: web::Data<GoalService>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    goal_service.delete_goal(user_id.into_inner(), path.into_inner()).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
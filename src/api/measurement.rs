@@ -0,0 +1,203 @@
+use crate::error::AppError;
+use crate::models::{CreateMeasurementRequest, UpdateMeasurementRequest, MeasurementFilter, UnitsQuery};
+use crate::services::{MeasurementService, PreferencesService};
+use actix_web::{web, HttpResponse, get, post, put, delete};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Record a measurement
+///
+/// Record a new body measurement for the authenticated user. Weights are sent in
+/// kg, and the response uses the requested units.
+#[utoipa::path(
+    post,
+    path = "/measurements",
+    params(
+        UnitsQuery
+    ),
+    request_body = CreateMeasurementRequest,
+    responses(
+        (status = 201, description = "Measurement recorded successfully", body = Measurement),
+        (status = 400, description = "Invalid request data", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "measurements",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[post("")]
+pub async fn create_measurement(
+    measurement_service: web::Data<MeasurementService>,
+    preferences_service: web::Data<PreferencesService>,
+    user_id: web::ReqData<Uuid>,
+    query: web::Query<UnitsQuery>,
+    req: web::Json<CreateMeasurementRequest>,
+) -> Result<HttpResponse, AppError> {
+    // Validate request
+    req.validate()?;
+
+    let user_id = user_id.into_inner();
+    let units = preferences_service.units(user_id, query.units).await?;
+
+    let mut measurement = measurement_service.create_measurement(user_id, req.into_inner()).await?;
+    measurement.convert_units(units);
+
+    Ok(HttpResponse::Created().json(measurement))
+}
+
+/// Get measurement details
+///
+/// Get a specific body measurement, with weights in kg unless other units are requested
+#[utoipa::path(
+    get,
+    path = "/measurements/{measurement_id}",
+    params(
+        ("measurement_id" = Uuid, Path, description = "Measurement ID"),
+        UnitsQuery
+    ),
+    responses(
+        (status = 200, description = "Measurement retrieved successfully", body = Measurement),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Measurement not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "measurements",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[get("/{measurement_id}")]
+pub async fn get_measurement(
+    measurement_service: web::Data<MeasurementService>,
+    preferences_service: web::Data<PreferencesService>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+    query: web::Query<UnitsQuery>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = user_id.into_inner();
+    let units = preferences_service.units(user_id, query.units).await?;
+
+    let mut measurement = measurement_service.get_measurement(user_id, path.into_inner()).await?;
+    measurement.convert_units(units);
+
+    Ok(HttpResponse::Ok().json(measurement))
+}
+
+/// Get all measurements
+///
+/// Get the authenticated user's measurements, newest first, optionally within a date range
+#[utoipa::path(
+    get,
+    path = "/measurements",
+    params(
+        MeasurementFilter
+    ),
+    responses(
+        (status = 200, description = "Measurements retrieved successfully", body = [Measurement]),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "measurements",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[get("")]
+pub async fn get_measurements(
+    measurement_service: web::Data<MeasurementService>,
+    preferences_service: web::Data<PreferencesService>,
+    user_id: web::ReqData<Uuid>,
+    filter: web::Query<MeasurementFilter>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = user_id.into_inner();
+    let units = preferences_service.units(user_id, filter.units).await?;
+
+    let mut measurements = measurement_service.get_measurements(user_id, filter.into_inner()).await?;
+    for measurement in &mut measurements {
+        measurement.convert_units(units);
+    }
+
+    Ok(HttpResponse::Ok().json(measurements))
+}
+
+/// Update a measurement
+///
+/// Update a specific measurement. Fields that are omitted are left unchanged.
+/// Weights are sent in kg, and the response uses the requested units.
+#[utoipa::path(
+    put,
+    path = "/measurements/{measurement_id}",
+    params(
+        ("measurement_id" = Uuid, Path, description = "Measurement ID"),
+        UnitsQuery
+    ),
+    request_body = UpdateMeasurementRequest,
+    responses(
+        (status = 200, description = "Measurement updated successfully", body = Measurement),
+        (status = 400, description = "Invalid request data", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Measurement not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "measurements",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[put("/{measurement_id}")]
+pub async fn update_measurement(
+    measurement_service: web::Data<MeasurementService>,
+    preferences_service: web::Data<PreferencesService>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+    query: web::Query<UnitsQuery>,
+    req: web::Json<UpdateMeasurementRequest>,
+) -> Result<HttpResponse, AppError> {
+    // Validate request
+    req.validate()?;
+
+    let user_id = user_id.into_inner();
+    let units = preferences_service.units(user_id, query.units).await?;
+
+    let mut measurement = measurement_service
+        .update_measurement(user_id, path.into_inner(), req.into_inner())
+        .await?;
+    measurement.convert_units(units);
+
+    Ok(HttpResponse::Ok().json(measurement))
+}
+
+/// Delete a measurement
+///
+/// Delete a specific measurement
+#[utoipa::path(
+    delete,
+    path = "/measurements/{measurement_id}",
+    params(
+        ("measurement_id" = Uuid, Path, description = "Measurement ID")
+    ),
+    responses(
+        (status = 204, description = "Measurement deleted successfully"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Measurement not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "measurements",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[delete("/{measurement_id}")]
+pub async fn delete_measurement(
+    measurement_service: web::Data<MeasurementService>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    measurement_service
+        .delete_measurement(user_id.into_inner(), path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,162 @@
+use crate::error::AppError;
+use crate::models::{MfaCodeRequest, MfaDisableRequest, MfaVerifyRequest};
+use crate::services::{MfaService, UserService};
+use actix_web::{web, HttpResponse, post};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Start two-factor setup
+///
+/// Generate a TOTP secret for an authenticator app. Two-factor authentication is
+/// only enabled once the secret is confirmed with a code.
+#[utoipa::path(
+    post,
+    path = "/auth/mfa/setup",
+    responses(
+        (status = 200, description = "Secret generated", body = MfaSetupResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 409, description = "Two-factor authentication is already enabled", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "auth",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[post("/setup")]
+pub async fn setup(
+    mfa_service: web::Data<MfaService>,
+    user_id: web::ReqData<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let setup = mfa_service.setup(user_id.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(setup))
+}
+
+/// Enable two-factor authentication
+///
+/// Confirm the secret from setup with a code from the authenticator app. Returns
+/// recovery codes, which are only shown once.
+#[utoipa::path(
+    post,
+    path = "/auth/mfa/confirm",
+    request_body = MfaCodeRequest,
+    responses(
+        (status = 200, description = "Two-factor authentication enabled", body = RecoveryCodesResponse),
+        (status = 400, description = "Invalid code, or setup was not started", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 409, description = "Two-factor authentication is already enabled", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "auth",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[post("/confirm")]
+pub async fn confirm(
+    mfa_service: web::Data<MfaService>,
+    user_id: web::ReqData<Uuid>,
+    req: web::Json<MfaCodeRequest>,
+) -> Result<HttpResponse, AppError> {
+    // Validate request
+    req.validate()?;
+
+    let codes = mfa_service.confirm(user_id.into_inner(), &req.code).await?;
+
+    Ok(HttpResponse::Ok().json(codes))
+}
+
+/// Disable two-factor authentication
+///
+/// Requires the password and a code from the authenticator app or a recovery code
+#[utoipa::path(
+    post,
+    path = "/auth/mfa/disable",
+    request_body = MfaDisableRequest,
+    responses(
+        (status = 204, description = "Two-factor authentication disabled"),
+        (status = 400, description = "Invalid password or code, or not enabled", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "auth",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[post("/disable")]
+pub async fn disable(
+    mfa_service: web::Data<MfaService>,
+    user_id: web::ReqData<Uuid>,
+    req: web::Json<MfaDisableRequest>,
+) -> Result<HttpResponse, AppError> {
+    // Validate request
+    req.validate()?;
+
+    mfa_service.disable(user_id.into_inner(), req.into_inner()).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Regenerate recovery codes
+///
+/// Replace all recovery codes after checking a code from the authenticator app
+/// or a recovery code
+#[utoipa::path(
+    post,
+    path = "/auth/mfa/recovery-codes",
+    request_body = MfaCodeRequest,
+    responses(
+        (status = 200, description = "New recovery codes", body = RecoveryCodesResponse),
+        (status = 400, description = "Invalid code", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "auth",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[post("/recovery-codes")]
+pub async fn regenerate_recovery_codes(
+    mfa_service: web::Data<MfaService>,
+    user_id: web::ReqData<Uuid>,
+    req: web::Json<MfaCodeRequest>,
+) -> Result<HttpResponse, AppError> {
+    // Validate request
+    req.validate()?;
+
+    let codes = mfa_service.regenerate_recovery_codes(user_id.into_inner(), &req.code).await?;
+
+    Ok(HttpResponse::Ok().json(codes))
+}
+
+/// Complete a two-factor login
+///
+/// Exchange the challenge token from login and a code from the authenticator app
+/// or a recovery code for tokens
+#[utoipa::path(
+    post,
+    path = "/auth/mfa/verify",
+    request_body = MfaVerifyRequest,
+    responses(
+        (status = 200, description = "Login successful", body = LoginResponse),
+        (status = 400, description = "Invalid request data", body = ErrorResponse),
+        (status = 401, description = "Invalid code, or invalid or expired challenge", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "auth"
+)]
+#[post("/verify")]
+pub async fn verify(
+    user_service: web::Data<UserService>,
+    req: web::Json<MfaVerifyRequest>,
+) -> Result<HttpResponse, AppError> {
+    // Validate request
+    req.validate()?;
+
+    let login = user_service.verify_mfa(req.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(login))
+}
@@ -1,30 +1,31 @@
-use crate::utils::validate_token;
+use crate::error::AppError;
+use crate::models::SessionId;
+use crate::rate_limit::RateLimiter;
+use crate::services::AuthService;
 use actix_web::{
     dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
     Error, HttpMessage,
 };
 use futures::future::{ready, LocalBoxFuture, Ready};
 use std::rc::Rc;
-use std::task::{Context, Poll};
 use uuid::Uuid;
 
 // JWT auth middleware
+#[derive(Clone)]
 pub struct JwtAuth {
-    jwt_secret: Rc<String>,
+    auth_service: AuthService,
 }
 
 impl JwtAuth {
-    pub fn new(jwt_secret: String) -> Self {
-        Self {
-            jwt_secret: Rc::new(jwt_secret),
-        }
+    pub fn new(auth_service: AuthService) -> Self {
+        Self { auth_service }
     }
 }
 
 // Middleware factory implementation
 impl<S, B> Transform<S, ServiceRequest> for JwtAuth
 where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
     S::Future: 'static,
     B: 'static,
 {
@@ -36,21 +37,21 @@ where
 
     fn new_transform(&self, service: S) -> Self::Future {
         ready(Ok(JwtAuthMiddleware {
-            service,
-            jwt_secret: self.jwt_secret.clone(),
+            service: Rc::new(service),
+            auth_service: self.auth_service.clone(),
         }))
     }
 }
 
 // Middleware service implementation
 pub struct JwtAuthMiddleware<S> {
-    service: S,
-    jwt_secret: Rc<String>,
+    service: Rc<S>,
+    auth_service: AuthService,
 }
 
 impl<S, B> Service<ServiceRequest> for JwtAuthMiddleware<S>
 where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + Clone,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
     S::Future: 'static,
     B: 'static,
 {
@@ -61,7 +62,7 @@ where
     forward_ready!(service);
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
-        let jwt_secret = self.jwt_secret.clone();
+        let auth_service = self.auth_service.clone();
         let service = self.service.clone();
 
         Box::pin(async move {
@@ -70,41 +71,117 @@ where
                 .headers()
                 .get("Authorization")
                 .and_then(|h| h.to_str().ok())
-                .and_then(|h| {
-                    if h.starts_with("Bearer ") {
-                        Some(h[7..].to_string())
-                    } else {
-                        None
-                    }
-                });
+                .and_then(|h| h.strip_prefix("Bearer "))
+                .map(|h| h.to_string());
 
             // If no token is present, return 401 Unauthorized
             let token = match auth_header {
                 Some(t) => t,
                 None => {
-                    return Err(actix_web::error::ErrorUnauthorized("No token provided"));
+                    return Err(AppError::Unauthorized("No token provided".to_string()).into());
                 }
             };
 
-            // Validate the token
-            match validate_token(&token, &jwt_secret) {
-                Ok(claims) => {
-                    // Parse the user ID from the token
-                    let user_id = match Uuid::parse_str(&claims.sub) {
-                        Ok(id) => id,
-                        Err(_) => {
-                            return Err(actix_web::error::ErrorUnauthorized("Invalid token"));
-                        }
-                    };
-                    
-                    // Add the user ID to the request extensions
-                    req.extensions_mut().insert(user_id);
-                    
-                    // Continue with the request
-                    service.call(req).await
-                }
-                Err(_) => Err(actix_web::error::ErrorUnauthorized("Invalid token")),
+            // Validate the token and check that its session has not been revoked
+            let (user_id, session_id) = auth_service.authenticate(&token).await?;
+
+            // Add the user ID and session ID to the request extensions
+            req.extensions_mut().insert(user_id);
+            req.extensions_mut().insert(SessionId(session_id));
+
+            // Continue with the request
+            service.call(req).await
+        })
+    }
+}
+
+/// Which client a rate limit counts requests for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitKey {
+    /// The client IP address
+    Ip,
+    /// The authenticated user, or the IP address for anonymous requests.
+    /// Must run inside `JwtAuth` to see the user.
+    User,
+}
+
+// Rate limit middleware
+#[derive(Clone)]
+pub struct RateLimit {
+    limiter: RateLimiter,
+    key: RateLimitKey,
+    trust_proxy: bool,
+}
+
+impl RateLimit {
+    pub fn new(limiter: RateLimiter, key: RateLimitKey, trust_proxy: bool) -> Self {
+        Self { limiter, key, trust_proxy }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RateLimitMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimitMiddleware {
+            service: Rc::new(service),
+            config: self.clone(),
+        }))
+    }
+}
+
+pub struct RateLimitMiddleware<S> {
+    service: Rc<S>,
+    config: RateLimit,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let limiter = self.config.limiter.clone();
+        let service = self.service.clone();
+
+        let user_id = match self.config.key {
+            RateLimitKey::User => req.extensions().get::<Uuid>().copied(),
+            RateLimitKey::Ip => None,
+        };
+
+        let client = match user_id {
+            Some(user_id) => format!("user:{}", user_id),
+            None => {
+                let ip = if self.config.trust_proxy {
+                    req.connection_info().realip_remote_addr().map(|ip| ip.to_string())
+                } else {
+                    req.peer_addr().map(|addr| addr.ip().to_string())
+                };
+
+                format!("ip:{}", ip.unwrap_or_else(|| "unknown".to_string()))
             }
+        };
+
+        Box::pin(async move {
+            limiter.check(&client).await?;
+
+            service.call(req).await
         })
     }
 }
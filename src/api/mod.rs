@@ -1,5 +1,12 @@
 pub mod auth;
+pub mod exercise;
+pub mod export;
+pub mod goal;
+pub mod measurement;
+pub mod mfa;
 pub mod middleware;
+pub mod record;
+pub mod stats;
 pub mod user;
 pub mod workout;
 pub mod docs;
@@ -13,20 +20,102 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
         web::scope("/auth")
             .service(auth::register)
             .service(auth::login)
+            .service(auth::refresh)
+            .service(auth::forgot_password)
+            .service(auth::reset_password)
+            .service(auth::verify_email)
+            .service(auth::logout)
+            .service(auth::logout_all)
+            .service(
+                web::scope("/mfa")
+                    .service(mfa::verify)
+                    .service(mfa::setup)
+                    .service(mfa::confirm)
+                    .service(mfa::disable)
+                    .service(mfa::regenerate_recovery_codes)
+            )
     );
     
     // User routes
     cfg.service(
         web::scope("/users")
             .service(user::get_profile)
+            .service(user::update_profile)
+            .service(user::change_password)
+            .service(user::delete_account)
+            .service(user::get_preferences)
+            .service(user::update_preferences)
+            .service(export::request_export)
+            .service(export::get_export)
+    );
+    
+    // Data export downloads, authorized by the signed link
+    cfg.service(
+        web::scope("/exports")
+            .service(export::download_export)
     );
     
     // Workout routes
     cfg.service(
         web::scope("/workouts")
             .service(workout::create_workout)
+            .service(workout::export_workouts_csv)
+            .service(workout::import_workouts)
             .service(workout::get_workout)
+            .service(workout::get_workout_route)
+            .service(workout::get_workout_laps)
+            .service(workout::get_workout_samples)
             .service(workout::get_workouts)
             .service(workout::delete_workout)
+            .service(workout::update_workout)
+            .service(workout::patch_workout)
+            .service(workout::add_workout_exercise)
+            .service(workout::update_workout_exercise)
+            .service(workout::reorder_workout_exercises)
+            .service(workout::remove_workout_exercise)
+    );
+    
+    // Goal routes
+    cfg.service(
+        web::scope("/goals")
+            .service(goal::create_goal)
+            .service(goal::get_goal)
+            .service(goal::get_goals)
+            .service(goal::update_goal)
+            .service(goal::delete_goal)
+    );
+    
+    // Measurement routes
+    cfg.service(
+        web::scope("/measurements")
+            .service(measurement::create_measurement)
+            .service(measurement::get_measurement)
+            .service(measurement::get_measurements)
+            .service(measurement::update_measurement)
+            .service(measurement::delete_measurement)
+    );
+    
+    // Exercise catalog routes
+    cfg.service(
+        web::scope("/exercises")
+            .service(exercise::list_exercises)
+            .service(exercise::get_exercise)
+            .service(exercise::create_exercise)
+            .service(exercise::get_exercise_records)
+            .service(exercise::get_exercise_progression)
+    );
+    
+    // Personal record routes
+    cfg.service(
+        web::scope("/records")
+            .service(record::get_records)
+    );
+    
+    // Statistics routes
+    cfg.service(
+        web::scope("/stats")
+            .service(stats::get_summary)
+            .service(stats::get_streaks)
+            .service(stats::get_activity)
     );
 }
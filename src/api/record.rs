@@ -0,0 +1,35 @@
+use crate::error::AppError;
+use crate::models::RecordFilter;
+use crate::services::RecordService;
+use actix_web::{web, HttpResponse, get};
+use uuid::Uuid;
+
+/// Get current personal records
+///
+/// Get the authenticated user's current personal records across all exercises
+#[utoipa::path(
+    get,
+    path = "/records",
+    params(
+        RecordFilter
+    ),
+    responses(
+        (status = 200, description = "Records retrieved successfully", body = [PersonalRecord]),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "records",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[get("")]
+pub async fn get_records(
+    record_service: web::Data<RecordService>,
+    user_id: web::ReqData<Uuid>,
+    filter: web::Query<RecordFilter>,
+) -> Result<HttpResponse, AppError> {
+    let records = record_service.get_records(user_id.into_inner(), filter.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(records))
+}
@@ -0,0 +1,108 @@
+use crate::error::AppError;
+use crate::models::{SummaryFilter, StreakFilter, ActivityFilter};
+use crate::services::StatsService;
+use actix_web::{web, HttpResponse, get};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Get training summary
+///
+/// Get workout totals, tonnage, distance, training frequency and breakdowns by
+/// exercise category and muscle group for the authenticated user
+#[utoipa::path(
+    get,
+    path = "/stats/summary",
+    params(
+        SummaryFilter
+    ),
+    responses(
+        (status = 200, description = "Summary retrieved successfully", body = StatsSummary),
+        (status = 400, description = "Invalid range", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "stats",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[get("/summary")]
+pub async fn get_summary(
+    stats_service: web::Data<StatsService>,
+    user_id: web::ReqData<Uuid>,
+    filter: web::Query<SummaryFilter>,
+) -> Result<HttpResponse, AppError> {
+    let summary = stats_service.summary(user_id.into_inner(), filter.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(summary))
+}
+
+/// Get workout streaks
+///
+/// Get the current and longest daily and weekly workout streaks of the authenticated user.
+/// Days are computed in the given timezone, so streaks don't break at UTC midnight.
+#[utoipa::path(
+    get,
+    path = "/stats/streaks",
+    params(
+        StreakFilter
+    ),
+    responses(
+        (status = 200, description = "Streaks retrieved successfully", body = StreakStats),
+        (status = 400, description = "Invalid query parameters or unknown timezone", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "stats",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[get("/streaks")]
+pub async fn get_streaks(
+    stats_service: web::Data<StatsService>,
+    user_id: web::ReqData<Uuid>,
+    filter: web::Query<StreakFilter>,
+) -> Result<HttpResponse, AppError> {
+    // Validate query parameters
+    filter.validate()?;
+
+    let streaks = stats_service.streaks(user_id.into_inner(), filter.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(streaks))
+}
+
+/// Get activity calendar
+///
+/// Get the number of workouts, duration and volume for every day in a range,
+/// for drawing an activity heatmap
+#[utoipa::path(
+    get,
+    path = "/stats/activity",
+    params(
+        ActivityFilter
+    ),
+    responses(
+        (status = 200, description = "Activity retrieved successfully", body = ActivityCalendar),
+        (status = 400, description = "Invalid query parameters, range or unknown timezone", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "stats",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[get("/activity")]
+pub async fn get_activity(
+    stats_service: web::Data<StatsService>,
+    user_id: web::ReqData<Uuid>,
+    filter: web::Query<ActivityFilter>,
+) -> Result<HttpResponse, AppError> {
+    // Validate query parameters
+    filter.validate()?;
+
+    let activity = stats_service.activity(user_id.into_inner(), filter.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(activity))
+}
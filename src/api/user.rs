@@ -1,7 +1,11 @@
-use crate::services::UserService;
-use actix_web::{web, HttpResponse, Responder, get};
+use crate::error::AppError;
+use crate::models::{
+    UpdateProfileRequest, ChangePasswordRequest, DeleteAccountRequest, SessionId, UpdatePreferencesRequest,
+};
+use crate::services::{PreferencesService, UserService};
+use actix_web::{web, HttpResponse, get, patch, post, delete};
 use uuid::Uuid;
-use utoipa::OpenApi;
+use validator::Validate;
 
 /// Get the current user's profile
 ///
@@ -11,9 +15,9 @@ use utoipa::OpenApi;
     path = "/users/profile",
     responses(
         (status = 200, description = "User profile retrieved successfully", body = UserProfileResponse),
-        (status = 404, description = "User not found"),
+        (status = 404, description = "User not found", body = ErrorResponse),
         (status = 401, description = "Unauthorized"),
-        (status = 500, description = "Internal server error")
+        (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     tag = "users",
     security(
@@ -24,15 +28,178 @@ use utoipa::OpenApi;
 pub async fn get_profile(
     user_service: web::Data<UserService>,
     user_id: web::ReqData<Uuid>,
-) -> impl Responder {
+) -> Result<HttpResponse, AppError> {
     // Get user ID from request data (set by JWT middleware)
     let user_id = user_id.into_inner();
     
     // Get user profile
-    match user_service.get_profile(user_id).await {
-        Ok(profile) => HttpResponse::Ok().json(profile),
-        Err(_) => HttpResponse::NotFound().json(serde_json::json!({
-            "error": "User not found"
-        }))
-    }
+    let profile = user_service.get_profile(user_id).await?;
+    
+    Ok(HttpResponse::Ok().json(profile))
+}
+
+/// Update the current user's profile
+///
+/// Only the fields present in the request are changed. A new email address has
+/// to be verified again.
+#[utoipa::path(
+    patch,
+    path = "/users/profile",
+    request_body = UpdateProfileRequest,
+    responses(
+        (status = 200, description = "User profile updated successfully", body = UserProfileResponse),
+        (status = 400, description = "Invalid request data", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 409, description = "Email or username is already taken", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "users",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[patch("/profile")]
+pub async fn update_profile(
+    user_service: web::Data<UserService>,
+    user_id: web::ReqData<Uuid>,
+    req: web::Json<UpdateProfileRequest>,
+) -> Result<HttpResponse, AppError> {
+    // Validate request
+    req.validate()?;
+    
+    let profile = user_service.update_profile(user_id.into_inner(), req.into_inner()).await?;
+    
+    Ok(HttpResponse::Ok().json(profile))
+}
+
+/// Change password
+///
+/// Requires the current password. Every other session is logged out, the
+/// current one stays logged in.
+#[utoipa::path(
+    post,
+    path = "/users/password",
+    request_body = ChangePasswordRequest,
+    responses(
+        (status = 204, description = "Password changed successfully"),
+        (status = 400, description = "Invalid request data or current password", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "users",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[post("/password")]
+pub async fn change_password(
+    user_service: web::Data<UserService>,
+    user_id: web::ReqData<Uuid>,
+    session_id: web::ReqData<SessionId>,
+    req: web::Json<ChangePasswordRequest>,
+) -> Result<HttpResponse, AppError> {
+    // Validate request
+    req.validate()?;
+    
+    user_service
+        .change_password(user_id.into_inner(), session_id.into_inner().0, req.into_inner())
+        .await?;
+    
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Delete account
+///
+/// Permanently delete the account with all workouts, goals, measurements and
+/// custom exercises. Requires the password, and a two-factor code when two-factor
+/// authentication is enabled.
+#[utoipa::path(
+    delete,
+    path = "/users/me",
+    request_body = DeleteAccountRequest,
+    responses(
+        (status = 204, description = "Account deleted successfully"),
+        (status = 400, description = "Invalid password or two-factor code", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "users",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[delete("/me")]
+pub async fn delete_account(
+    user_service: web::Data<UserService>,
+    user_id: web::ReqData<Uuid>,
+    req: web::Json<DeleteAccountRequest>,
+) -> Result<HttpResponse, AppError> {
+    // Validate request
+    req.validate()?;
+    
+    user_service.delete_account(user_id.into_inner(), req.into_inner()).await?;
+    
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Get preferences
+///
+/// Get the authenticated user's unit system, timezone, first day of the week,
+/// default rest timer and locale
+#[utoipa::path(
+    get,
+    path = "/users/preferences",
+    responses(
+        (status = 200, description = "Preferences retrieved successfully", body = UserPreferences),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "users",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[get("/preferences")]
+pub async fn get_preferences(
+    preferences_service: web::Data<PreferencesService>,
+    user_id: web::ReqData<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let preferences = preferences_service.get(user_id.into_inner()).await?;
+    
+    Ok(HttpResponse::Ok().json(preferences))
+}
+
+/// Update preferences
+///
+/// Only the fields present in the request are changed. Stored data is not
+/// affected; preferences only change how responses are presented.
+#[utoipa::path(
+    patch,
+    path = "/users/preferences",
+    request_body = UpdatePreferencesRequest,
+    responses(
+        (status = 200, description = "Preferences updated successfully", body = UserPreferences),
+        (status = 400, description = "Invalid request data or unknown timezone", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "users",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[patch("/preferences")]
+pub async fn update_preferences(
+    preferences_service: web::Data<PreferencesService>,
+    user_id: web::ReqData<Uuid>,
+    req: web::Json<UpdatePreferencesRequest>,
+) -> Result<HttpResponse, AppError> {
+    // Validate request
+    req.validate()?;
+    
+    let preferences = preferences_service.update(user_id.into_inner(), req.into_inner()).await?;
+    
+    Ok(HttpResponse::Ok().json(preferences))
 }
@@ -1,9 +1,14 @@
-use crate::models::CreateWorkoutRequest;
-use crate::services::WorkoutService;
-use actix_web::{web, HttpResponse, Responder, get, post, delete};
+use crate::error::AppError;
+use crate::models::{
+    PersonalRecord, CreateWorkoutRequest, PatchWorkoutRequest, WorkoutExerciseInput,
+    UpdateWorkoutExerciseRequest, ReorderWorkoutExercisesRequest, WorkoutFilter, UnitsQuery, ImportQuery,
+};
+use crate::services::{ImportService, PreferencesService, WorkoutService};
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{web, HttpResponse, get, post, put, patch, delete};
+use futures::StreamExt;
 use uuid::Uuid;
 use validator::Validate;
-use utoipa::OpenApi;
 
 /// Create a new workout
 ///
@@ -14,9 +19,10 @@ use utoipa::OpenApi;
     request_body = CreateWorkoutRequest,
     responses(
         (status = 201, description = "Workout created successfully", body = CreateWorkoutResponse),
-        (status = 400, description = "Invalid request data"),
+        (status = 400, description = "Invalid request data", body = ErrorResponse),
         (status = 401, description = "Unauthorized"),
-        (status = 500, description = "Internal server error")
+        (status = 422, description = "Unknown exercise", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     tag = "workouts",
     security(
@@ -28,40 +34,35 @@ pub async fn create_workout(
     workout_service: web::Data<WorkoutService>,
     user_id: web::ReqData<Uuid>,
     req: web::Json<CreateWorkoutRequest>,
-) -> impl Responder {
+) -> Result<HttpResponse, AppError> {
     // Validate request
-    if let Err(errors) = req.validate() {
-        return HttpResponse::BadRequest().json(errors);
-    }
+    req.validate()?;
     
     // Get user ID from request data (set by JWT middleware)
     let user_id = user_id.into_inner();
     
     // Create workout
-    match workout_service.create_workout(user_id, req.into_inner()).await {
-        Ok(workout_id) => HttpResponse::Created().json(serde_json::json!({
-            "id": workout_id
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": format!("Failed to create workout: {}", e)
-        }))
-    }
+    let (id, records) = workout_service.create_workout(user_id, req.into_inner()).await?;
+    
+    Ok(HttpResponse::Created().json(CreateWorkoutResponse { id, records }))
 }
 
 /// Get workout details
 ///
-/// Get details of a specific workout
+/// Get details of a specific workout, with weights in kg and distances in km
+/// unless other units are requested
 #[utoipa::path(
     get,
     path = "/workouts/{workout_id}",
     params(
-        ("workout_id" = Uuid, Path, description = "Workout ID")
+        ("workout_id" = Uuid, Path, description = "Workout ID"),
+        UnitsQuery
     ),
     responses(
         (status = 200, description = "Workout details retrieved successfully", body = WorkoutDetailsResponse),
         (status = 401, description = "Unauthorized"),
-        (status = 404, description = "Workout not found"),
-        (status = 500, description = "Internal server error")
+        (status = 404, description = "Workout not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     tag = "workouts",
     security(
@@ -71,38 +72,43 @@ pub async fn create_workout(
 #[get("/{workout_id}")]
 pub async fn get_workout(
     workout_service: web::Data<WorkoutService>,
+    preferences_service: web::Data<PreferencesService>,
     user_id: web::ReqData<Uuid>,
     path: web::Path<Uuid>,
-) -> impl Responder {
+    query: web::Query<UnitsQuery>,
+) -> Result<HttpResponse, AppError> {
     let user_id = user_id.into_inner();
     let workout_id = path.into_inner();
     
-    match workout_service.get_workout(user_id, workout_id).await {
-        Ok(workout) => HttpResponse::Ok().json(workout),
-        Err(e) => {
-            if e.to_string().contains("not found") {
-                return HttpResponse::NotFound().json(serde_json::json!({
-                    "error": "Workout not found"
-                }));
-            }
-            
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to get workout"
-            }))
-        }
-    }
+    let units = preferences_service.units(user_id, query.units).await?;
+    
+    let mut workout = workout_service.get_workout(user_id, workout_id).await?;
+    workout.convert_units(units);
+    
+    Ok(HttpResponse::Ok().json(workout))
 }
 
 /// Get all workouts
 ///
-/// Get all workouts for the authenticated user
+/// Get the authenticated user's workouts, newest first by default. Results are
+/// paginated with an opaque cursor: pass the `X-Next-Cursor` response header as
+/// `cursor` to get the next page. `X-Total-Count` holds the number of workouts
+/// matching the filters across all pages. With `include=exercises` each workout
+/// is returned with its exercises and sets, as in the workout details endpoint.
 #[utoipa::path(
     get,
     path = "/workouts",
+    params(WorkoutFilter),
     responses(
-        (status = 200, description = "Workouts retrieved successfully", body = [Workout]),
+        (status = 200, description = "Workouts retrieved successfully", body = WorkoutList,
+            headers(
+                ("X-Total-Count" = i64, description = "Number of workouts matching the filters"),
+                ("X-Next-Cursor" = String, description = "Cursor for the next page, absent on the last page")
+            )
+        ),
+        (status = 400, description = "Invalid filter or cursor", body = ErrorResponse),
         (status = 401, description = "Unauthorized"),
-        (status = 500, description = "Internal server error")
+        (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     tag = "workouts",
     security(
@@ -112,16 +118,28 @@ pub async fn get_workout(
 #[get("")]
 pub async fn get_workouts(
     workout_service: web::Data<WorkoutService>,
+    preferences_service: web::Data<PreferencesService>,
     user_id: web::ReqData<Uuid>,
-) -> impl Responder {
+    filter: web::Query<WorkoutFilter>,
+) -> Result<HttpResponse, AppError> {
+    // Validate filter
+    filter.validate()?;
+    
     let user_id = user_id.into_inner();
     
-    match workout_service.get_workouts(user_id).await {
-        Ok(workouts) => HttpResponse::Ok().json(workouts),
-        Err(_) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": "Failed to get workouts"
-        }))
+    let units = preferences_service.units(user_id, filter.units).await?;
+    
+    let mut page = workout_service.get_workouts(user_id, filter.into_inner()).await?;
+    page.workouts.convert_units(units);
+    
+    let mut response = HttpResponse::Ok();
+    response.insert_header(("X-Total-Count", page.total.to_string()));
+    
+    if let Some(cursor) = page.next_cursor {
+        response.insert_header(("X-Next-Cursor", cursor));
     }
+    
+    Ok(response.json(page.workouts))
 }
 
 /// Delete a workout
@@ -136,8 +154,8 @@ pub async fn get_workouts(
     responses(
         (status = 204, description = "Workout deleted successfully"),
         (status = 401, description = "Unauthorized"),
-        (status = 404, description = "Workout not found"),
-        (status = 500, description = "Internal server error")
+        (status = 404, description = "Workout not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     tag = "workouts",
     security(
@@ -149,28 +167,466 @@ pub async fn delete_workout(
     workout_service: web::Data<WorkoutService>,
     user_id: web::ReqData<Uuid>,
     path: web::Path<Uuid>,
-) -> impl Responder {
+) -> Result<HttpResponse, AppError> {
+    let user_id = user_id.into_inner();
+    let workout_id = path.into_inner();
+    
+    workout_service.delete_workout(user_id, workout_id).await?;
+    
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Replace a workout
+///
+/// Replace a workout's fields and all of its exercise entries
+#[utoipa::path(
+    put,
+    path = "/workouts/{workout_id}",
+    params(
+        ("workout_id" = Uuid, Path, description = "Workout ID")
+    ),
+    request_body = CreateWorkoutRequest,
+    responses(
+        (status = 200, description = "Workout updated successfully", body = WorkoutDetailsResponse),
+        (status = 400, description = "Invalid request data", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Workout not found", body = ErrorResponse),
+        (status = 422, description = "Unknown exercise", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "workouts",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[put("/{workout_id}")]
+pub async fn update_workout(
+    workout_service: web::Data<WorkoutService>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+    req: web::Json<CreateWorkoutRequest>,
+) -> Result<HttpResponse, AppError> {
+    // Validate request
+    req.validate()?;
+    
+    let user_id = user_id.into_inner();
+    let workout_id = path.into_inner();
+    
+    let workout = workout_service.update_workout(user_id, workout_id, req.into_inner()).await?;
+    
+    Ok(HttpResponse::Ok().json(workout))
+}
+
+/// Update workout fields
+///
+/// Update some of a workout's fields without touching its exercises. Omitted fields are left unchanged.
+#[utoipa::path(
+    patch,
+    path = "/workouts/{workout_id}",
+    params(
+        ("workout_id" = Uuid, Path, description = "Workout ID")
+    ),
+    request_body = PatchWorkoutRequest,
+    responses(
+        (status = 200, description = "Workout updated successfully", body = WorkoutDetailsResponse),
+        (status = 400, description = "Invalid request data", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Workout not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "workouts",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[patch("/{workout_id}")]
+pub async fn patch_workout(
+    workout_service: web::Data<WorkoutService>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+    req: web::Json<PatchWorkoutRequest>,
+) -> Result<HttpResponse, AppError> {
+    // Validate request
+    req.validate()?;
+    
+    let user_id = user_id.into_inner();
+    let workout_id = path.into_inner();
+    
+    let workout = workout_service.patch_workout(user_id, workout_id, req.into_inner()).await?;
+    
+    Ok(HttpResponse::Ok().json(workout))
+}
+
+/// Add an exercise to a workout
+///
+/// Append an exercise entry to the end of a workout
+#[utoipa::path(
+    post,
+    path = "/workouts/{workout_id}/exercises",
+    params(
+        ("workout_id" = Uuid, Path, description = "Workout ID")
+    ),
+    request_body = WorkoutExerciseInput,
+    responses(
+        (status = 201, description = "Exercise added successfully", body = WorkoutDetailsResponse),
+        (status = 400, description = "Invalid request data", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Workout not found", body = ErrorResponse),
+        (status = 422, description = "Unknown exercise", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "workouts",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[post("/{workout_id}/exercises")]
+pub async fn add_workout_exercise(
+    workout_service: web::Data<WorkoutService>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+    req: web::Json<WorkoutExerciseInput>,
+) -> Result<HttpResponse, AppError> {
+    // Validate request
+    req.validate()?;
+    
+    let user_id = user_id.into_inner();
+    let workout_id = path.into_inner();
+    
+    let workout = workout_service.add_workout_exercise(user_id, workout_id, req.into_inner()).await?;
+    
+    Ok(HttpResponse::Created().json(workout))
+}
+
+/// Update a workout exercise
+///
+/// Update an exercise entry of a workout. Omitted fields are left unchanged.
+#[utoipa::path(
+    patch,
+    path = "/workouts/{workout_id}/exercises/{workout_exercise_id}",
+    params(
+        ("workout_id" = Uuid, Path, description = "Workout ID"),
+        ("workout_exercise_id" = Uuid, Path, description = "Workout exercise entry ID")
+    ),
+    request_body = UpdateWorkoutExerciseRequest,
+    responses(
+        (status = 200, description = "Exercise updated successfully", body = WorkoutDetailsResponse),
+        (status = 400, description = "Invalid request data", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Workout or workout exercise not found", body = ErrorResponse),
+        (status = 422, description = "Unknown exercise", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "workouts",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[patch("/{workout_id}/exercises/{workout_exercise_id}")]
+pub async fn update_workout_exercise(
+    workout_service: web::Data<WorkoutService>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<(Uuid, Uuid)>,
+    req: web::Json<UpdateWorkoutExerciseRequest>,
+) -> Result<HttpResponse, AppError> {
+    // Validate request
+    req.validate()?;
+    
+    let user_id = user_id.into_inner();
+    let (workout_id, workout_exercise_id) = path.into_inner();
+    
+    let workout = workout_service
+        .update_workout_exercise(user_id, workout_id, workout_exercise_id, req.into_inner())
+        .await?;
+    
+    Ok(HttpResponse::Ok().json(workout))
+}
+
+/// Reorder workout exercises
+///
+/// Set the order of a workout's exercise entries. Every entry must be listed exactly once.
+#[utoipa::path(
+    put,
+    path = "/workouts/{workout_id}/exercises/order",
+    params(
+        ("workout_id" = Uuid, Path, description = "Workout ID")
+    ),
+    request_body = ReorderWorkoutExercisesRequest,
+    responses(
+        (status = 200, description = "Exercises reordered successfully", body = WorkoutDetailsResponse),
+        (status = 400, description = "The new order does not match the workout's exercises", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Workout not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "workouts",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[put("/{workout_id}/exercises/order")]
+pub async fn reorder_workout_exercises(
+    workout_service: web::Data<WorkoutService>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+    req: web::Json<ReorderWorkoutExercisesRequest>,
+) -> Result<HttpResponse, AppError> {
     let user_id = user_id.into_inner();
     let workout_id = path.into_inner();
     
-    match workout_service.delete_workout(user_id, workout_id).await {
-        Ok(_) => HttpResponse::NoContent().finish(),
-        Err(e) => {
-            if e.to_string().contains("not found") {
-                return HttpResponse::NotFound().json(serde_json::json!({
-                    "error": "Workout not found"
-                }));
-            }
-            
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to delete workout"
-            }))
+    let workout = workout_service
+        .reorder_workout_exercises(user_id, workout_id, req.into_inner().workout_exercise_ids)
+        .await?;
+    
+    Ok(HttpResponse::Ok().json(workout))
+}
+
+/// Remove an exercise from a workout
+///
+/// Remove an exercise entry from a workout
+#[utoipa::path(
+    delete,
+    path = "/workouts/{workout_id}/exercises/{workout_exercise_id}",
+    params(
+        ("workout_id" = Uuid, Path, description = "Workout ID"),
+        ("workout_exercise_id" = Uuid, Path, description = "Workout exercise entry ID")
+    ),
+    responses(
+        (status = 200, description = "Exercise removed successfully", body = WorkoutDetailsResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Workout or workout exercise not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "workouts",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[delete("/{workout_id}/exercises/{workout_exercise_id}")]
+pub async fn remove_workout_exercise(
+    workout_service: web::Data<WorkoutService>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = user_id.into_inner();
+    let (workout_id, workout_exercise_id) = path.into_inner();
+    
+    let workout = workout_service
+        .remove_workout_exercise(user_id, workout_id, workout_exercise_id)
+        .await?;
+    
+    Ok(HttpResponse::Ok().json(workout))
+}
+
+/// Largest file accepted by the workout import, in bytes
+const MAX_IMPORT_SIZE: usize = 10 * 1024 * 1024;
+
+/// Export workout history as CSV
+///
+/// Export all workouts with one row per set, in the columns of Strong's CSV
+/// export: Date, Workout Name, Duration, Exercise Name, Set Order, Weight, Reps,
+/// Distance, Seconds, Notes, Workout Notes and RPE. Dates are in the user's
+/// timezone, weights in kg and distances in km unless other units are requested.
+#[utoipa::path(
+    get,
+    path = "/workouts/export.csv",
+    params(UnitsQuery),
+    responses(
+        (status = 200, description = "CSV file", content_type = "text/csv", body = String),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "workouts",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[get("/export.csv")]
+pub async fn export_workouts_csv(
+    import_service: web::Data<ImportService>,
+    user_id: web::ReqData<Uuid>,
+    query: web::Query<UnitsQuery>,
+) -> Result<HttpResponse, AppError> {
+    let csv = import_service.export_csv(user_id.into_inner(), query.units).await?;
+    
+    Ok(HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename("workouts.csv".to_string())],
+        })
+        .body(csv))
+}
+
+/// Import workout history from CSV, GPX, TCX or FIT
+///
+/// Import workouts from a CSV file with one row per set, like the exports of
+/// Strong, Hevy or `GET /workouts/export.csv`, or cardio workouts from the GPS
+/// tracks of a GPX or TCX file or the sessions of a Garmin FIT activity file,
+/// sent as the request body. FIT files also bring laps and heart rate, power
+/// and cadence samples. Exercise names are
+/// matched onto existing exercises by similarity, and workouts with the same
+/// name and start time as an existing one are skipped. Rows and track points
+/// that cannot be read are reported and left out. With `dry_run` nothing is
+/// saved and the response previews the import.
+#[utoipa::path(
+    post,
+    path = "/workouts/import",
+    params(ImportQuery),
+    request_body(content = String, content_type = "text/csv", description = "CSV, GPX, TCX or FIT file of at most 10 MB"),
+    responses(
+        (status = 200, description = "Import result or preview", body = WorkoutImportResponse),
+        (status = 400, description = "Unreadable file or missing columns", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "workouts",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[post("/import")]
+pub async fn import_workouts(
+    import_service: web::Data<ImportService>,
+    user_id: web::ReqData<Uuid>,
+    query: web::Query<ImportQuery>,
+    mut payload: web::Payload,
+) -> Result<HttpResponse, AppError> {
+    let mut data = Vec::new();
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk.map_err(|e| AppError::BadRequest(format!("Failed to read the file: {}", e)))?;
+        if data.len() + chunk.len() > MAX_IMPORT_SIZE {
+            return Err(AppError::BadRequest("The file is larger than 10 MB".to_string()));
         }
+        data.extend_from_slice(&chunk);
     }
+    
+    let response = import_service
+        .import_file(user_id.into_inner(), &data, query.into_inner())
+        .await?;
+    
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Get the GPS route of a workout
+///
+/// Get the route of a workout imported from a GPX, TCX or FIT file as a GeoJSON
+/// feature. Its properties hold the distance, moving and elapsed time,
+/// elevation gain, average pace and splits per km, or per mile in imperial units.
+#[utoipa::path(
+    get,
+    path = "/workouts/{workout_id}/route",
+    params(
+        ("workout_id" = Uuid, Path, description = "Workout ID"),
+        UnitsQuery
+    ),
+    responses(
+        (status = 200, description = "Route retrieved successfully", content_type = "application/geo+json", body = RouteFeature),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Workout not found or without a route", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "workouts",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[get("/{workout_id}/route")]
+pub async fn get_workout_route(
+    workout_service: web::Data<WorkoutService>,
+    preferences_service: web::Data<PreferencesService>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+    query: web::Query<UnitsQuery>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = user_id.into_inner();
+    let units = preferences_service.units(user_id, query.units).await?;
+    
+    let route = workout_service.get_route(user_id, path.into_inner(), units).await?;
+    
+    Ok(HttpResponse::Ok()
+        .content_type("application/geo+json")
+        .json(route))
+}
+
+/// Get the laps of a workout
+///
+/// Get the laps recorded by the device of a workout imported from a FIT file,
+/// with distances and paces per km, or per mile in imperial units. Workouts
+/// without laps have an empty list.
+#[utoipa::path(
+    get,
+    path = "/workouts/{workout_id}/laps",
+    params(
+        ("workout_id" = Uuid, Path, description = "Workout ID"),
+        UnitsQuery
+    ),
+    responses(
+        (status = 200, description = "Laps retrieved successfully", body = Vec<WorkoutLap>),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Workout not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "workouts",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[get("/{workout_id}/laps")]
+pub async fn get_workout_laps(
+    workout_service: web::Data<WorkoutService>,
+    preferences_service: web::Data<PreferencesService>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+    query: web::Query<UnitsQuery>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = user_id.into_inner();
+    let units = preferences_service.units(user_id, query.units).await?;
+    
+    let laps = workout_service.get_laps(user_id, path.into_inner(), units).await?;
+    
+    Ok(HttpResponse::Ok().json(laps))
+}
+
+/// Get the sensor samples of a workout
+///
+/// Get the heart rate, power and cadence recorded by the device of a workout
+/// imported from a FIT file, in time order. Workouts without samples have an
+/// empty list.
+#[utoipa::path(
+    get,
+    path = "/workouts/{workout_id}/samples",
+    params(
+        ("workout_id" = Uuid, Path, description = "Workout ID")
+    ),
+    responses(
+        (status = 200, description = "Samples retrieved successfully", body = Vec<WorkoutSample>),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Workout not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "workouts",
+    security(
+        ("jwt_auth" = [])
+    )
+)]
+#[get("/{workout_id}/samples")]
+pub async fn get_workout_samples(
+    workout_service: web::Data<WorkoutService>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let samples = workout_service
+        .get_samples(user_id.into_inner(), path.into_inner())
+        .await?;
+    
+    Ok(HttpResponse::Ok().json(samples))
 }
 
 // Define a type for create workout response for Swagger documentation
 #[derive(serde::Serialize, utoipa::ToSchema)]
 pub struct CreateWorkoutResponse {
     id: Uuid,
+    /// Personal records set in this workout
+    records: Vec<PersonalRecord>,
 }
@@ -1,3 +1,4 @@
+use crate::rate_limit::{Quota, StoreKind};
 use serde::Deserialize;
 use std::env;
 use anyhow::Result;
@@ -13,8 +14,85 @@ pub struct AppConfig {
     pub database_url: String,
     /// JWT secret for authentication
     pub jwt_secret: String,
-    /// JWT token expiration time in seconds
+    /// JWT access token expiration time in seconds
     pub jwt_expiration: u64,
+    /// Refresh token expiration time in seconds
+    pub refresh_token_expiration: u64,
+    /// Apply pending database migrations on startup
+    pub run_migrations: bool,
+    /// Base URL of the app, used for links in emails
+    pub app_url: String,
+    /// SMTP connection URL. Emails are not sent when it is not set.
+    pub smtp_url: Option<String>,
+    /// Log emails, including their links, when `smtp_url` is not set. Only meant for development.
+    pub mail_log: bool,
+    /// Sender address of emails
+    pub mail_from: String,
+    /// Directory that emails are written to when `smtp_url` is not set. Only meant for development.
+    pub mail_outbox_dir: Option<String>,
+    /// Seconds a data export can be downloaded before it is deleted
+    pub export_expiration: u64,
+    /// Rate limits per route scope
+    #[serde(skip)]
+    pub rate_limit: RateLimitConfig,
+}
+
+/// Rate limits per route scope. A scope without a quota is not limited.
+#[derive(Clone, Debug)]
+pub struct RateLimitConfig {
+    /// Where token buckets are kept. Use Postgres when running several instances.
+    pub store: StoreKind,
+    /// Take the client IP from `Forwarded`/`X-Forwarded-For`, for deployments behind a reverse proxy
+    pub trust_proxy: bool,
+    /// Requests per client IP to the `/auth` routes
+    pub auth: Option<Quota>,
+    /// Login attempts per email address
+    pub login: Option<Quota>,
+    /// Requests per user to all authenticated routes
+    pub api: Option<Quota>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            store: StoreKind::Memory,
+            trust_proxy: false,
+            auth: None,
+            login: None,
+            api: None,
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// Load rate limits from environment variables. Quotas are given as
+    /// `REQUESTS/SECONDS`, or `off` to disable a limit.
+    pub fn from_env() -> Result<Self> {
+        let store = env::var("RATE_LIMIT_STORE")
+            .unwrap_or_else(|_| "memory".to_string())
+            .parse::<StoreKind>()?;
+        let trust_proxy = env::var("RATE_LIMIT_TRUST_PROXY")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse::<bool>()?;
+
+        Ok(Self {
+            store,
+            trust_proxy,
+            auth: quota_from_env("RATE_LIMIT_AUTH", "20/60")?, // Default to 20 per minute
+            login: quota_from_env("RATE_LIMIT_LOGIN", "5/60")?, // Default to 5 per minute
+            api: quota_from_env("RATE_LIMIT_API", "300/60")?, // Default to 300 per minute
+        })
+    }
+}
+
+fn quota_from_env(name: &str, default: &str) -> Result<Option<Quota>> {
+    let value = env::var(name).unwrap_or_else(|_| default.to_string());
+
+    if value == "off" {
+        return Ok(None);
+    }
+
+    Ok(Some(value.parse()?))
 }
 
 impl AppConfig {
@@ -28,8 +106,26 @@ impl AppConfig {
         let database_url = env::var("DATABASE_URL")?;
         let jwt_secret = env::var("JWT_SECRET")?;
         let jwt_expiration = env::var("JWT_EXPIRATION")
-            .unwrap_or_else(|_| "86400".to_string()) // Default to 24 hours
+            .unwrap_or_else(|_| "900".to_string()) // Default to 15 minutes
+            .parse::<u64>()?;
+        let refresh_token_expiration = env::var("REFRESH_TOKEN_EXPIRATION")
+            .unwrap_or_else(|_| "2592000".to_string()) // Default to 30 days
+            .parse::<u64>()?;
+        let run_migrations = env::var("RUN_MIGRATIONS")
+            .unwrap_or_else(|_| "true".to_string())
+            .parse::<bool>()?;
+        let app_url = env::var("APP_URL").unwrap_or_else(|_| "http://localhost:8080".to_string());
+        let smtp_url = env::var("SMTP_URL").ok().filter(|url| !url.is_empty());
+        let mail_log = env::var("MAIL_LOG")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse::<bool>()?;
+        let mail_from = env::var("MAIL_FROM")
+            .unwrap_or_else(|_| "Fitness Progress Tracker <no-reply@localhost>".to_string());
+        let mail_outbox_dir = env::var("MAIL_OUTBOX_DIR").ok().filter(|dir| !dir.is_empty());
+        let export_expiration = env::var("EXPORT_EXPIRATION")
+            .unwrap_or_else(|_| "86400".to_string()) // Default to 1 day
             .parse::<u64>()?;
+        let rate_limit = RateLimitConfig::from_env()?;
 
         Ok(Self {
             host,
@@ -37,6 +133,15 @@ impl AppConfig {
             database_url,
             jwt_secret,
             jwt_expiration,
+            refresh_token_expiration,
+            run_migrations,
+            app_url,
+            smtp_url,
+            mail_log,
+            mail_from,
+            mail_outbox_dir,
+            export_expiration,
+            rate_limit,
         })
     }
     
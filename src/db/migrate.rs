@@ -0,0 +1,84 @@
+use super::DbPool;
+use anyhow::{Result, anyhow};
+use log::info;
+use sqlx::migrate::{Migrate, Migrator};
+
+/// Migrations from `migrations/`, embedded in the binary at compile time.
+///
+/// Every migration is idempotent, so databases that were set up by running the
+/// SQL files by hand can be brought under version tracking by applying them all.
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Whether a migration has been applied to the database
+#[derive(Debug)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub applied: bool,
+    /// The migration file changed after it was applied
+    pub checksum_mismatch: bool,
+}
+
+/// Apply all pending migrations
+pub async fn run_migrations(pool: &DbPool) -> Result<()> {
+    info!("Running database migrations");
+
+    MIGRATOR.run(pool).await?;
+
+    info!("Database migrations are up to date");
+
+    Ok(())
+}
+
+/// List every known migration and whether it has been applied
+pub async fn migration_status(pool: &DbPool) -> Result<Vec<MigrationStatus>> {
+    let mut conn = pool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+    let applied = conn.list_applied_migrations().await?;
+
+    let status = MIGRATOR
+        .iter()
+        .filter(|migration| migration.migration_type.is_up_migration())
+        .map(|migration| {
+            let applied = applied.iter().find(|applied| applied.version == migration.version);
+
+            MigrationStatus {
+                version: migration.version,
+                description: migration.description.to_string(),
+                applied: applied.is_some(),
+                checksum_mismatch: applied.is_some_and(|applied| applied.checksum != migration.checksum),
+            }
+        })
+        .collect();
+
+    Ok(status)
+}
+
+/// Revert applied migrations newer than `target`, or only the latest one when
+/// no target is given. Returns the reverted versions, newest first.
+pub async fn revert_migrations(pool: &DbPool, target: Option<i64>) -> Result<Vec<i64>> {
+    let mut applied: Vec<i64> = {
+        let mut conn = pool.acquire().await?;
+        conn.ensure_migrations_table().await?;
+        conn.list_applied_migrations()
+            .await?
+            .into_iter()
+            .map(|migration| migration.version)
+            .collect()
+    };
+    applied.sort_unstable_by(|a, b| b.cmp(a));
+
+    let latest = *applied.first().ok_or_else(|| anyhow!("No migrations have been applied"))?;
+    let target = target.unwrap_or_else(|| applied.get(1).copied().unwrap_or(0));
+
+    if target >= latest {
+        return Err(anyhow!("Target version {} is not older than the latest applied migration {}", target, latest));
+    }
+
+    MIGRATOR.undo(pool, target).await?;
+
+    let reverted: Vec<i64> = applied.into_iter().filter(|version| *version > target).collect();
+    info!("Reverted migrations {:?}", reverted);
+
+    Ok(reverted)
+}
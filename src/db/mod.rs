@@ -1,8 +1,15 @@
+pub mod migrate;
+pub mod seed;
+#[cfg(test)]
+mod tests;
+
 use anyhow::Result;
 use log::{info, error};
 use sqlx::postgres::{PgPool, PgPoolOptions};
 use std::time::Duration;
 
+pub use migrate::run_migrations;
+
 /// Database connection pool
 pub type DbPool = PgPool;
 
@@ -18,20 +25,9 @@ pub async fn init_db(database_url: &str) -> Result<DbPool> {
     
     info!("Database connection established");
     
-    // Run migrations (we'll implement this later)
-    // run_migrations(&pool).await?;
-    
     Ok(pool)
 }
 
-/// Run database migrations
-/// This function will be implemented in the future when we
-/// add SQL migrations
-async fn run_migrations(_pool: &DbPool) -> Result<()> {
-    // We'll implement this later with sqlx migrations
-    Ok(())
-}
-
 /// Test the database connection
 pub async fn test_connection(pool: &DbPool) -> Result<()> {
     match sqlx::query("SELECT 1").execute(pool).await {
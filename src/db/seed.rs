@@ -0,0 +1,121 @@
+use crate::db::DbPool;
+use crate::models::{Mechanics, MeasurementType};
+use anyhow::Result;
+use chrono::Utc;
+use log::info;
+use serde::Deserialize;
+use uuid::Uuid;
+
+/// Version of the built-in exercise library.
+/// Bump this whenever `data/exercise_library.json` changes so existing databases pick up the changes.
+pub const EXERCISE_LIBRARY_VERSION: i32 = 1;
+
+const EXERCISE_LIBRARY_SEED: &str = "exercise_library";
+const EXERCISE_LIBRARY: &str = include_str!("../../data/exercise_library.json");
+
+/// An exercise in the built-in library
+#[derive(Debug, Deserialize)]
+pub struct LibraryExercise {
+    pub slug: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub category: String,
+    pub primary_muscles: Vec<String>,
+    #[serde(default)]
+    pub secondary_muscles: Vec<String>,
+    pub equipment: Option<String>,
+    pub mechanics: Option<Mechanics>,
+    pub measurement_type: MeasurementType,
+}
+
+/// Parse the exercise library shipped with the crate
+pub fn exercise_library() -> Result<Vec<LibraryExercise>> {
+    Ok(serde_json::from_str(EXERCISE_LIBRARY)?)
+}
+
+/// Seed the built-in exercise library.
+///
+/// Exercises are upserted by slug, so running this repeatedly is safe. The applied
+/// version is recorded in `seed_versions` and the seed is skipped once it is current.
+pub async fn seed_exercise_library(pool: &DbPool) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    // Serialize concurrent seeding from several instances starting at once
+    sqlx::query!("SELECT pg_advisory_xact_lock(hashtext($1))", EXERCISE_LIBRARY_SEED)
+        .execute(&mut *tx)
+        .await?;
+
+    let applied_version = sqlx::query_scalar!(
+        "SELECT version FROM seed_versions WHERE name = $1",
+        EXERCISE_LIBRARY_SEED
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    if applied_version.is_some_and(|version| version >= EXERCISE_LIBRARY_VERSION) {
+        info!("Exercise library is up to date (version {})", EXERCISE_LIBRARY_VERSION);
+        return Ok(());
+    }
+
+    let exercises = exercise_library()?;
+    let now = Utc::now();
+
+    for exercise in &exercises {
+        sqlx::query!(
+            r#"
+            INSERT INTO exercises (id, slug, name, description, category, primary_muscles, secondary_muscles,
+                                   equipment, mechanics, measurement_type, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            ON CONFLICT (slug) DO UPDATE
+            SET name = EXCLUDED.name,
+                description = EXCLUDED.description,
+                category = EXCLUDED.category,
+                primary_muscles = EXCLUDED.primary_muscles,
+                secondary_muscles = EXCLUDED.secondary_muscles,
+                equipment = EXCLUDED.equipment,
+                mechanics = EXCLUDED.mechanics,
+                measurement_type = EXCLUDED.measurement_type,
+                updated_at = EXCLUDED.updated_at
+            "#,
+            Uuid::new_v4(),
+            exercise.slug,
+            exercise.name,
+            exercise.description,
+            exercise.category,
+            &exercise.primary_muscles,
+            &exercise.secondary_muscles,
+            exercise.equipment,
+            exercise.mechanics as _,
+            exercise.measurement_type as _,
+            now,
+            now
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO seed_versions (name, version, applied_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (name) DO UPDATE
+        SET version = EXCLUDED.version, applied_at = EXCLUDED.applied_at
+        "#,
+        EXERCISE_LIBRARY_SEED,
+        EXERCISE_LIBRARY_VERSION,
+        now
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    info!(
+        "Seeded {} exercises from the built-in library (version {})",
+        exercises.len(),
+        EXERCISE_LIBRARY_VERSION
+    );
+
+    Ok(())
+}
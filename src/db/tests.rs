@@ -0,0 +1,28 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests {
+    use crate::db::seed::exercise_library;
+    use crate::models::exercise::{EQUIPMENT, MUSCLE_GROUPS};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_exercise_library_is_valid() {
+        let exercises = exercise_library().expect("Failed to parse exercise library");
+
+        assert!(exercises.len() >= 200, "Library should contain a few hundred exercises");
+
+        let mut slugs = HashSet::new();
+        for exercise in &exercises {
+            assert!(slugs.insert(exercise.slug.as_str()), "Duplicate slug {}", exercise.slug);
+            assert!(!exercise.primary_muscles.is_empty(), "{} has no primary muscles", exercise.slug);
+
+            for muscle in exercise.primary_muscles.iter().chain(&exercise.secondary_muscles) {
+                assert!(MUSCLE_GROUPS.contains(&muscle.as_str()), "Unknown muscle {} in {}", muscle, exercise.slug);
+            }
+
+            if let Some(equipment) = &exercise.equipment {
+                assert!(EQUIPMENT.contains(&equipment.as_str()), "Unknown equipment {} in {}", equipment, exercise.slug);
+            }
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use actix_web::{http::{header, StatusCode}, web, HttpResponse, ResponseError};
+use log::error;
+use serde::Serialize;
+use utoipa::ToSchema;
+use validator::ValidationErrors;
+
+/// Result type used by services that return typed errors
+pub type Result<T, E = AppError> = std::result::Result<T, E>;
+
+/// Error returned by services and handlers, mapped to an HTTP status and a JSON body
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    /// The request body or query failed validation
+    #[error("Invalid request data")]
+    Validation(#[from] ValidationErrors),
+
+    /// The request is malformed or inconsistent, e.g. an invalid cursor
+    #[error("{0}")]
+    BadRequest(String),
+
+    /// Missing or wrong credentials
+    #[error("{0}")]
+    Unauthorized(String),
+
+    /// The resource does not exist or belongs to another user
+    #[error("{0}")]
+    NotFound(String),
+
+    /// The request conflicts with existing data, e.g. a duplicate email
+    #[error("{0}")]
+    Conflict(String),
+
+    /// The request references data that does not exist, e.g. an unknown exercise
+    #[error("{0}")]
+    Unprocessable(String),
+
+    /// A rate limit was hit or the account is temporarily locked. The client
+    /// should wait `retry_after` seconds before trying again.
+    #[error("{message}")]
+    TooManyRequests { message: String, retry_after: u64 },
+
+    /// Database failure that is not the client's fault
+    #[error(transparent)]
+    Database(sqlx::Error),
+
+    /// Any other failure, e.g. from a helper that returns `anyhow` errors
+    #[error(transparent)]
+    Internal(#[from] anyhow::Error),
+}
+
+impl AppError {
+    /// Machine-readable code sent in the `code` field of error responses
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Validation(_) => "validation_failed",
+            AppError::BadRequest(_) => "bad_request",
+            AppError::Unauthorized(_) => "unauthorized",
+            AppError::NotFound(_) => "not_found",
+            AppError::Conflict(_) => "conflict",
+            AppError::Unprocessable(_) => "unprocessable_entity",
+            AppError::TooManyRequests { .. } => "too_many_requests",
+            AppError::Database(_) | AppError::Internal(_) => "internal_error",
+        }
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(e: sqlx::Error) -> Self {
+        if let Some(db_error) = e.as_database_error() {
+            if db_error.is_unique_violation() {
+                return AppError::Conflict("A resource with the same unique fields already exists".to_string());
+            }
+
+            if db_error.is_foreign_key_violation() {
+                return AppError::Unprocessable("The request references a resource that does not exist".to_string());
+            }
+        }
+
+        AppError::Database(e)
+    }
+}
+
+/// JSON body of every error response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorResponse {
+    /// Human-readable message
+    #[schema(example = "Workout not found")]
+    pub error: String,
+    /// Machine-readable error code
+    #[schema(example = "not_found")]
+    pub code: String,
+    /// Field errors, only present for `validation_failed`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub details: Option<ValidationErrors>,
+}
+
+impl ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::Validation(_) | AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::Unprocessable(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
+            AppError::Database(_) | AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let message = match self {
+            // Never leak internal details to clients
+            AppError::Database(_) | AppError::Internal(_) => {
+                error!("Internal error: {:#}", self);
+                "Internal server error".to_string()
+            }
+            _ => self.to_string(),
+        };
+
+        let details = match self {
+            AppError::Validation(errors) => Some(errors.clone()),
+            _ => None,
+        };
+
+        let mut response = HttpResponse::build(self.status_code());
+
+        if let AppError::TooManyRequests { retry_after, .. } = self {
+            response.insert_header((header::RETRY_AFTER, retry_after.to_string()));
+        }
+
+        response.json(ErrorResponse {
+            error: message,
+            code: self.code().to_string(),
+            details,
+        })
+    }
+}
+
+/// JSON body extractor config that reports malformed bodies as JSON errors
+pub fn json_config() -> web::JsonConfig {
+    web::JsonConfig::default()
+        .error_handler(|e, _| AppError::BadRequest(format!("Invalid request body: {}", e)).into())
+}
+
+/// Query extractor config that reports malformed query strings as JSON errors
+pub fn query_config() -> web::QueryConfig {
+    web::QueryConfig::default()
+        .error_handler(|e, _| AppError::BadRequest(format!("Invalid query parameters: {}", e)).into())
+}
+
+/// Path extractor config that reports malformed path segments, e.g. invalid
+/// IDs, as JSON errors
+pub fn path_config() -> web::PathConfig {
+    web::PathConfig::default()
+        .error_handler(|e, _| AppError::BadRequest(format!("Invalid path: {}", e)).into())
+}
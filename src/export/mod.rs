@@ -0,0 +1,195 @@
+#[cfg(test)]
+mod tests;
+
+use crate::models::{
+    Exercise, GoalResponse, Measurement, MeasurementType, Mechanics, SetType, UserPreferences, UserProfileResponse,
+    WorkoutDetailsResponse,
+};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::io::{Cursor, Seek, Write};
+use uuid::Uuid;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Everything exported for a user. Credentials like the password hash or
+/// TOTP secret are never part of it.
+#[derive(Serialize)]
+pub struct ExportData {
+    pub exported_at: DateTime<Utc>,
+    pub profile: UserProfileResponse,
+    pub preferences: UserPreferences,
+    pub workouts: Vec<WorkoutDetailsResponse>,
+    pub goals: Vec<GoalResponse>,
+    pub measurements: Vec<Measurement>,
+    /// Custom exercises created by the user
+    pub exercises: Vec<Exercise>,
+}
+
+/// Build the ZIP archive of an export: `data.json` with everything, plus a CSV
+/// file per kind of record for spreadsheets
+pub fn build_archive(data: &ExportData) -> Result<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    zip.start_file("data.json", options)?;
+    serde_json::to_writer_pretty(&mut zip, data)?;
+
+    write_csv(&mut zip, options, "profile.csv", [&data.profile])?;
+    write_csv(&mut zip, options, "preferences.csv", [&data.preferences])?;
+    write_csv(&mut zip, options, "workouts.csv", data.workouts.iter().map(WorkoutRow::from))?;
+    write_csv(
+        &mut zip,
+        options,
+        "workout_exercises.csv",
+        data.workouts.iter().flat_map(|workout| {
+            workout.exercises.iter().map(move |entry| WorkoutExerciseRow {
+                id: entry.id,
+                workout_id: workout.id,
+                position: entry.position,
+                exercise_id: entry.exercise.id,
+                exercise_name: &entry.exercise.name,
+                sets: entry.sets,
+                reps: entry.reps,
+                weight: entry.weight,
+                duration: entry.duration,
+                distance: entry.distance,
+                notes: entry.notes.as_deref(),
+            })
+        }),
+    )?;
+    write_csv(
+        &mut zip,
+        options,
+        "workout_sets.csv",
+        data.workouts.iter().flat_map(|workout| {
+            workout.exercises.iter().flat_map(move |entry| {
+                entry.workout_sets.iter().map(move |set| WorkoutSetRow {
+                    id: set.id,
+                    workout_id: workout.id,
+                    workout_exercise_id: entry.id,
+                    set_index: set.set_index,
+                    reps: set.reps,
+                    weight: set.weight,
+                    duration: set.duration,
+                    distance: set.distance,
+                    rpe: set.rpe,
+                    rir: set.rir,
+                    set_type: set.set_type,
+                    completed: set.completed,
+                })
+            })
+        }),
+    )?;
+    write_csv(&mut zip, options, "goals.csv", &data.goals)?;
+    write_csv(&mut zip, options, "measurements.csv", &data.measurements)?;
+    write_csv(&mut zip, options, "exercises.csv", data.exercises.iter().map(ExerciseRow::from))?;
+
+    Ok(zip.finish()?.into_inner())
+}
+
+/// Write rows as a CSV file, with a header row unless there are no rows
+fn write_csv<W, T, I>(zip: &mut ZipWriter<W>, options: FileOptions, name: &str, rows: I) -> Result<()>
+where
+    W: Write + Seek,
+    T: Serialize,
+    I: IntoIterator<Item = T>,
+{
+    zip.start_file(name, options)?;
+
+    let mut writer = csv::Writer::from_writer(zip);
+    for row in rows {
+        writer.serialize(row)?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// A workout without its exercises, which have their own file
+#[derive(Serialize)]
+struct WorkoutRow<'a> {
+    id: Uuid,
+    name: &'a str,
+    description: Option<&'a str>,
+    date: DateTime<Utc>,
+    duration: Option<i32>,
+    calories_burned: Option<i32>,
+}
+
+impl<'a> From<&'a WorkoutDetailsResponse> for WorkoutRow<'a> {
+    fn from(workout: &'a WorkoutDetailsResponse) -> Self {
+        Self {
+            id: workout.id,
+            name: &workout.name,
+            description: workout.description.as_deref(),
+            date: workout.date,
+            duration: workout.duration,
+            calories_burned: workout.calories_burned,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct WorkoutExerciseRow<'a> {
+    id: Uuid,
+    workout_id: Uuid,
+    position: i32,
+    exercise_id: Uuid,
+    exercise_name: &'a str,
+    sets: Option<i32>,
+    reps: Option<i32>,
+    weight: Option<f64>,
+    duration: Option<i32>,
+    distance: Option<f64>,
+    notes: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct WorkoutSetRow {
+    id: Uuid,
+    workout_id: Uuid,
+    workout_exercise_id: Uuid,
+    set_index: i32,
+    reps: Option<i32>,
+    weight: Option<f64>,
+    duration: Option<i32>,
+    distance: Option<f64>,
+    rpe: Option<f64>,
+    rir: Option<i32>,
+    set_type: SetType,
+    completed: bool,
+}
+
+/// An exercise with its muscle lists joined by `;`, since CSV cells hold single values
+#[derive(Serialize)]
+struct ExerciseRow<'a> {
+    id: Uuid,
+    name: &'a str,
+    description: Option<&'a str>,
+    category: Option<&'a str>,
+    primary_muscles: String,
+    secondary_muscles: String,
+    equipment: Option<&'a str>,
+    mechanics: Option<Mechanics>,
+    measurement_type: MeasurementType,
+    created_at: DateTime<Utc>,
+}
+
+impl<'a> From<&'a Exercise> for ExerciseRow<'a> {
+    fn from(exercise: &'a Exercise) -> Self {
+        Self {
+            id: exercise.id,
+            name: &exercise.name,
+            description: exercise.description.as_deref(),
+            category: exercise.category.as_deref(),
+            primary_muscles: exercise.primary_muscles.join(";"),
+            secondary_muscles: exercise.secondary_muscles.join(";"),
+            equipment: exercise.equipment.as_deref(),
+            mechanics: exercise.mechanics,
+            measurement_type: exercise.measurement_type,
+            created_at: exercise.created_at,
+        }
+    }
+}
@@ -0,0 +1,108 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests {
+    use crate::export::{build_archive, ExportData};
+    use crate::models::{
+        Exercise, MeasurementType, SetType, UserPreferences, UserProfileResponse, WorkoutDetailsResponse,
+        WorkoutExerciseDetails, WorkoutSet,
+    };
+    use chrono::Utc;
+    use std::io::{Cursor, Read};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_build_archive() {
+        let now = Utc::now();
+        let exercise = Exercise {
+            id: Uuid::new_v4(),
+            name: "Zercher Squat".to_string(),
+            description: None,
+            category: Some("strength".to_string()),
+            owner_id: Some(Uuid::new_v4()),
+            slug: None,
+            primary_muscles: vec!["quadriceps".to_string(), "glutes".to_string()],
+            secondary_muscles: Vec::new(),
+            equipment: Some("barbell".to_string()),
+            mechanics: None,
+            measurement_type: MeasurementType::WeightReps,
+            created_at: now,
+            updated_at: now,
+        };
+        let entry_id = Uuid::new_v4();
+        let workout = WorkoutDetailsResponse {
+            id: Uuid::new_v4(),
+            name: "Leg day".to_string(),
+            description: None,
+            date: now,
+            duration: Some(3600),
+            calories_burned: None,
+            updated_at: now,
+            exercises: vec![WorkoutExerciseDetails {
+                id: entry_id,
+                exercise: Exercise { owner_id: None, ..exercise },
+                sets: Some(2),
+                reps: Some(5),
+                weight: Some(100.0),
+                duration: None,
+                distance: None,
+                notes: Some("Felt heavy, \"grindy\"".to_string()),
+                position: 0,
+                workout_sets: (0..2)
+                    .map(|set_index| WorkoutSet {
+                        id: Uuid::new_v4(),
+                        workout_exercise_id: entry_id,
+                        set_index,
+                        reps: Some(5),
+                        weight: Some(100.0),
+                        duration: None,
+                        distance: None,
+                        rpe: None,
+                        rir: None,
+                        set_type: SetType::Working,
+                        completed: true,
+                        created_at: now,
+                        updated_at: now,
+                    })
+                    .collect(),
+            }],
+        };
+        let data = ExportData {
+            exported_at: now,
+            profile: UserProfileResponse {
+                id: Uuid::new_v4(),
+                email: "user@example.com".to_string(),
+                username: "user".to_string(),
+                first_name: None,
+                last_name: None,
+                email_verified: true,
+                mfa_enabled: false,
+                created_at: now,
+            },
+            preferences: UserPreferences::default(),
+            workouts: vec![workout],
+            goals: Vec::new(),
+            measurements: Vec::new(),
+            exercises: Vec::new(),
+        };
+
+        let archive = build_archive(&data).unwrap();
+        let mut zip = zip::ZipArchive::new(Cursor::new(archive)).unwrap();
+        let mut read = |name: &str| {
+            let mut contents = String::new();
+            zip.by_name(name).unwrap().read_to_string(&mut contents).unwrap();
+            contents
+        };
+
+        let json: serde_json::Value = serde_json::from_str(&read("data.json")).unwrap();
+        assert_eq!(json["profile"]["email"], "user@example.com");
+        assert_eq!(json["workouts"][0]["exercises"][0]["workout_sets"].as_array().unwrap().len(), 2);
+
+        let entries = read("workout_exercises.csv");
+        assert!(entries.starts_with("id,workout_id,position,exercise_id,exercise_name,"));
+        assert!(entries.contains("\"Felt heavy, \"\"grindy\"\"\""));
+
+        // Header and one row per set
+        assert_eq!(read("workout_sets.csv").lines().count(), 3);
+        assert_eq!(read("goals.csv"), "");
+    }
+}
@@ -0,0 +1,569 @@
+use super::{
+    truncate_name, ImportDate, ImportedExercise, ImportedFile, ImportedWorkout, MAX_ACTIVITY_SECONDS, MAX_REPS,
+    MAX_SET_VALUE,
+};
+use crate::models::{ImportRowError, SetType, UnitSystem, WorkoutDetailsResponse, WorkoutSetInput};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use csv::{ReaderBuilder, StringRecord, Writer};
+use std::collections::HashMap;
+
+/// Columns written by [`write`], the layout of Strong's CSV export, which
+/// other apps import as well
+pub const HEADERS: [&str; 12] = [
+    "Date",
+    "Workout Name",
+    "Duration",
+    "Exercise Name",
+    "Set Order",
+    "Weight",
+    "Reps",
+    "Distance",
+    "Seconds",
+    "Notes",
+    "Workout Notes",
+    "RPE",
+];
+
+/// Name for workouts without one in the file
+const DEFAULT_WORKOUT_NAME: &str = "Imported workout";
+
+/// Formats of dates without an offset, which are in the user's timezone
+const LOCAL_DATE_FORMATS: [&str; 5] = [
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    // Hevy, e.g. "15 Jan 2024, 18:30"
+    "%d %b %Y, %H:%M",
+];
+
+/// Formats of dates with an offset
+const OFFSET_DATE_FORMATS: [&str; 2] = ["%Y-%m-%d %H:%M:%S %z", "%Y-%m-%d %H:%M %z"];
+
+/// Which column holds which value. Weight and distance columns may name their
+/// units, or there may be columns with the units of each row.
+#[derive(Debug, Default)]
+struct ColumnMapping {
+    date: usize,
+    end_time: Option<usize>,
+    workout_name: Option<usize>,
+    duration: Option<usize>,
+    exercise_name: usize,
+    set_order: Option<usize>,
+    set_type: Option<usize>,
+    weight: Option<(usize, Option<UnitSystem>)>,
+    weight_unit: Option<usize>,
+    reps: Option<usize>,
+    distance: Option<(usize, Option<UnitSystem>)>,
+    distance_unit: Option<usize>,
+    seconds: Option<usize>,
+    rpe: Option<usize>,
+    rir: Option<usize>,
+    notes: Option<usize>,
+    workout_notes: Option<usize>,
+}
+
+impl ColumnMapping {
+    /// Map the header row, accepting the column names of common apps. The
+    /// first of several columns for the same value wins.
+    fn from_headers(headers: &StringRecord) -> Result<Self, String> {
+        let mut date = None;
+        let mut exercise_name = None;
+        let mut mapping = ColumnMapping::default();
+
+        for (index, header) in headers.iter().enumerate() {
+            let key: String = header
+                .chars()
+                .filter(|c| c.is_alphanumeric())
+                .collect::<String>()
+                .to_lowercase();
+
+            let slot = match key.as_str() {
+                "date" | "starttime" | "workoutdate" => {
+                    date.get_or_insert(index);
+                    continue;
+                }
+                "exercisename" | "exercisetitle" | "exercise" => {
+                    exercise_name.get_or_insert(index);
+                    continue;
+                }
+                "weight" => {
+                    mapping.weight.get_or_insert((index, None));
+                    continue;
+                }
+                "weightkg" => {
+                    mapping.weight.get_or_insert((index, Some(UnitSystem::Metric)));
+                    continue;
+                }
+                "weightlb" | "weightlbs" => {
+                    mapping.weight.get_or_insert((index, Some(UnitSystem::Imperial)));
+                    continue;
+                }
+                "distance" => {
+                    mapping.distance.get_or_insert((index, None));
+                    continue;
+                }
+                "distancekm" => {
+                    mapping.distance.get_or_insert((index, Some(UnitSystem::Metric)));
+                    continue;
+                }
+                "distancemi" | "distancemiles" => {
+                    mapping.distance.get_or_insert((index, Some(UnitSystem::Imperial)));
+                    continue;
+                }
+                "weightunit" => &mut mapping.weight_unit,
+                "distanceunit" => &mut mapping.distance_unit,
+                "endtime" => &mut mapping.end_time,
+                "workoutname" | "title" | "workout" => &mut mapping.workout_name,
+                "duration" | "workoutduration" => &mut mapping.duration,
+                "setorder" | "setindex" | "set" | "setnumber" => &mut mapping.set_order,
+                "settype" => &mut mapping.set_type,
+                "reps" | "repetitions" => &mut mapping.reps,
+                "seconds" | "durationseconds" | "time" => &mut mapping.seconds,
+                "rpe" => &mut mapping.rpe,
+                "rir" => &mut mapping.rir,
+                "notes" | "exercisenotes" => &mut mapping.notes,
+                "workoutnotes" | "description" => &mut mapping.workout_notes,
+                _ => continue,
+            };
+            slot.get_or_insert(index);
+        }
+
+        mapping.date = date.ok_or("Missing date column")?;
+        mapping.exercise_name = exercise_name.ok_or("Missing exercise name column")?;
+
+        Ok(mapping)
+    }
+}
+
+/// A data row: one set of an exercise in a workout
+struct Row {
+    date: ImportDate,
+    end_time: Option<ImportDate>,
+    workout_name: String,
+    duration: Option<i32>,
+    exercise_name: String,
+    set: WorkoutSetInput,
+    notes: Option<String>,
+    workout_notes: Option<String>,
+}
+
+/// Read workout history with one row per set, as exported by Strong, Hevy and
+/// [`write`]. Column names are matched loosely, and commas, semicolons and tabs
+/// are accepted as separators.
+///
+/// Rows with the same date and workout name form a workout, and consecutive rows
+/// of the same exercise form an exercise entry. Weights and distances are in
+/// `units` unless their column names the units. Zeros count as empty, since some
+/// apps write them for values that were not logged. Rows that cannot be read are
+/// reported as errors and left out.
+pub fn read(data: &[u8], units: UnitSystem) -> Result<ImportedFile, String> {
+    let data = data.strip_prefix("\u{feff}".as_bytes()).unwrap_or(data);
+
+    let mut reader = ReaderBuilder::new()
+        .delimiter(detect_delimiter(data))
+        .flexible(true)
+        .from_reader(data);
+
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("Invalid CSV file: {}", e))?
+        .clone();
+    let mapping = ColumnMapping::from_headers(&headers)?;
+
+    let mut file = ImportedFile::default();
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        file.rows += 1;
+
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map(|position| position.line()).unwrap_or_default();
+                file.errors.push(ImportRowError { line, message: format!("Invalid CSV row: {}", e) });
+                continue;
+            }
+        };
+        let line = record.position().map(|position| position.line()).unwrap_or_default();
+
+        match read_row(&record, &mapping, units) {
+            Ok(row) => rows.push(row),
+            Err(message) => file.errors.push(ImportRowError { line, message }),
+        }
+    }
+
+    file.workouts = group_rows(rows);
+
+    Ok(file)
+}
+
+/// Write workouts with one row per set in the columns of [`HEADERS`].
+/// `dates` holds the local start time of each workout.
+pub fn write(workouts: &[WorkoutDetailsResponse], dates: &[NaiveDateTime]) -> Result<Vec<u8>> {
+    if workouts.len() != dates.len() {
+        return Err(anyhow!("Expected a date for each workout"));
+    }
+
+    let mut writer = Writer::from_writer(Vec::new());
+    writer.write_record(HEADERS)?;
+
+    for (workout, date) in workouts.iter().zip(dates) {
+        let date = date.format("%Y-%m-%d %H:%M:%S").to_string();
+        let duration = workout.duration.map(format_duration).unwrap_or_default();
+        let workout_notes = workout.description.as_deref().unwrap_or_default();
+
+        for entry in &workout.exercises {
+            let notes = entry.notes.as_deref().unwrap_or_default();
+
+            // Keep entries without sets, so the exercise is not lost
+            if entry.workout_sets.is_empty() {
+                writer.write_record([
+                    date.as_str(), &workout.name, &duration, &entry.exercise.name, "1", "", "", "", "", notes,
+                    workout_notes, "",
+                ])?;
+                continue;
+            }
+
+            let mut working_sets = 0;
+            for set in &entry.workout_sets {
+                let set_order = match set.set_type {
+                    SetType::Working => {
+                        working_sets += 1;
+                        working_sets.to_string()
+                    }
+                    SetType::Warmup => "W".to_string(),
+                    SetType::Drop => "D".to_string(),
+                    SetType::Failure => "F".to_string(),
+                };
+
+                writer.write_record([
+                    date.as_str(),
+                    &workout.name,
+                    &duration,
+                    &entry.exercise.name,
+                    &set_order,
+                    &format_value(set.weight),
+                    &format_value(set.reps),
+                    &format_value(set.distance),
+                    &format_value(set.duration),
+                    notes,
+                    workout_notes,
+                    &format_value(set.rpe),
+                ])?;
+            }
+        }
+    }
+
+    Ok(writer.into_inner()?)
+}
+
+fn read_row(record: &StringRecord, mapping: &ColumnMapping, units: UnitSystem) -> Result<Row, String> {
+    let field = |index: Option<usize>| {
+        index
+            .and_then(|index| record.get(index))
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    };
+
+    let date = field(Some(mapping.date)).ok_or("Missing date")?;
+    let date = parse_date(date).ok_or_else(|| format!("Invalid date: {}", date))?;
+
+    let end_time = match field(mapping.end_time) {
+        Some(value) => Some(parse_date(value).ok_or_else(|| format!("Invalid end time: {}", value))?),
+        None => None,
+    };
+
+    let exercise_name = field(Some(mapping.exercise_name)).ok_or("Missing exercise name")?;
+
+    let duration = match field(mapping.duration) {
+        Some(value) => Some(parse_duration(value).ok_or_else(|| format!("Invalid duration: {}", value))?),
+        None => None,
+    };
+
+    let set_type = match field(mapping.set_type).or(field(mapping.set_order)) {
+        Some(value) => parse_set_type(value).ok_or_else(|| format!("Invalid set type: {}", value))?,
+        None => SetType::Working,
+    };
+
+    let weight_units = match field(mapping.weight_unit) {
+        Some(value) => Some(parse_weight_unit(value).ok_or_else(|| format!("Invalid weight unit: {}", value))?),
+        None => None,
+    };
+    let weight = match mapping.weight {
+        Some((index, column_units)) => number(field(Some(index)), "weight")?
+            .map(|weight| column_units.or(weight_units).unwrap_or(units).weight_to_kg(weight)),
+        None => None,
+    };
+
+    let distance = match mapping.distance {
+        Some((index, column_units)) => match (number(field(Some(index)), "distance")?, field(mapping.distance_unit)) {
+            (Some(distance), Some(unit)) if column_units.is_none() => {
+                Some(distance_to_km(distance, unit).ok_or_else(|| format!("Invalid distance unit: {}", unit))?)
+            }
+            (distance, _) => distance.map(|distance| column_units.unwrap_or(units).distance_to_km(distance)),
+        },
+        None => None,
+    };
+    if weight.is_some_and(|weight| weight > MAX_SET_VALUE) {
+        return Err("Weight is too large".to_string());
+    }
+    if distance.is_some_and(|distance| distance > MAX_SET_VALUE) {
+        return Err("Distance is too large".to_string());
+    }
+
+    let reps = number(field(mapping.reps), "reps")?;
+    let seconds = number(field(mapping.seconds), "seconds")?;
+    let rir = number(field(mapping.rir), "RIR")?;
+    let rpe = number(field(mapping.rpe), "RPE")?;
+    if reps.is_some_and(|reps| reps > MAX_REPS) {
+        return Err("Reps are too large".to_string());
+    }
+    if rir.is_some_and(|rir| rir > MAX_REPS) {
+        return Err("RIR is too large".to_string());
+    }
+    if seconds.is_some_and(|seconds| seconds > f64::from(MAX_ACTIVITY_SECONDS)) {
+        return Err("Duration is too long".to_string());
+    }
+    if rpe.is_some_and(|rpe| !(1.0..=10.0).contains(&rpe)) {
+        return Err("RPE must be between 1 and 10".to_string());
+    }
+
+    Ok(Row {
+        date,
+        end_time,
+        workout_name: field(mapping.workout_name).map(truncate_name).unwrap_or_else(|| DEFAULT_WORKOUT_NAME.to_string()),
+        duration,
+        exercise_name: truncate_name(exercise_name),
+        set: WorkoutSetInput {
+            reps: reps.map(|reps| reps.round() as i32),
+            weight,
+            duration: seconds.map(|seconds| seconds.round() as i32),
+            distance,
+            rpe,
+            rir: rir.map(|rir| rir.round() as i32),
+            set_type: Some(set_type),
+            completed: Some(true),
+        },
+        notes: field(mapping.notes).map(str::to_string),
+        workout_notes: field(mapping.workout_notes).map(str::to_string),
+    })
+}
+
+/// Group rows into workouts by date and name, in the order they first appear
+fn group_rows(rows: Vec<Row>) -> Vec<ImportedWorkout> {
+    let mut workouts: Vec<ImportedWorkout> = Vec::new();
+    let mut end_times: Vec<Option<ImportDate>> = Vec::new();
+    let mut index_by_key: HashMap<(ImportDate, String), usize> = HashMap::new();
+
+    for row in rows {
+        let index = *index_by_key
+            .entry((row.date, row.workout_name.clone()))
+            .or_insert_with(|| {
+                workouts.push(ImportedWorkout {
+                    name: row.workout_name.clone(),
+                    date: row.date,
+                    duration: None,
+                    calories_burned: None,
+                    notes: None,
+                    exercises: Vec::new(),
+                    route: None,
+                    laps: Vec::new(),
+                    samples: Vec::new(),
+                });
+                end_times.push(None);
+                workouts.len() - 1
+            });
+        let workout = &mut workouts[index];
+
+        workout.duration = workout.duration.or(row.duration);
+        workout.notes = workout.notes.take().or(row.workout_notes);
+        end_times[index] = end_times[index].or(row.end_time);
+
+        let entry = match workout.exercises.last_mut() {
+            Some(entry) if entry.name == row.exercise_name => entry,
+            _ => {
+                workout.exercises.push(ImportedExercise {
+                    name: row.exercise_name,
+                    notes: None,
+                    sets: Vec::new(),
+                });
+                workout.exercises.last_mut().expect("entry was just added")
+            }
+        };
+
+        if let Some(notes) = row.notes {
+            match &mut entry.notes {
+                Some(existing) if existing.split('\n').any(|line| line == notes) => {}
+                Some(existing) => {
+                    existing.push('\n');
+                    existing.push_str(&notes);
+                }
+                None => entry.notes = Some(notes),
+            }
+        }
+        entry.sets.push(row.set);
+    }
+
+    // Derive durations from end times, e.g. in Hevy's export
+    for (workout, end_time) in workouts.iter_mut().zip(end_times) {
+        if workout.duration.is_none() {
+            workout.duration = end_time.and_then(|end_time| seconds_between(workout.date, end_time));
+        }
+    }
+
+    workouts
+}
+
+/// The most common of comma, semicolon and tab in the header line
+fn detect_delimiter(data: &[u8]) -> u8 {
+    let header = data.split(|byte| *byte == b'\n').next().unwrap_or_default();
+
+    [b',', b';', b'\t']
+        .into_iter()
+        .max_by_key(|delimiter| {
+            (
+                header.iter().filter(|byte| *byte == delimiter).count(),
+                *delimiter == b',',
+            )
+        })
+        .unwrap_or(b',')
+}
+
+fn parse_date(value: &str) -> Option<ImportDate> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Some(ImportDate::Utc(date.with_timezone(&Utc)));
+    }
+
+    for format in OFFSET_DATE_FORMATS {
+        if let Ok(date) = DateTime::parse_from_str(value, format) {
+            return Some(ImportDate::Utc(date.with_timezone(&Utc)));
+        }
+    }
+
+    for format in LOCAL_DATE_FORMATS {
+        if let Ok(date) = NaiveDateTime::parse_from_str(value, format) {
+            return Some(ImportDate::Local(date));
+        }
+    }
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(ImportDate::Local)
+}
+
+/// Parse a duration in seconds like "3600", "1:05:00", "65:00" or "1h 5m"
+fn parse_duration(value: &str) -> Option<i32> {
+    if let Ok(seconds) = value.parse::<u32>() {
+        return i32::try_from(seconds).ok();
+    }
+
+    if value.contains(':') {
+        let parts: Vec<u32> = value.split(':').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+        let seconds = match parts.as_slice() {
+            [minutes, seconds] => minutes * 60 + seconds,
+            [hours, minutes, seconds] => hours * 3600 + minutes * 60 + seconds,
+            _ => return None,
+        };
+        return i32::try_from(seconds).ok();
+    }
+
+    let mut total = 0;
+    let mut found = false;
+    for part in value.split_whitespace() {
+        let split = part.find(|c: char| !c.is_ascii_digit())?;
+        let (amount, unit) = part.split_at(split);
+        let amount: i32 = amount.parse().ok()?;
+        let factor = match unit {
+            "h" | "hr" | "hrs" => 3600,
+            "m" | "min" | "mins" => 60,
+            "s" | "sec" | "secs" => 1,
+            _ => return None,
+        };
+        total += amount.checked_mul(factor)?;
+        found = true;
+    }
+
+    found.then_some(total)
+}
+
+/// Set type from a set type column or Strong's set order, which numbers
+/// working sets and marks others with a letter
+fn parse_set_type(value: &str) -> Option<SetType> {
+    if value.parse::<u32>().is_ok() {
+        return Some(SetType::Working);
+    }
+
+    match value.to_lowercase().replace([' ', '-', '_'], "").as_str() {
+        "normal" | "working" => Some(SetType::Working),
+        "w" | "warmup" => Some(SetType::Warmup),
+        "d" | "drop" | "dropset" => Some(SetType::Drop),
+        "f" | "failure" => Some(SetType::Failure),
+        _ => None,
+    }
+}
+
+/// A non-negative number, with a comma accepted as the decimal separator.
+/// Zero counts as empty.
+fn number(value: Option<&str>, name: &str) -> Result<Option<f64>, String> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+
+    match value.replace(',', ".").parse::<f64>() {
+        Ok(number) if number.is_finite() && number >= 0.0 && number < i32::MAX as f64 => {
+            Ok(Some(number).filter(|number| *number > 0.0))
+        }
+        _ => Err(format!("Invalid {}: {}", name, value)),
+    }
+}
+
+fn parse_weight_unit(value: &str) -> Option<UnitSystem> {
+    match value.to_lowercase().as_str() {
+        "kg" | "kgs" => Some(UnitSystem::Metric),
+        "lb" | "lbs" => Some(UnitSystem::Imperial),
+        _ => None,
+    }
+}
+
+/// Convert a distance with a unit column like Strong's to km
+fn distance_to_km(distance: f64, unit: &str) -> Option<f64> {
+    match unit.to_lowercase().as_str() {
+        "km" => Some(distance),
+        "m" => Some(distance / 1000.0),
+        "mi" | "miles" => Some(UnitSystem::Imperial.distance_to_km(distance)),
+        _ => None,
+    }
+}
+
+fn seconds_between(start: ImportDate, end: ImportDate) -> Option<i32> {
+    let seconds = match (start, end) {
+        (ImportDate::Utc(start), ImportDate::Utc(end)) => (end - start).num_seconds(),
+        (ImportDate::Local(start), ImportDate::Local(end)) => (end - start).num_seconds(),
+        _ => return None,
+    };
+
+    i32::try_from(seconds).ok().filter(|seconds| *seconds >= 0)
+}
+
+/// Format a duration like Strong does, e.g. "1h 5m"
+fn format_duration(seconds: i32) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+
+    let parts: Vec<String> = [(hours, "h"), (minutes, "m"), (seconds, "s")]
+        .into_iter()
+        .filter(|(amount, _)| *amount > 0)
+        .map(|(amount, unit)| format!("{}{}", amount, unit))
+        .collect();
+
+    if parts.is_empty() {
+        "0s".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+fn format_value<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
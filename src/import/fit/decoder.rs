@@ -0,0 +1,425 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// Seconds from the Unix epoch to the FIT epoch, 1989-12-31 00:00:00 UTC
+const FIT_EPOCH: i64 = 631_065_600;
+
+/// Field number of the timestamp in every message that has one
+pub const TIMESTAMP: u8 = 253;
+
+/// Global number of the messages that describe developer fields
+const FIELD_DESCRIPTION: u16 = 206;
+
+/// A decoded field value. Fields holding several values are arrays.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Unsigned(u64),
+    Signed(i64),
+    Float(f64),
+    Text(String),
+    Bytes(Vec<u8>),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Unsigned(value) => Some(*value as f64),
+            Value::Signed(value) => Some(*value as f64),
+            Value::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// A field added by an app on the device, like running power from a foot pod
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeveloperField {
+    pub name: String,
+    pub units: Option<String>,
+    pub value: Value,
+}
+
+/// A data message, with the fields that hold valid values
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Message {
+    /// Global message number, like 20 for records
+    pub number: u16,
+    pub fields: HashMap<u8, Value>,
+    pub developer_fields: Vec<DeveloperField>,
+}
+
+impl Message {
+    /// Numeric value of a field
+    pub fn value(&self, field: u8) -> Option<f64> {
+        self.fields.get(&field).and_then(Value::as_f64)
+    }
+
+    /// Numeric value of a field in its unit, from the stored `value / scale - offset`
+    pub fn scaled(&self, field: u8, scale: f64, offset: f64) -> Option<f64> {
+        self.value(field).map(|value| value / scale - offset)
+    }
+
+    /// Value of a date field, stored as seconds since the FIT epoch in a uint32.
+    /// Values outside of its range are left out.
+    pub fn time(&self, field: u8) -> Option<DateTime<Utc>> {
+        self.value(field)
+            .filter(|seconds| (0.0..=f64::from(u32::MAX)).contains(seconds))
+            .and_then(|seconds| FIT_EPOCH.checked_add(seconds as i64))
+            .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+    }
+
+    /// Numeric value of a developer field, by name regardless of case
+    pub fn developer_value(&self, name: &str) -> Option<f64> {
+        self.developer_fields
+            .iter()
+            .find(|field| field.name.eq_ignore_ascii_case(name))
+            .and_then(|field| field.value.as_f64())
+    }
+}
+
+struct FieldDefinition {
+    number: u8,
+    size: usize,
+    base_type: u8,
+}
+
+struct DeveloperFieldDefinition {
+    number: u8,
+    size: usize,
+    developer_index: u8,
+}
+
+/// Layout of the data messages of a local message type
+struct Definition {
+    number: u16,
+    big_endian: bool,
+    fields: Vec<FieldDefinition>,
+    developer_fields: Vec<DeveloperFieldDefinition>,
+}
+
+/// Name, type and scale of a developer field, from a field description message
+struct FieldDescription {
+    name: String,
+    units: Option<String>,
+    base_type: u8,
+    scale: Option<f64>,
+    offset: Option<f64>,
+}
+
+/// Decode the data messages of a FIT file, checking its CRC. Several files
+/// chained one after another are decoded in turn.
+pub fn decode(data: &[u8]) -> Result<Vec<Message>, String> {
+    let mut messages = Vec::new();
+
+    let mut rest = data;
+    while !rest.is_empty() {
+        let length = decode_file(rest, &mut messages)?;
+        rest = &rest[length..];
+    }
+
+    Ok(messages)
+}
+
+/// Decode a single file at the start of `data`, returning its length
+fn decode_file(data: &[u8], messages: &mut Vec<Message>) -> Result<usize, String> {
+    let header_size = data.first().copied().unwrap_or_default() as usize;
+    if header_size < 12 || data.len() < header_size || &data[8..12] != b".FIT" {
+        return Err("Invalid FIT file header".to_string());
+    }
+
+    let data_size = u32::from_le_bytes([data[4], data[5], data[6], data[7]]) as usize;
+    let end = header_size + data_size;
+    if data.len() < end + 2 {
+        return Err("The FIT file is truncated".to_string());
+    }
+
+    // The header CRC is optional and 0 when not set
+    if header_size >= 14 {
+        let header_crc = u16::from_le_bytes([data[12], data[13]]);
+        if header_crc != 0 && header_crc != crc(&data[..12]) {
+            return Err("The FIT file header is damaged".to_string());
+        }
+    }
+    if u16::from_le_bytes([data[end], data[end + 1]]) != crc(&data[..end]) {
+        return Err("The FIT file is damaged, its checksum does not match".to_string());
+    }
+
+    Decoder::default().decode(&data[header_size..end], messages)?;
+
+    Ok(end + 2)
+}
+
+/// CRC-16 that FIT files end with
+pub fn crc(data: &[u8]) -> u16 {
+    const TABLE: [u16; 16] = [
+        0x0000, 0xCC01, 0xD801, 0x1400, 0xF001, 0x3C00, 0x2800, 0xE401, 0xA001, 0x6C00, 0x7800, 0xB401,
+        0x5000, 0x9C01, 0x8801, 0x4400,
+    ];
+
+    data.iter().fold(0, |mut crc, byte| {
+        for nibble in [byte & 0x0F, byte >> 4] {
+            let low = TABLE[(crc & 0x0F) as usize];
+            crc = ((crc >> 4) & 0x0FFF) ^ low ^ TABLE[nibble as usize];
+        }
+        crc
+    })
+}
+
+/// State while decoding the records of a file
+#[derive(Default)]
+struct Decoder {
+    /// Definitions by local message type
+    definitions: HashMap<u8, Definition>,
+    /// Developer fields by developer data index and field number
+    descriptions: HashMap<(u8, u8), FieldDescription>,
+    /// Latest timestamp, which compressed timestamps are relative to
+    timestamp: Option<u32>,
+}
+
+impl Decoder {
+    fn decode(&mut self, data: &[u8], messages: &mut Vec<Message>) -> Result<(), String> {
+        let mut reader = Reader { data, position: 0 };
+
+        while reader.position < data.len() {
+            let header = reader.byte()?;
+
+            if header & 0x80 != 0 {
+                // Compressed timestamp header: a data message of local type 0
+                // to 3, timed by an offset of up to 31 seconds
+                let offset = (header & 0x1F) as u32;
+                let last = self
+                    .timestamp
+                    .ok_or_else(|| "Compressed timestamp before any timestamp in the FIT file".to_string())?;
+                let mut timestamp = (last & !0x1F).wrapping_add(offset);
+                if offset < last & 0x1F {
+                    timestamp = timestamp.wrapping_add(0x20);
+                }
+
+                let mut message = self.data_message(&mut reader, (header >> 5) & 0x03)?;
+                message.fields.insert(TIMESTAMP, Value::Unsigned(timestamp as u64));
+                self.timestamp = Some(timestamp);
+                messages.push(message);
+            } else if header & 0x40 != 0 {
+                self.definition(&mut reader, header & 0x0F, header & 0x20 != 0)?;
+            } else {
+                let message = self.data_message(&mut reader, header & 0x0F)?;
+                if let Some(timestamp) = message.value(TIMESTAMP) {
+                    self.timestamp = Some(timestamp as u32);
+                }
+                if message.number == FIELD_DESCRIPTION {
+                    self.describe(&message);
+                }
+                messages.push(message);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn definition(&mut self, reader: &mut Reader, local_type: u8, has_developer_fields: bool) -> Result<(), String> {
+        let header = reader.take(5)?;
+        let big_endian = header[1] == 1;
+        let number = if big_endian {
+            u16::from_be_bytes([header[2], header[3]])
+        } else {
+            u16::from_le_bytes([header[2], header[3]])
+        };
+
+        let fields = reader
+            .take(header[4] as usize * 3)?
+            .chunks(3)
+            .map(|field| FieldDefinition {
+                number: field[0],
+                size: field[1] as usize,
+                base_type: field[2],
+            })
+            .collect();
+
+        let developer_fields = if has_developer_fields {
+            let count = reader.byte()? as usize;
+            reader
+                .take(count * 3)?
+                .chunks(3)
+                .map(|field| DeveloperFieldDefinition {
+                    number: field[0],
+                    size: field[1] as usize,
+                    developer_index: field[2],
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        self.definitions.insert(
+            local_type,
+            Definition {
+                number,
+                big_endian,
+                fields,
+                developer_fields,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn data_message(&self, reader: &mut Reader, local_type: u8) -> Result<Message, String> {
+        let definition = self
+            .definitions
+            .get(&local_type)
+            .ok_or_else(|| format!("FIT data message of undefined local type {}", local_type))?;
+
+        let mut message = Message {
+            number: definition.number,
+            ..Default::default()
+        };
+
+        for field in &definition.fields {
+            let bytes = reader.take(field.size)?;
+            if let Some(value) = decode_value(bytes, field.base_type, definition.big_endian) {
+                message.fields.insert(field.number, value);
+            }
+        }
+
+        for field in &definition.developer_fields {
+            let bytes = reader.take(field.size)?;
+            // Fields without a description cannot be read and are skipped
+            let Some(description) = self.descriptions.get(&(field.developer_index, field.number)) else {
+                continue;
+            };
+            let Some(mut value) = decode_value(bytes, description.base_type, definition.big_endian) else {
+                continue;
+            };
+
+            if let Some(number) = value.as_f64() {
+                if description.scale.is_some() || description.offset.is_some() {
+                    value = Value::Float(number / description.scale.unwrap_or(1.0) - description.offset.unwrap_or(0.0));
+                }
+            }
+            message.developer_fields.push(DeveloperField {
+                name: description.name.clone(),
+                units: description.units.clone(),
+                value,
+            });
+        }
+
+        Ok(message)
+    }
+
+    /// Register the developer field of a field description message
+    fn describe(&mut self, message: &Message) {
+        let text = |field| match message.fields.get(&field) {
+            Some(Value::Text(text)) => Some(text.clone()),
+            _ => None,
+        };
+        let (Some(developer_index), Some(number), Some(base_type)) =
+            (message.value(0), message.value(1), message.value(2))
+        else {
+            return;
+        };
+
+        self.descriptions.insert(
+            (developer_index as u8, number as u8),
+            FieldDescription {
+                name: text(3).unwrap_or_default(),
+                units: text(8),
+                base_type: base_type as u8,
+                scale: message.value(6).filter(|scale| *scale != 0.0),
+                offset: message.value(7),
+            },
+        );
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], String> {
+        let bytes = self
+            .data
+            .get(self.position..self.position + length)
+            .ok_or_else(|| "The FIT file ends in the middle of a message".to_string())?;
+        self.position += length;
+
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+}
+
+/// Size in bytes of a single value of a base type
+fn base_type_size(base_type: u8) -> Option<usize> {
+    match base_type & 0x1F {
+        0 | 1 | 2 | 7 | 10 | 13 => Some(1),
+        3 | 4 | 11 => Some(2),
+        5 | 6 | 8 | 12 => Some(4),
+        9 | 14 | 15 | 16 => Some(8),
+        _ => None,
+    }
+}
+
+/// Decode a field, or `None` if it holds the invalid value of its type
+fn decode_value(bytes: &[u8], base_type: u8, big_endian: bool) -> Option<Value> {
+    match base_type & 0x1F {
+        // Null-terminated string
+        7 => {
+            let end = bytes.iter().position(|byte| *byte == 0).unwrap_or(bytes.len());
+            let text = String::from_utf8_lossy(&bytes[..end]).trim().to_string();
+            (!text.is_empty()).then_some(Value::Text(text))
+        }
+        13 => (!bytes.iter().all(|byte| *byte == 0xFF)).then(|| Value::Bytes(bytes.to_vec())),
+        kind => {
+            let size = match base_type_size(kind) {
+                Some(size) if !bytes.is_empty() && bytes.chunks_exact(size).remainder().is_empty() => size,
+                _ => return Some(Value::Bytes(bytes.to_vec())),
+            };
+
+            let mut values: Vec<Value> = bytes
+                .chunks_exact(size)
+                .filter_map(|chunk| decode_number(chunk, kind, big_endian))
+                .collect();
+            match (bytes.len() / size, values.len()) {
+                (_, 0) => None,
+                (1, _) => values.pop(),
+                _ => Some(Value::Array(values)),
+            }
+        }
+    }
+}
+
+fn decode_number(bytes: &[u8], kind: u8, big_endian: bool) -> Option<Value> {
+    let raw = if big_endian {
+        bytes.iter().fold(0u64, |raw, byte| raw << 8 | *byte as u64)
+    } else {
+        bytes.iter().rev().fold(0u64, |raw, byte| raw << 8 | *byte as u64)
+    };
+    let bits = bytes.len() as u32 * 8;
+    let all_ones = u64::MAX >> (64 - bits);
+
+    match kind {
+        // enum, uint8, uint16, uint32 and uint64
+        0 | 2 | 4 | 6 | 15 => (raw != all_ones).then_some(Value::Unsigned(raw)),
+        // uint8z, uint16z, uint32z and uint64z
+        10 | 11 | 12 | 16 => (raw != 0).then_some(Value::Unsigned(raw)),
+        // sint8, sint16, sint32 and sint64
+        1 | 3 | 5 | 14 => {
+            let shift = 64 - bits;
+            (raw != all_ones >> 1).then(|| Value::Signed(((raw << shift) as i64) >> shift))
+        }
+        8 => {
+            let value = f32::from_bits(raw as u32);
+            (raw != all_ones && value.is_finite()).then_some(Value::Float(value as f64))
+        }
+        9 => {
+            let value = f64::from_bits(raw);
+            (raw != all_ones && value.is_finite()).then_some(Value::Float(value))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,192 @@
+mod decoder;
+
+pub use decoder::{crc, decode, DeveloperField, Message, Value};
+
+use super::{Activity, ImportedFile};
+use crate::models::{ImportRowError, RoutePoint, WorkoutLap, WorkoutSample};
+use crate::utils::route::thin_out;
+use chrono::{DateTime, Duration, Utc};
+use decoder::TIMESTAMP;
+
+// Global message numbers
+const SESSION: u16 = 18;
+const LAP: u16 = 19;
+const RECORD: u16 = 20;
+
+/// Most sensor samples stored per workout, 3 hours at one per second
+const MAX_SAMPLES: usize = 10_800;
+
+/// Degrees in a semicircle, the unit of FIT positions
+const DEGREES_PER_SEMICIRCLE: f64 = 180.0 / 2_147_483_648.0;
+
+/// Whether the data is a FIT file, which has ".FIT" in its header
+pub fn is_fit(data: &[u8]) -> bool {
+    data.get(8..12) == Some(b".FIT".as_slice())
+}
+
+/// Read the sessions of a FIT activity file, each as a cardio workout with
+/// its laps and heart rate, power and cadence samples.
+///
+/// The distance, times, climb and calories recorded in the session take
+/// precedence over those of the track. Running power written by an app as a
+/// developer field named "Power" counts when the device itself records none.
+pub fn read(data: &[u8]) -> Result<ImportedFile, String> {
+    let messages = decode(data)?;
+    let records: Vec<&Message> = messages.iter().filter(|message| message.number == RECORD).collect();
+    let laps: Vec<&Message> = messages.iter().filter(|message| message.number == LAP).collect();
+    let sessions: Vec<&Message> = messages.iter().filter(|message| message.number == SESSION).collect();
+
+    let mut file = ImportedFile {
+        rows: records.len(),
+        ..Default::default()
+    };
+
+    // Files of activities that were cut short may have no session
+    if sessions.is_empty() {
+        let start = records.iter().find_map(|record| record.time(TIMESTAMP));
+        let Some(start) = start else {
+            return Err("The FIT file has no activity".to_string());
+        };
+        let workout = activity(None, &laps, &records).into_workout(start)?;
+        file.workouts.push(workout);
+        return Ok(file);
+    }
+
+    for (index, session) in sessions.into_iter().enumerate() {
+        let Some(start) = session
+            .time(2)
+            .or_else(|| records.iter().find_map(|record| record.time(TIMESTAMP)))
+        else {
+            continue;
+        };
+        // Elapsed times too large for a date are left out
+        let end = session
+            .scaled(7, 1000.0, 0.0)
+            .and_then(|seconds| Duration::try_milliseconds((seconds * 1000.0) as i64))
+            .and_then(|elapsed| start.checked_add_signed(elapsed))
+            .or_else(|| session.time(TIMESTAMP));
+
+        // Multisport files have a session per sport, each with its own laps and records
+        let session_laps: Vec<&Message> = laps
+            .iter()
+            .filter(|lap| within(lap, 2, start, end))
+            .copied()
+            .collect();
+        let session_records: Vec<&Message> = records
+            .iter()
+            .filter(|record| within(record, TIMESTAMP, start, end))
+            .copied()
+            .collect();
+
+        match activity(Some(session), &session_laps, &session_records).into_workout(start) {
+            Ok(workout) => file.workouts.push(workout),
+            Err(message) => file.errors.push(ImportRowError { line: index as u64 + 1, message }),
+        }
+    }
+
+    Ok(file)
+}
+
+/// Whether the time in a field of the message falls within a session
+fn within(message: &Message, field: u8, start: DateTime<Utc>, end: Option<DateTime<Utc>>) -> bool {
+    message
+        .time(field)
+        .is_some_and(|time| time >= start && !matches!(end, Some(end) if time > end))
+}
+
+fn activity(session: Option<&Message>, laps: &[&Message], records: &[&Message]) -> Activity {
+    let mut activity = Activity {
+        sport: session.and_then(sport),
+        laps: laps.iter().filter_map(|lap| read_lap(lap)).collect(),
+        ..Default::default()
+    };
+    for (index, lap) in activity.laps.iter_mut().enumerate() {
+        lap.lap = index as i32 + 1;
+    }
+
+    if let Some(session) = session {
+        activity.distance = session.scaled(9, 100_000.0, 0.0);
+        activity.moving_time = session.scaled(8, 1000.0, 0.0).map(|seconds| seconds.round() as i32);
+        activity.elapsed_time = session.scaled(7, 1000.0, 0.0).map(|seconds| seconds.round() as i32);
+        activity.elevation_gain = session.value(22);
+        activity.calories = integer(session, 11);
+    }
+
+    let mut samples = Vec::new();
+    for record in records {
+        let time = record.time(TIMESTAMP);
+
+        if let (Some(latitude), Some(longitude)) = (record.value(0), record.value(1)) {
+            activity.points.push(RoutePoint {
+                latitude: latitude * DEGREES_PER_SEMICIRCLE,
+                longitude: longitude * DEGREES_PER_SEMICIRCLE,
+                // Enhanced altitude has a larger range than altitude
+                elevation: record.scaled(78, 5.0, 500.0).or_else(|| record.scaled(2, 5.0, 500.0)),
+                time,
+            });
+        }
+
+        let sample = time.map(|time| WorkoutSample {
+            time,
+            heart_rate: integer(record, 3),
+            power: integer(record, 7).or_else(|| record.developer_value("power").map(|power| power.round() as i32)),
+            cadence: integer(record, 4),
+        });
+        // Devices may log several records per second
+        match sample {
+            Some(sample) if sample.heart_rate.is_some() || sample.power.is_some() || sample.cadence.is_some() => {
+                if samples.last().is_some_and(|last: &WorkoutSample| last.time == sample.time) {
+                    samples.pop();
+                }
+                samples.push(sample);
+            }
+            _ => {}
+        }
+    }
+    activity.samples = thin_out(samples, MAX_SAMPLES);
+
+    activity
+}
+
+fn read_lap(lap: &Message) -> Option<WorkoutLap> {
+    Some(WorkoutLap {
+        lap: 0,
+        start_time: lap.time(2)?,
+        elapsed_time: lap.scaled(7, 1000.0, 0.0).unwrap_or_default().round() as i32,
+        moving_time: lap.scaled(8, 1000.0, 0.0).unwrap_or_default().round() as i32,
+        distance: lap.scaled(9, 100_000.0, 0.0),
+        average_pace: None,
+        average_heart_rate: integer(lap, 15),
+        max_heart_rate: integer(lap, 16),
+        average_power: integer(lap, 19),
+        max_power: integer(lap, 20),
+        average_cadence: integer(lap, 17),
+        calories: integer(lap, 11),
+        elevation_gain: lap.value(21),
+    })
+}
+
+fn integer(message: &Message, field: u8) -> Option<i32> {
+    message.value(field).map(|value| value.round() as i32)
+}
+
+/// Activity type of a session, like "running" or "cycling indoor_cycling", for
+/// the sports and sub sports that map onto different exercises
+fn sport(session: &Message) -> Option<String> {
+    let sport = match session.value(5)? as u8 {
+        1 => "running",
+        2 => "cycling",
+        5 => "swimming",
+        11 => "walking",
+        17 => "hiking",
+        _ => return None,
+    };
+    let sub_sport = match session.value(6).map(|sub_sport| sub_sport as u8) {
+        Some(1) => " treadmill",
+        Some(6) => " indoor_cycling",
+        Some(17) => " lap_swimming",
+        _ => "",
+    };
+
+    Some(format!("{}{}", sport, sub_sport))
+}
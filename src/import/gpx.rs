@@ -0,0 +1,63 @@
+use super::{child, child_text, line, parse_time, route_point, Activity, ImportedFile};
+use crate::models::ImportRowError;
+use roxmltree::Document;
+
+/// Read the tracks of a GPX file, each as a cardio workout.
+///
+/// The activity type comes from the track's `type`, as written by Garmin and
+/// Strava. Track points that cannot be read are reported and left out.
+pub fn read(document: &Document) -> Result<ImportedFile, String> {
+    let root = document.root_element();
+    let tracks: Vec<_> = root.children().filter(|node| node.has_tag_name("trk")).collect();
+    if tracks.is_empty() {
+        return Err("The GPX file has no tracks".to_string());
+    }
+
+    // Start time of the recording, for tracks whose points have no times
+    let metadata_time = child(root, "metadata")
+        .and_then(|metadata| child_text(metadata, "time"))
+        .and_then(|time| parse_time(time).ok());
+
+    let mut file = ImportedFile::default();
+    for track in tracks {
+        let mut activity = Activity {
+            name: child_text(track, "name").map(str::to_string),
+            sport: child_text(track, "type").map(str::to_string),
+            notes: child_text(track, "desc").map(str::to_string),
+            ..Default::default()
+        };
+
+        for node in track.descendants().filter(|node| node.has_tag_name("trkpt")) {
+            file.rows += 1;
+
+            let point = route_point(
+                node.attribute("lat").unwrap_or_default(),
+                node.attribute("lon").unwrap_or_default(),
+                child_text(node, "ele"),
+                child_text(node, "time"),
+            );
+            match point {
+                Ok(point) => activity.points.push(point),
+                Err(message) => file.errors.push(ImportRowError { line: line(document, node), message }),
+            }
+        }
+
+        let date = activity.points.iter().find_map(|point| point.time).or(metadata_time);
+        match date {
+            Some(date) if !activity.points.is_empty() => match activity.into_workout(date) {
+                Ok(workout) => file.workouts.push(workout),
+                Err(message) => file.errors.push(ImportRowError { line: line(document, track), message }),
+            },
+            Some(_) => file.errors.push(ImportRowError {
+                line: line(document, track),
+                message: "Track has no points".to_string(),
+            }),
+            None => file.errors.push(ImportRowError {
+                line: line(document, track),
+                message: "Track has no times".to_string(),
+            }),
+        }
+    }
+
+    Ok(file)
+}
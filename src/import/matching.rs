@@ -0,0 +1,160 @@
+use crate::models::Exercise;
+
+/// Lowest similarity at which a name is taken to mean an exercise
+pub const MATCH_THRESHOLD: f64 = 0.75;
+
+/// Words that only name the default variant of an exercise, like "back" in
+/// "Barbell Back Squat", so names without them still match fully
+const DEFAULT_VARIANT_WORDS: [&str; 4] = ["back", "conventional", "flat", "standard"];
+
+/// Matches exercise names from other apps, like "Bench Press (Barbell)", onto
+/// exercises, like "Barbell Bench Press".
+///
+/// Names are compared word by word regardless of order. Words may differ by a
+/// plural "s" or, for longer words, one typo, and a word naming the exercise's
+/// equipment counts as matching even if the exercise name leaves it out.
+pub struct ExerciseMatcher<'a> {
+    candidates: Vec<Candidate<'a>>,
+}
+
+struct Candidate<'a> {
+    exercise: &'a Exercise,
+    words: Vec<String>,
+    compact: String,
+    equipment: Vec<String>,
+}
+
+impl<'a> ExerciseMatcher<'a> {
+    pub fn new(exercises: &'a [Exercise]) -> Self {
+        let candidates = exercises
+            .iter()
+            .map(|exercise| {
+                let name_words = words(&exercise.name);
+                Candidate {
+                    exercise,
+                    compact: name_words.concat(),
+                    words: name_words,
+                    equipment: exercise.equipment.as_deref().map(words).unwrap_or_default(),
+                }
+            })
+            .collect();
+
+        Self { candidates }
+    }
+
+    /// The most similar exercise and its similarity from 0 to 1, if it reaches
+    /// [`MATCH_THRESHOLD`]. Ties go to custom exercises, then to shorter names.
+    pub fn find(&self, name: &str) -> Option<(&'a Exercise, f64)> {
+        let source = words(name);
+        if source.is_empty() {
+            return None;
+        }
+        let compact = source.concat();
+
+        let mut best: Option<(&Candidate, f64)> = None;
+        for candidate in &self.candidates {
+            let score = if candidate.compact == compact {
+                1.0
+            } else {
+                similarity(&source, candidate)
+            };
+
+            let better = match best {
+                None => true,
+                Some((current, current_score)) => {
+                    score > current_score
+                        || (score == current_score && rank(candidate) < rank(current))
+                }
+            };
+            if better {
+                best = Some((candidate, score));
+            }
+        }
+
+        best.filter(|(_, score)| *score >= MATCH_THRESHOLD)
+            .map(|(candidate, score)| (candidate.exercise, score))
+    }
+}
+
+/// Sort key among equally similar candidates
+fn rank<'a>(candidate: &'a Candidate) -> (bool, usize, &'a str) {
+    (
+        candidate.exercise.owner_id.is_none(),
+        candidate.words.len(),
+        &candidate.exercise.name,
+    )
+}
+
+/// Share of the words of both names that have a match in the other name
+fn similarity(source: &[String], candidate: &Candidate) -> f64 {
+    let matched_source = source
+        .iter()
+        .filter(|word| {
+            candidate
+                .words
+                .iter()
+                .chain(&candidate.equipment)
+                .any(|other| words_match(word, other))
+        })
+        .count();
+
+    let candidate_words: Vec<&String> = candidate
+        .words
+        .iter()
+        .filter(|word| {
+            !DEFAULT_VARIANT_WORDS.contains(&word.as_str()) || source.iter().any(|other| other == *word)
+        })
+        .collect();
+    let matched_candidate = candidate_words
+        .iter()
+        .filter(|word| source.iter().any(|other| words_match(word, other)))
+        .count();
+
+    let total = source.len() + candidate_words.len();
+    if total == 0 {
+        return 0.0;
+    }
+
+    (matched_source + matched_candidate) as f64 / total as f64
+}
+
+fn words_match(a: &str, b: &str) -> bool {
+    a == b
+        || singular(a) == singular(b)
+        || (a.len() >= 5 && b.len() >= 5 && edit_distance(a, b) <= 1)
+}
+
+fn singular(word: &str) -> &str {
+    match word.strip_suffix('s') {
+        Some(stem) if stem.len() >= 3 => stem,
+        _ => word,
+    }
+}
+
+/// Lowercase words of a name, e.g. `["farmers", "walk", "dumbbell"]` for
+/// "Farmer's Walk (Dumbbell)"
+fn words(name: &str) -> Vec<String> {
+    name.to_lowercase()
+        .replace(['\'', '\u{2019}'], "")
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Levenshtein distance between two words
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
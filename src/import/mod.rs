@@ -0,0 +1,328 @@
+pub mod csv;
+pub mod fit;
+pub mod gpx;
+mod matching;
+pub mod tcx;
+#[cfg(test)]
+mod tests;
+
+pub use matching::ExerciseMatcher;
+
+use crate::models::{
+    ImportRowError, MeasurementType, RoutePoint, RouteSplit, RouteTotals, UnitSystem, WorkoutLap, WorkoutSample,
+    WorkoutSetInput,
+};
+use crate::utils::route;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use roxmltree::{Document, Node};
+
+/// Longest workout or exercise name that is kept; longer names are cut off
+pub const MAX_NAME_LENGTH: usize = 100;
+
+/// Largest weight or distance a set can store
+const MAX_SET_VALUE: f64 = 9999.99;
+
+/// Most reps, or reps in reserve, of a set, the same as the API allows
+const MAX_REPS: f64 = 10_000.0;
+
+/// Longest activity or set that is imported, in seconds
+const MAX_ACTIVITY_SECONDS: i32 = 31 * 24 * 60 * 60;
+
+/// Largest elevation gain a route can store, in m
+const MAX_ELEVATION_GAIN: f64 = 999_999.9;
+
+/// When a workout started. Files without an offset hold the user's local time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImportDate {
+    Utc(DateTime<Utc>),
+    Local(NaiveDateTime),
+}
+
+/// A workout read from another app's file. Exercise names still have to be
+/// matched onto exercises with [`ExerciseMatcher`].
+#[derive(Debug, Clone)]
+pub struct ImportedWorkout {
+    pub name: String,
+    pub date: ImportDate,
+    /// In seconds
+    pub duration: Option<i32>,
+    pub calories_burned: Option<i32>,
+    pub notes: Option<String>,
+    pub exercises: Vec<ImportedExercise>,
+    /// GPS route of a cardio workout
+    pub route: Option<ImportedRoute>,
+    /// Laps recorded by a device
+    pub laps: Vec<WorkoutLap>,
+    /// Heart rate, power and cadence recorded by a device
+    pub samples: Vec<WorkoutSample>,
+}
+
+/// An exercise entry read from a file, before its name is matched
+#[derive(Debug, Clone)]
+pub struct ImportedExercise {
+    /// Name as written in the file
+    pub name: String,
+    pub notes: Option<String>,
+    /// Sets in kg and km
+    pub sets: Vec<WorkoutSetInput>,
+}
+
+impl ImportedExercise {
+    /// Measurement type for a custom exercise created for this entry, guessed
+    /// from what its sets record
+    pub fn measurement_type(&self) -> MeasurementType {
+        if self.sets.iter().any(|set| set.weight.is_some()) {
+            MeasurementType::WeightReps
+        } else if self.sets.iter().any(|set| set.distance.is_some()) {
+            MeasurementType::Distance
+        } else if self.sets.iter().any(|set| set.reps.is_some()) {
+            MeasurementType::Reps
+        } else if self.sets.iter().any(|set| set.duration.is_some()) {
+            MeasurementType::Time
+        } else {
+            MeasurementType::WeightReps
+        }
+    }
+}
+
+/// A GPS route read from a file
+#[derive(Debug, Clone)]
+pub struct ImportedRoute {
+    /// Totals of the full track
+    pub totals: RouteTotals,
+    /// Per-km splits of the full track
+    pub splits: Vec<RouteSplit>,
+    /// Downsampled points to store
+    pub points: Vec<RoutePoint>,
+}
+
+impl ImportedRoute {
+    pub fn new(points: &[RoutePoint]) -> Self {
+        Self {
+            totals: route::totals(points),
+            splits: route::splits(points, UnitSystem::Metric),
+            points: route::downsample(points),
+        }
+    }
+}
+
+/// Workouts read from a file, and the rows that could not be read
+#[derive(Debug, Default)]
+pub struct ImportedFile {
+    pub workouts: Vec<ImportedWorkout>,
+    /// Data rows in the file, or track points in GPS files
+    pub rows: usize,
+    pub errors: Vec<ImportRowError>,
+}
+
+/// Read a CSV, GPX, TCX or FIT file, telling them apart by their content.
+/// `units` applies to CSV files; activity files are always metric.
+pub fn read(data: &[u8], units: UnitSystem) -> Result<ImportedFile, String> {
+    if fit::is_fit(data) {
+        return fit::read(data);
+    }
+
+    let data = data.strip_prefix("\u{feff}".as_bytes()).unwrap_or(data);
+    if data.iter().find(|byte| !byte.is_ascii_whitespace()) != Some(&b'<') {
+        return csv::read(data, units);
+    }
+
+    let text = std::str::from_utf8(data).map_err(|_| "The file is not valid UTF-8".to_string())?;
+    let document = Document::parse(text).map_err(|e| format!("Invalid XML file: {}", e))?;
+
+    match document.root_element().tag_name().name() {
+        "gpx" => gpx::read(&document),
+        "TrainingCenterDatabase" => tcx::read(&document),
+        name => Err(format!("Unsupported XML file with root element {}", name)),
+    }
+}
+
+/// An activity recorded by a GPS watch or app, which becomes a workout with a
+/// single cardio entry
+#[derive(Debug, Default)]
+struct Activity {
+    name: Option<String>,
+    /// Activity type as written in the file, like "running" or "Biking"
+    sport: Option<String>,
+    notes: Option<String>,
+    points: Vec<RoutePoint>,
+    // Totals recorded by the device, which are more accurate than those of the
+    // track and the only ones for activities without positions
+    /// In km
+    distance: Option<f64>,
+    /// In seconds
+    moving_time: Option<i32>,
+    /// In seconds
+    elapsed_time: Option<i32>,
+    /// In m
+    elevation_gain: Option<f64>,
+    calories: Option<i32>,
+    laps: Vec<WorkoutLap>,
+    samples: Vec<WorkoutSample>,
+}
+
+impl Activity {
+    /// The workout of the activity, failing when its values are too large to store
+    fn into_workout(self, date: DateTime<Utc>) -> Result<ImportedWorkout, String> {
+        let exercise = sport_exercise(self.sport.as_deref());
+
+        let mut route = (self.points.len() >= 2).then(|| ImportedRoute::new(&self.points));
+        if let Some(route) = &mut route {
+            let totals = &mut route.totals;
+            totals.distance = self.distance.unwrap_or(totals.distance);
+            totals.moving_time = self.moving_time.unwrap_or(totals.moving_time);
+            totals.elapsed_time = self.elapsed_time.unwrap_or(totals.elapsed_time);
+            totals.elevation_gain = self.elevation_gain.or(totals.elevation_gain);
+        }
+
+        let totals = route.as_ref().map(|route| route.totals);
+        let distance = totals.map(|totals| totals.distance).or(self.distance);
+        let moving_time = totals.map(|totals| totals.moving_time).or(self.moving_time);
+        let elapsed_time = totals
+            .map(|totals| totals.elapsed_time)
+            .or(self.elapsed_time)
+            .or(moving_time);
+        let distance = distance
+            .filter(|km| *km > 0.0)
+            .map(|km| (km * 100.0).round() / 100.0);
+        let elevation_gain = route.as_ref().and_then(|route| route.totals.elevation_gain);
+
+        if distance.is_some_and(|km| km > MAX_SET_VALUE) {
+            return Err("Distance is too large".to_string());
+        }
+        if [moving_time, elapsed_time].into_iter().flatten().any(|seconds| seconds > MAX_ACTIVITY_SECONDS) {
+            return Err("Activity is too long".to_string());
+        }
+        if elevation_gain.is_some_and(|meters| meters > MAX_ELEVATION_GAIN) {
+            return Err("Elevation gain is too large".to_string());
+        }
+        for lap in &self.laps {
+            if lap.distance.is_some_and(|km| km > MAX_SET_VALUE) {
+                return Err(format!("Distance of lap {} is too large", lap.lap));
+            }
+            if lap.elapsed_time > MAX_ACTIVITY_SECONDS || lap.moving_time > MAX_ACTIVITY_SECONDS {
+                return Err(format!("Lap {} is too long", lap.lap));
+            }
+            if lap.elevation_gain.is_some_and(|meters| meters > MAX_ELEVATION_GAIN) {
+                return Err(format!("Elevation gain of lap {} is too large", lap.lap));
+            }
+        }
+
+        let set = WorkoutSetInput {
+            reps: None,
+            weight: None,
+            duration: moving_time.filter(|seconds| *seconds > 0),
+            distance,
+            rpe: None,
+            rir: None,
+            set_type: None,
+            completed: None,
+        };
+
+        Ok(ImportedWorkout {
+            name: self
+                .name
+                .map(|name| truncate_name(&name))
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| exercise.to_string()),
+            date: ImportDate::Utc(date),
+            duration: elapsed_time.filter(|seconds| *seconds > 0),
+            calories_burned: self.calories,
+            notes: self.notes,
+            exercises: vec![ImportedExercise {
+                name: exercise.to_string(),
+                notes: None,
+                sets: vec![set],
+            }],
+            route,
+            laps: self.laps,
+            samples: self.samples,
+        })
+    }
+}
+
+/// Exercise name for an activity type, like GPX's "running", TCX's "Biking" or
+/// FIT's "cycling indoor_cycling". Activities of unknown types count as runs.
+fn sport_exercise(sport: Option<&str>) -> &'static str {
+    let sport = sport.unwrap_or_default().to_lowercase();
+
+    if sport.contains("treadmill") {
+        if sport.contains("walk") {
+            "Treadmill Walking"
+        } else {
+            "Treadmill Running"
+        }
+    } else if ["indoor_cycling", "spin"].iter().any(|word| sport.contains(word)) {
+        "Stationary Bike"
+    } else if sport.contains("lap_swimming") {
+        "Swimming"
+    } else if ["bik", "cycl", "ride"].iter().any(|word| sport.contains(word)) {
+        "Cycling"
+    } else if sport.contains("walk") {
+        "Walking"
+    } else if sport.contains("hik") {
+        "Hiking"
+    } else if sport.contains("swim") {
+        "Open Water Swimming"
+    } else {
+        "Running"
+    }
+}
+
+/// First child element with the given name, ignoring namespaces
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(name))
+}
+
+/// Trimmed text of the first child element with the given name, unless empty
+fn child_text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    child(node, name)
+        .and_then(|child| child.text())
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+}
+
+/// Line of an element in the file
+fn line(document: &Document, node: Node) -> u64 {
+    document.text_pos_at(node.range().start).row as u64
+}
+
+fn parse_time(value: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|date| date.with_timezone(&Utc))
+        .map_err(|_| format!("Invalid time: {}", value))
+}
+
+fn parse_number(value: &str, name: &str) -> Result<f64, String> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|number| number.is_finite())
+        .ok_or_else(|| format!("Invalid {}: {}", name, value))
+}
+
+/// A point from its coordinates, elevation and time as written in the file
+fn route_point(
+    latitude: &str,
+    longitude: &str,
+    elevation: Option<&str>,
+    time: Option<&str>,
+) -> Result<RoutePoint, String> {
+    let latitude = parse_number(latitude, "latitude")?;
+    let longitude = parse_number(longitude, "longitude")?;
+    if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+        return Err(format!("Invalid position: {}, {}", latitude, longitude));
+    }
+
+    Ok(RoutePoint {
+        latitude,
+        longitude,
+        elevation: elevation.map(|value| parse_number(value, "elevation")).transpose()?,
+        time: time.map(parse_time).transpose()?,
+    })
+}
+
+/// Cut a name down to [`MAX_NAME_LENGTH`] characters
+fn truncate_name(name: &str) -> String {
+    name.trim().chars().take(MAX_NAME_LENGTH).collect()
+}
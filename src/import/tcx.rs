@@ -0,0 +1,78 @@
+use super::{child, child_text, line, parse_number, parse_time, route_point, Activity, ImportedFile};
+use crate::models::ImportRowError;
+use roxmltree::Document;
+
+/// Read the activities of a TCX file, each as a cardio workout.
+///
+/// The distance and time of the laps take precedence over those of the track.
+/// Trackpoints without a position, e.g. from a treadmill, are skipped, and
+/// those that cannot be read are reported and left out.
+pub fn read(document: &Document) -> Result<ImportedFile, String> {
+    let activities: Vec<_> = document
+        .root_element()
+        .descendants()
+        .filter(|node| node.has_tag_name("Activity"))
+        .collect();
+    if activities.is_empty() {
+        return Err("The TCX file has no activities".to_string());
+    }
+
+    let mut file = ImportedFile::default();
+    for node in activities {
+        let mut activity = Activity {
+            sport: node.attribute("Sport").map(str::to_string),
+            notes: child_text(node, "Notes").map(str::to_string),
+            ..Default::default()
+        };
+
+        let mut lap_distance = 0.0;
+        let mut lap_seconds = 0.0;
+        for lap in node.children().filter(|child| child.has_tag_name("Lap")) {
+            lap_distance += child_text(lap, "DistanceMeters")
+                .and_then(|value| parse_number(value, "distance").ok())
+                .unwrap_or_default();
+            lap_seconds += child_text(lap, "TotalTimeSeconds")
+                .and_then(|value| parse_number(value, "duration").ok())
+                .unwrap_or_default();
+
+            for trackpoint in lap.descendants().filter(|child| child.has_tag_name("Trackpoint")) {
+                file.rows += 1;
+
+                let Some(position) = child(trackpoint, "Position") else {
+                    continue;
+                };
+                let point = route_point(
+                    child_text(position, "LatitudeDegrees").unwrap_or_default(),
+                    child_text(position, "LongitudeDegrees").unwrap_or_default(),
+                    child_text(trackpoint, "AltitudeMeters"),
+                    child_text(trackpoint, "Time"),
+                );
+                match point {
+                    Ok(point) => activity.points.push(point),
+                    Err(message) => file.errors.push(ImportRowError {
+                        line: line(document, trackpoint),
+                        message,
+                    }),
+                }
+            }
+        }
+        activity.distance = (lap_distance > 0.0).then_some(lap_distance / 1000.0);
+        // Saturates, so that `into_workout` rejects activities that are too long
+        activity.moving_time = (lap_seconds > 0.0).then_some(lap_seconds.round() as i32);
+
+        // The ID of an activity is its start time
+        match child_text(node, "Id").map(parse_time) {
+            Some(Ok(date)) => match activity.into_workout(date) {
+                Ok(workout) => file.workouts.push(workout),
+                Err(message) => file.errors.push(ImportRowError { line: line(document, node), message }),
+            },
+            Some(Err(message)) => file.errors.push(ImportRowError { line: line(document, node), message }),
+            None => file.errors.push(ImportRowError {
+                line: line(document, node),
+                message: "Activity has no start time".to_string(),
+            }),
+        }
+    }
+
+    Ok(file)
+}
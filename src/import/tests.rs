@@ -0,0 +1,476 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests {
+    use crate::db::seed::exercise_library;
+    use crate::import::{self, csv, fit, ExerciseMatcher, ImportDate};
+    use crate::models::{
+        Exercise, MeasurementType, SetType, UnitSystem, WorkoutDetailsResponse, WorkoutExerciseDetails, WorkoutSet,
+    };
+    use chrono::{Duration, NaiveDate, TimeZone, Utc};
+    use uuid::Uuid;
+
+    fn library() -> Vec<Exercise> {
+        let now = Utc::now();
+        exercise_library()
+            .expect("Failed to parse exercise library")
+            .into_iter()
+            .map(|exercise| Exercise {
+                id: Uuid::new_v4(),
+                name: exercise.name,
+                description: exercise.description,
+                category: Some(exercise.category),
+                owner_id: None,
+                slug: Some(exercise.slug),
+                primary_muscles: exercise.primary_muscles,
+                secondary_muscles: exercise.secondary_muscles,
+                equipment: exercise.equipment,
+                mechanics: exercise.mechanics,
+                measurement_type: exercise.measurement_type,
+                created_at: now,
+                updated_at: now,
+            })
+            .collect()
+    }
+
+    /// A FIT file with the given definition and data messages
+    fn fit_file(messages: &[u8]) -> Vec<u8> {
+        let mut data = vec![14, 0x20, 0, 0];
+        data.extend((messages.len() as u32).to_le_bytes());
+        data.extend(b".FIT");
+        data.extend([0, 0]);
+        data.extend(messages);
+        data.extend(fit::crc(&data).to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_read_strong_csv() {
+        let data = "\u{feff}Date;Workout Name;Duration;Exercise Name;Set Order;Weight;Reps;Distance;Seconds;Notes;Workout Notes;RPE\n\
+            2024-03-01 18:30:00;Push;1h 5m;Bench Press (Barbell);W;40;10;0;0;;Felt strong;\n\
+            2024-03-01 18:30:00;Push;1h 5m;Bench Press (Barbell);1;82,5;5;0;0;Paused;;8\n\
+            2024-03-01 18:30:00;Push;1h 5m;Bench Press (Barbell);2;abc;5;0;0;;;\n\
+            2024-03-01 18:30:00;Push;1h 5m;Push Up;1;0;20;0;0;;;\n\
+            2024-03-03 09:00:00;Run;30m;Running;1;0;0;5;1800;;;\n";
+
+        let file = csv::read(data.as_bytes(), UnitSystem::Metric).unwrap();
+        assert_eq!(file.rows, 5);
+        assert_eq!(file.errors.len(), 1);
+        assert_eq!(file.errors[0].line, 4);
+        assert_eq!(file.errors[0].message, "Invalid weight: abc");
+
+        assert_eq!(file.workouts.len(), 2);
+        let push = &file.workouts[0];
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap().and_hms_opt(18, 30, 0).unwrap();
+        assert_eq!(push.date, ImportDate::Local(date));
+        assert_eq!(push.duration, Some(3900));
+        assert_eq!(push.notes.as_deref(), Some("Felt strong"));
+        assert_eq!(push.exercises.len(), 2);
+
+        let bench = &push.exercises[0];
+        assert_eq!(bench.notes.as_deref(), Some("Paused"));
+        assert_eq!(bench.sets.len(), 2);
+        assert_eq!(bench.sets[0].set_type, Some(SetType::Warmup));
+        assert_eq!(bench.sets[1].weight, Some(82.5));
+        assert_eq!(bench.sets[1].rpe, Some(8.0));
+
+        // Zeros are not logged values
+        let push_up = &push.exercises[1];
+        assert_eq!(push_up.sets[0].weight, None);
+        assert_eq!(push_up.measurement_type(), MeasurementType::Reps);
+
+        let run = &file.workouts[1].exercises[0];
+        assert_eq!(run.sets[0].distance, Some(5.0));
+        assert_eq!(run.sets[0].duration, Some(1800));
+        assert_eq!(run.measurement_type(), MeasurementType::Distance);
+    }
+
+    #[test]
+    fn test_read_csv_out_of_range() {
+        // Values the API would reject, and that records or aggregates could not store
+        let data = "Date;Workout Name;Exercise Name;Set Order;Weight;Reps;Distance;Seconds\n\
+            2024-03-01 18:30:00;Push;Bench Press (Barbell);1;100;1000000000;0;0\n\
+            2024-03-01 18:30:00;Push;Bench Press (Barbell);2;100;10000;0;0\n\
+            2024-03-03 09:00:00;Run;Running;1;0;0;5;99999999\n";
+
+        let file = csv::read(data.as_bytes(), UnitSystem::Metric).unwrap();
+        let errors: Vec<_> = file.errors.iter().map(|error| (error.line, error.message.as_str())).collect();
+        assert_eq!(errors, [(2, "Reps are too large"), (4, "Duration is too long")]);
+        assert_eq!(file.workouts.len(), 1);
+        assert_eq!(file.workouts[0].exercises[0].sets[0].reps, Some(10000));
+    }
+
+    #[test]
+    fn test_read_hevy_csv() {
+        let data = "title,start_time,end_time,description,exercise_title,superset_id,exercise_notes,set_index,set_type,weight_lbs,reps,distance_miles,duration_seconds,rpe\n\
+            Legs,\"15 Jan 2024, 18:30\",\"15 Jan 2024, 19:45\",,Squat (Barbell),,,0,warmup,135,5,,,\n\
+            Legs,\"15 Jan 2024, 18:30\",\"15 Jan 2024, 19:45\",,Squat (Barbell),,,1,normal,225,5,,,9\n\
+            Legs,\"15 Jan 2024, 18:30\",\"15 Jan 2024, 19:45\",,Squat (Barbell),,,2,superset,225,5,,,\n";
+
+        // Columns that name their units win over the requested units
+        let file = csv::read(data.as_bytes(), UnitSystem::Metric).unwrap();
+        assert_eq!(file.errors.len(), 1);
+        assert_eq!(file.errors[0].message, "Invalid set type: superset");
+
+        let workout = &file.workouts[0];
+        assert_eq!(workout.name, "Legs");
+        assert_eq!(workout.duration, Some(4500));
+
+        let squat = &workout.exercises[0];
+        assert_eq!(squat.sets[0].set_type, Some(SetType::Warmup));
+        assert_eq!(squat.sets[1].set_type, Some(SetType::Working));
+        assert!((squat.sets[1].weight.unwrap() - 102.058).abs() < 0.001);
+
+        let missing = csv::read(b"title,weight_kg\nLegs,100\n", UnitSystem::Metric).unwrap_err();
+        assert_eq!(missing, "Missing date column");
+    }
+
+    #[test]
+    fn test_write_csv_round_trip() {
+        let now = Utc::now();
+        let set = |set_type, weight| WorkoutSet {
+            id: Uuid::new_v4(),
+            workout_exercise_id: Uuid::new_v4(),
+            set_index: 0,
+            reps: Some(5),
+            weight: Some(weight),
+            duration: None,
+            distance: None,
+            rpe: None,
+            rir: None,
+            set_type,
+            completed: true,
+            created_at: now,
+            updated_at: now,
+        };
+        let workout = WorkoutDetailsResponse {
+            id: Uuid::new_v4(),
+            name: "Heavy, \"top\" sets".to_string(),
+            description: Some("Good day".to_string()),
+            date: Utc.with_ymd_and_hms(2024, 3, 1, 17, 30, 0).unwrap(),
+            duration: Some(3930),
+            calories_burned: None,
+            updated_at: now,
+            exercises: vec![WorkoutExerciseDetails {
+                id: Uuid::new_v4(),
+                exercise: library().remove(0),
+                sets: Some(2),
+                reps: Some(5),
+                weight: Some(100.0),
+                duration: None,
+                distance: None,
+                notes: None,
+                position: 0,
+                workout_sets: vec![set(SetType::Warmup, 60.0), set(SetType::Working, 100.0), set(SetType::Working, 100.0)],
+            }],
+        };
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap().and_hms_opt(18, 30, 0).unwrap();
+
+        let data = csv::write(&[workout], &[date]).unwrap();
+        let text = String::from_utf8(data.clone()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], csv::HEADERS.join(","));
+        assert_eq!(
+            lines[2],
+            "2024-03-01 18:30:00,\"Heavy, \"\"top\"\" sets\",1h 5m 30s,Barbell Bench Press,1,100,5,,,,Good day,"
+        );
+
+        let file = csv::read(&data, UnitSystem::Metric).unwrap();
+        assert!(file.errors.is_empty());
+        let workout = &file.workouts[0];
+        assert_eq!(workout.name, "Heavy, \"top\" sets");
+        assert_eq!(workout.date, ImportDate::Local(date));
+        assert_eq!(workout.duration, Some(3930));
+        let sets: Vec<_> = workout.exercises[0].sets.iter().map(|set| (set.set_type, set.weight)).collect();
+        assert_eq!(
+            sets,
+            vec![
+                (Some(SetType::Warmup), Some(60.0)),
+                (Some(SetType::Working), Some(100.0)),
+                (Some(SetType::Working), Some(100.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_match_exercise_names() {
+        let exercises = library();
+        let matcher = ExerciseMatcher::new(&exercises);
+        let matched = |name: &str| matcher.find(name).map(|(exercise, _)| exercise.name.as_str());
+
+        assert_eq!(matched("Bench Press (Barbell)"), Some("Barbell Bench Press"));
+        assert_eq!(matched("Squat (Barbell)"), Some("Barbell Back Squat"));
+        assert_eq!(matched("Deadlift (Barbell)"), Some("Deadlift"));
+        assert_eq!(matched("Lat Pulldown (Cable)"), Some("Lat Pulldown"));
+        assert_eq!(matched("Pull Up"), Some("Pull-Up"));
+        assert_eq!(matched("Bicep Curls (Dumbbell)"), Some("Dumbbell Bicep Curl"));
+        assert_eq!(matched("Bent Over Row (Barbell)"), Some("Barbell Bent-Over Row"));
+        assert_eq!(matched("Farmers Walk"), Some("Farmer's Walk"));
+        assert_eq!(matched("Romanian Deadlift (Dumbell)"), Some("Dumbbell Romanian Deadlift"));
+        assert_eq!(matched("Underwater Basket Weaving"), None);
+
+        let (_, score) = matcher.find("Incline Bench Press (Dumbbell)").unwrap();
+        assert_eq!(score, 1.0);
+    }
+
+    #[test]
+    fn test_read_gpx_track() {
+        // 25 points 100 m apart going north every 30 seconds and 1 m higher each
+        // time, with a minute's pause after the 13th
+        let start = Utc.with_ymd_and_hms(2024, 5, 4, 7, 0, 0).unwrap();
+        let mut points = String::new();
+        let mut seconds = 0;
+        for i in 0..25 {
+            let point = |seconds: i64| {
+                format!(
+                    "<trkpt lat=\"{:.7}\" lon=\"13.4\"><ele>{}</ele><time>{}</time></trkpt>\n",
+                    52.0 + i as f64 * 0.1 / 111.19508,
+                    50 + i,
+                    (start + Duration::seconds(seconds)).to_rfc3339()
+                )
+            };
+            points.push_str(&point(seconds));
+            if i == 12 {
+                seconds += 60;
+                points.push_str(&point(seconds));
+            }
+            seconds += 30;
+        }
+        let data = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+            <gpx version=\"1.1\" creator=\"Test\" xmlns=\"http://www.topografix.com/GPX/1/1\">\n\
+            <trk><name>Morning Run</name><type>running</type><trkseg>\n\
+            <trkpt lat=\"abc\" lon=\"13.4\"></trkpt>\n\
+            {}</trkseg></trk></gpx>",
+            points
+        );
+
+        let file = import::read(data.as_bytes(), UnitSystem::Imperial).unwrap();
+        assert_eq!(file.rows, 27);
+        assert_eq!(file.errors.len(), 1);
+        assert_eq!(file.errors[0].line, 4);
+        assert_eq!(file.errors[0].message, "Invalid latitude: abc");
+
+        let workout = &file.workouts[0];
+        assert_eq!(workout.name, "Morning Run");
+        assert_eq!(workout.date, ImportDate::Utc(start));
+        assert_eq!(workout.duration, Some(780));
+        assert_eq!(workout.exercises[0].name, "Running");
+        assert_eq!(workout.exercises[0].sets[0].distance, Some(2.4));
+        assert_eq!(workout.exercises[0].sets[0].duration, Some(720));
+
+        let route = workout.route.as_ref().unwrap();
+        assert!((route.totals.distance - 2.4).abs() < 0.001);
+        assert_eq!(route.totals.moving_time, 720);
+        assert_eq!(route.totals.elapsed_time, 780);
+        assert_eq!(route.totals.elevation_gain, Some(24.0));
+        // The pause is not stored
+        assert_eq!(route.points.len(), 25);
+
+        let splits: Vec<_> = route
+            .splits
+            .iter()
+            .map(|split| (split.distance, split.duration, split.pace, split.elevation_change))
+            .collect();
+        assert_eq!(
+            splits,
+            vec![
+                (1.0, 300, Some(300), Some(10.0)),
+                (1.0, 300, Some(300), Some(10.0)),
+                (0.4, 120, Some(300), Some(4.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_tcx_activities() {
+        let data = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <TrainingCenterDatabase xmlns="http://www.garmin.com/xmlschemas/TrainingCenterDatabase/v2">
+              <Activities>
+                <Activity Sport="Biking">
+                  <Id>2024-05-04T07:00:00Z</Id>
+                  <Lap StartTime="2024-05-04T07:00:00Z">
+                    <TotalTimeSeconds>60</TotalTimeSeconds>
+                    <DistanceMeters>500</DistanceMeters>
+                    <Track>
+                      <Trackpoint>
+                        <Time>2024-05-04T07:00:00Z</Time>
+                        <Position><LatitudeDegrees>52.0</LatitudeDegrees><LongitudeDegrees>13.4</LongitudeDegrees></Position>
+                        <AltitudeMeters>40</AltitudeMeters>
+                      </Trackpoint>
+                      <Trackpoint>
+                        <Time>2024-05-04T07:01:00Z</Time>
+                        <Position><LatitudeDegrees>52.0045</LatitudeDegrees><LongitudeDegrees>13.4</LongitudeDegrees></Position>
+                        <AltitudeMeters>45</AltitudeMeters>
+                      </Trackpoint>
+                    </Track>
+                  </Lap>
+                  <Notes>Commute</Notes>
+                </Activity>
+                <Activity Sport="Running">
+                  <Id>2024-05-05T18:00:00Z</Id>
+                  <Lap StartTime="2024-05-05T18:00:00Z">
+                    <TotalTimeSeconds>1800</TotalTimeSeconds>
+                    <DistanceMeters>5000</DistanceMeters>
+                    <Track>
+                      <Trackpoint><Time>2024-05-05T18:00:00Z</Time><HeartRateBpm><Value>120</Value></HeartRateBpm></Trackpoint>
+                    </Track>
+                  </Lap>
+                </Activity>
+              </Activities>
+            </TrainingCenterDatabase>"#;
+
+        let file = import::read(data.as_bytes(), UnitSystem::Metric).unwrap();
+        assert_eq!(file.rows, 3);
+        assert!(file.errors.is_empty());
+
+        let ride = &file.workouts[0];
+        assert_eq!(ride.name, "Cycling");
+        assert_eq!(ride.notes.as_deref(), Some("Commute"));
+        assert_eq!(ride.exercises[0].sets[0].distance, Some(0.5));
+        assert_eq!(ride.route.as_ref().unwrap().totals.elevation_gain, Some(5.0));
+
+        // Without positions the laps' totals are used
+        let treadmill = &file.workouts[1];
+        assert_eq!(treadmill.exercises[0].name, "Running");
+        assert_eq!(treadmill.exercises[0].sets[0].distance, Some(5.0));
+        assert_eq!(treadmill.exercises[0].sets[0].duration, Some(1800));
+        assert_eq!(treadmill.duration, Some(1800));
+        assert!(treadmill.route.is_none());
+
+        let unsupported = import::read(b"<kml></kml>", UnitSystem::Metric).unwrap_err();
+        assert_eq!(unsupported, "Unsupported XML file with root element kml");
+    }
+
+    #[test]
+    fn test_read_activities_out_of_range() {
+        let data = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <TrainingCenterDatabase xmlns="http://www.garmin.com/xmlschemas/TrainingCenterDatabase/v2">
+              <Activities>
+                <Activity Sport="Running">
+                  <Id>2024-05-05T18:00:00Z</Id>
+                  <Lap StartTime="2024-05-05T18:00:00Z">
+                    <TotalTimeSeconds>1e300</TotalTimeSeconds>
+                  </Lap>
+                </Activity>
+                <Activity Sport="Running">
+                  <Id>2024-05-06T18:00:00Z</Id>
+                  <Lap StartTime="2024-05-06T18:00:00Z">
+                    <DistanceMeters>10000000</DistanceMeters>
+                  </Lap>
+                </Activity>
+              </Activities>
+            </TrainingCenterDatabase>"#;
+
+        let file = import::read(data.as_bytes(), UnitSystem::Metric).unwrap();
+        assert!(file.workouts.is_empty());
+        let messages: Vec<&str> = file.errors.iter().map(|error| error.message.as_str()).collect();
+        assert_eq!(messages, ["Activity is too long", "Distance is too large"]);
+
+        // A track spanning decades and a climb the route can't store
+        let data = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <gpx version="1.1" creator="test" xmlns="http://www.topografix.com/GPX/1/1">
+              <trk><trkseg>
+                <trkpt lat="52.0" lon="13.4"><time>1970-01-01T00:00:00Z</time></trkpt>
+                <trkpt lat="52.0" lon="13.4"><time>2100-01-01T00:00:00Z</time></trkpt>
+              </trkseg></trk>
+              <trk><trkseg>
+                <trkpt lat="52.0" lon="13.4"><ele>0</ele><time>2024-05-05T18:00:00Z</time></trkpt>
+                <trkpt lat="52.0" lon="13.4"><ele>1e12</ele><time>2024-05-05T18:01:00Z</time></trkpt>
+              </trkseg></trk>
+            </gpx>"#;
+
+        let file = import::read(data.as_bytes(), UnitSystem::Metric).unwrap();
+        assert!(file.workouts.is_empty());
+        let messages: Vec<&str> = file.errors.iter().map(|error| error.message.as_str()).collect();
+        assert_eq!(messages, ["Activity is too long", "Elevation gain is too large"]);
+    }
+
+    #[test]
+    fn test_read_fit_activity() {
+        assert_eq!(fit::crc(b"123456789"), 0xBB3D);
+
+        let start = Utc.with_ymd_and_hms(2024, 5, 4, 7, 0, 0).unwrap();
+        let file = import::read(include_bytes!("testdata/run.fit"), UnitSystem::Metric).unwrap();
+        assert_eq!(file.rows, 146);
+        assert!(file.errors.is_empty());
+
+        let run = &file.workouts[0];
+        assert_eq!(run.name, "Running");
+        assert_eq!(run.date, ImportDate::Utc(start));
+        assert_eq!(run.duration, Some(780));
+        assert_eq!(run.calories_burned, Some(180));
+        assert_eq!(run.exercises[0].sets[0].distance, Some(2.4));
+        assert_eq!(run.exercises[0].sets[0].duration, Some(720));
+
+        // The session's totals take precedence over those of the track
+        let route = run.route.as_ref().unwrap();
+        assert_eq!(
+            (route.totals.distance, route.totals.moving_time, route.totals.elapsed_time),
+            (2.4, 720, 780)
+        );
+        assert_eq!(route.totals.elevation_gain, Some(12.0));
+        let splits: Vec<_> = route.splits.iter().map(|split| (split.distance, split.duration)).collect();
+        assert_eq!(splits, [(1.0, 300), (1.0, 300), (0.4, 120)]);
+
+        assert_eq!(run.laps.len(), 2);
+        let lap = &run.laps[1];
+        assert_eq!(lap.lap, 2);
+        assert_eq!(lap.start_time, start + Duration::seconds(300));
+        assert_eq!((lap.elapsed_time, lap.moving_time, lap.distance), (480, 420, Some(1.4)));
+        assert_eq!((lap.average_heart_rate, lap.max_heart_rate), (Some(145), Some(149)));
+        assert_eq!((lap.average_power, lap.max_power), (Some(250), Some(255)));
+        assert_eq!((lap.calories, lap.elevation_gain), (Some(105), Some(7.0)));
+
+        // Records with compressed timestamps follow on from the last full one,
+        // and the power comes from the app's developer field
+        assert_eq!(run.samples.len(), 146);
+        let last = run.samples.last().unwrap();
+        assert_eq!(last.time, start + Duration::seconds(780));
+        assert_eq!((last.power, last.cadence), (Some(250), Some(85)));
+        assert_eq!(run.samples[3].heart_rate, Some(143));
+
+        let ride = import::read(include_bytes!("testdata/indoor_ride.fit"), UnitSystem::Metric).unwrap();
+        let ride = &ride.workouts[0];
+        assert_eq!(ride.exercises[0].name, "Stationary Bike");
+        assert_eq!(ride.exercises[0].sets[0].distance, Some(5.0));
+        assert!(ride.route.is_none());
+        assert_eq!(ride.laps[0].average_power, Some(210));
+        // Invalid heart rates are left out of the samples
+        assert_eq!(ride.samples[0].heart_rate, None);
+        assert_eq!(ride.samples[0].power, Some(200));
+        assert_eq!(ride.samples[10].heart_rate, Some(130));
+
+        let mut damaged = include_bytes!("testdata/run.fit").to_vec();
+        damaged[200] ^= 0xFF;
+        let error = import::read(&damaged, UnitSystem::Metric).unwrap_err();
+        assert_eq!(error, "The FIT file is damaged, its checksum does not match");
+    }
+
+    #[test]
+    fn test_read_fit_out_of_range() {
+        // A session with an elapsed time too large for a date, and records whose
+        // compressed timestamps roll over the largest uint32
+        let mut messages = vec![0x40, 0, 0, 18, 0, 4, 253, 4, 0x86, 2, 4, 0x86, 7, 8, 0x8E, 9, 4, 0x86, 0];
+        messages.extend(0xFFFF_FFF0u32.to_le_bytes());
+        messages.extend(1_000_000_000u32.to_le_bytes());
+        messages.extend((i64::MAX - 1).to_le_bytes());
+        messages.extend(100_000u32.to_le_bytes());
+        messages.extend([0x41, 0, 0, 20, 0, 2, 253, 4, 0x86, 3, 1, 0x02, 1]);
+        messages.extend(0xFFFF_FFF0u32.to_le_bytes());
+        messages.push(120);
+        messages.extend([0x42, 0, 0, 20, 0, 1, 3, 1, 0x02, 0x80 | 2 << 5 | 0x05, 125]);
+
+        let file = import::read(&fit_file(&messages), UnitSystem::Metric).unwrap();
+        assert_eq!(file.rows, 2);
+        assert!(file.workouts.is_empty());
+        assert_eq!(file.errors.len(), 1);
+        assert_eq!((file.errors[0].line, file.errors[0].message.as_str()), (1, "Activity is too long"));
+
+        // Dates outside of the uint32 range are not valid
+        let mut messages = vec![0x40, 0, 0, 20, 0, 2, 253, 8, 0x8F, 3, 1, 0x02, 0];
+        messages.extend((u64::MAX - 1).to_le_bytes());
+        messages.push(120);
+        let error = import::read(&fit_file(&messages), UnitSystem::Metric).unwrap_err();
+        assert_eq!(error, "The FIT file has no activity");
+    }
+}
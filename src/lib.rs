@@ -1,7 +1,12 @@
 pub mod api;
 pub mod config;
 pub mod db;
+pub mod error;
+pub mod export;
+pub mod import;
+pub mod mailer;
 pub mod models;
+pub mod rate_limit;
 pub mod services;
 pub mod utils;
 
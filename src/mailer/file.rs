@@ -0,0 +1,58 @@
+use super::{Email, Mailer};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use futures::future::BoxFuture;
+use log::info;
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Mailer for development and tests. Emails are logged and, when an outbox
+/// directory is set, each one is also written to it as an `.eml` file.
+pub struct FileMailer {
+    from: String,
+    outbox: Option<PathBuf>,
+}
+
+impl FileMailer {
+    /// Create a mailer that writes to `outbox`, or only logs when it is `None`
+    pub fn new(from: &str, outbox: Option<&str>) -> Self {
+        Self {
+            from: from.to_string(),
+            outbox: outbox.map(PathBuf::from),
+        }
+    }
+
+    fn write(&self, outbox: &Path, email: &Email) -> Result<PathBuf> {
+        fs::create_dir_all(outbox)
+            .with_context(|| format!("Failed to create mail outbox {}", outbox.display()))?;
+
+        // Timestamp first so that a directory listing is in sending order
+        let path = outbox.join(format!("{}-{}.eml", Utc::now().format("%Y%m%dT%H%M%S%6f"), Uuid::new_v4().simple()));
+        let contents = format!(
+            "From: {}\r\nTo: {}\r\nSubject: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{}",
+            self.from, email.to, email.subject, email.body
+        );
+
+        fs::write(&path, contents)
+            .with_context(|| format!("Failed to write email to {}", path.display()))?;
+
+        Ok(path)
+    }
+}
+
+impl Mailer for FileMailer {
+    fn send<'a>(&'a self, email: &'a Email) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            match &self.outbox {
+                Some(outbox) => {
+                    let path = self.write(outbox, email)?;
+                    info!("Email \"{}\" to {} written to {}", email.subject, email.to, path.display());
+                }
+                None => info!("Email \"{}\" to {}:\n{}", email.subject, email.to, email.body),
+            }
+
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,56 @@
+pub mod file;
+pub mod smtp;
+pub mod templates;
+#[cfg(test)]
+mod tests;
+
+use crate::config::AppConfig;
+use anyhow::{anyhow, Result};
+use futures::future::BoxFuture;
+use log::warn;
+use std::sync::Arc;
+
+pub use file::FileMailer;
+pub use smtp::SmtpMailer;
+
+/// A plain-text email
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Sends emails to users
+pub trait Mailer: Send + Sync {
+    /// Deliver an email, failing if the server rejected it
+    fn send<'a>(&'a self, email: &'a Email) -> BoxFuture<'a, Result<()>>;
+}
+
+/// Mailer for deployments without email delivery. Every email fails to send,
+/// so its contents never reach the log.
+pub struct DisabledMailer;
+
+impl Mailer for DisabledMailer {
+    fn send<'a>(&'a self, _email: &'a Email) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async { Err(anyhow!("Email delivery is not configured")) })
+    }
+}
+
+/// Create the mailer selected by the configuration: SMTP when `SMTP_URL` is set.
+/// Otherwise emails are only logged with `MAIL_LOG=true` and written to
+/// `MAIL_OUTBOX_DIR` when it is set, since both expose their links.
+pub fn from_config(config: &AppConfig) -> Result<Arc<dyn Mailer>> {
+    let mailer: Arc<dyn Mailer> = match &config.smtp_url {
+        Some(url) => Arc::new(SmtpMailer::new(url, &config.mail_from)?),
+        None if config.mail_log || config.mail_outbox_dir.is_some() => {
+            Arc::new(FileMailer::new(&config.mail_from, config.mail_outbox_dir.as_deref()))
+        }
+        None => {
+            warn!("SMTP_URL is not set, so password reset and verification emails are not sent");
+            Arc::new(DisabledMailer)
+        }
+    };
+
+    Ok(mailer)
+}
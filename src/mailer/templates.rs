@@ -0,0 +1,30 @@
+use super::Email;
+
+/// Email with a link to choose a new password
+pub fn password_reset(to: &str, app_url: &str, token: &str, valid_minutes: i64) -> Email {
+    Email {
+        to: to.to_string(),
+        subject: "Reset your password".to_string(),
+        body: format!(
+            "Someone asked to reset the password of your Fitness Progress Tracker account.\n\n\
+             Open this link to choose a new password:\n{}/reset-password?token={}\n\n\
+             The link expires in {} minutes and can only be used once. \
+             If you did not ask for a reset, you can ignore this email.\n",
+            app_url.trim_end_matches('/'), token, valid_minutes
+        ),
+    }
+}
+
+/// Email with a link that confirms the user owns the address
+pub fn email_verification(to: &str, app_url: &str, token: &str, valid_hours: i64) -> Email {
+    Email {
+        to: to.to_string(),
+        subject: "Verify your email address".to_string(),
+        body: format!(
+            "Welcome to Fitness Progress Tracker!\n\n\
+             Open this link to verify your email address:\n{}/verify-email?token={}\n\n\
+             The link expires in {} hours.\n",
+            app_url.trim_end_matches('/'), token, valid_hours
+        ),
+    }
+}
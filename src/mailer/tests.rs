@@ -0,0 +1,25 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests {
+    use crate::mailer::{templates, FileMailer, Mailer};
+    use std::fs;
+    use uuid::Uuid;
+
+    #[actix_rt::test]
+    async fn test_file_mailer_writes_outbox() {
+        let outbox = std::env::temp_dir().join(format!("outbox-{}", Uuid::new_v4()));
+        let mailer = FileMailer::new("no-reply@example.com", outbox.to_str());
+
+        let email = templates::password_reset("user@example.com", "https://app.example.com/", "abc123", 60);
+        mailer.send(&email).await.unwrap();
+
+        let files: Vec<_> = fs::read_dir(&outbox).unwrap().map(|entry| entry.unwrap().path()).collect();
+        assert_eq!(files.len(), 1);
+
+        let contents = fs::read_to_string(&files[0]).unwrap();
+        assert!(contents.contains("To: user@example.com"));
+        assert!(contents.contains("https://app.example.com/reset-password?token=abc123"));
+
+        fs::remove_dir_all(&outbox).unwrap();
+    }
+}
@@ -1,25 +1,25 @@
-mod api;
-mod config;
-mod db;
-mod models;
-mod services;
-mod utils;
-
-use actix_web::{web, App, HttpServer, Responder, HttpResponse, get, middleware::Logger};
+use actix_web::{web, App, HttpServer, middleware::Logger};
 use log::{info, error};
 use std::process::exit;
 use utoipa_swagger_ui::SwaggerUi;
 
-use crate::api::middleware::JwtAuth;
-use crate::config::AppConfig;
-use crate::db::init_db;
-use crate::services::{UserService, WorkoutService};
-use crate::api::docs::ApiDoc;
-
-#[get("/health")]
-async fn health_check() -> impl Responder {
-    HttpResponse::Ok().json(serde_json::json!({ "status": "ok" }))
-}
+use fitness_progress_tracker::api;
+use fitness_progress_tracker::api::middleware::{JwtAuth, RateLimit, RateLimitKey};
+use fitness_progress_tracker::config::AppConfig;
+use fitness_progress_tracker::error;
+use fitness_progress_tracker::db::{init_db, run_migrations, DbPool};
+use fitness_progress_tracker::db::migrate::{migration_status, revert_migrations};
+use fitness_progress_tracker::db::seed::seed_exercise_library;
+use fitness_progress_tracker::services::{
+    AuthService, MfaService, UserService, WorkoutService, GoalService, MeasurementService, ExerciseService, RecordService,
+    StatsService, PreferencesService, ExportService, ImportService,
+};
+use fitness_progress_tracker::api::docs::ApiDoc;
+use fitness_progress_tracker::health_check;
+use fitness_progress_tracker::mailer;
+use fitness_progress_tracker::rate_limit::{self, RateLimiter};
+use fitness_progress_tracker::utils::derive_secret;
+use utoipa::OpenApi;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -53,17 +53,101 @@ async fn main() -> std::io::Result<()> {
         }
     };
     
+    // Handle management commands, e.g. `fitness-progress-tracker migrate status`
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(e) = run_command(&db_pool, &args).await {
+            error!("{:#}", e);
+            exit(1);
+        }
+        
+        return Ok(());
+    }
+    
+    // Bring the schema up to date
+    if config.run_migrations {
+        if let Err(e) = run_migrations(&db_pool).await {
+            error!("Failed to run database migrations: {:#}", e);
+            exit(1);
+        }
+    }
+    
+    // Seed the built-in exercise library
+    if let Err(e) = seed_exercise_library(&db_pool).await {
+        error!("Failed to seed exercise library: {}", e);
+    }
+    
+    // Create the mailer for password reset and verification emails
+    let mailer = match mailer::from_config(&config) {
+        Ok(mailer) => mailer,
+        Err(e) => {
+            error!("Failed to create mailer: {:#}", e);
+            exit(1);
+        }
+    };
+    
+    // Create rate limiters, sharing one token bucket store
+    let rate_limit_store = rate_limit::store(config.rate_limit.store, &db_pool);
+    let auth_limiter = RateLimiter::new("auth", config.rate_limit.auth, rate_limit_store.clone());
+    let login_limiter = RateLimiter::new("login", config.rate_limit.login, rate_limit_store.clone());
+    let api_limiter = RateLimiter::new("api", config.rate_limit.api, rate_limit_store);
+    let trust_proxy = config.rate_limit.trust_proxy;
+    
     // Create services
-    let user_service = UserService::new(
+    let auth_service = AuthService::new(
         db_pool.clone(),
         config.jwt_secret.clone(),
         config.jwt_expiration,
+        config.refresh_token_expiration,
     );
     
-    let workout_service = WorkoutService::new(db_pool.clone());
+    let mfa_service = MfaService::new(db_pool.clone());
+    
+    let user_service = UserService::new(
+        db_pool.clone(),
+        auth_service.clone(),
+        mfa_service.clone(),
+        login_limiter,
+        mailer,
+        config.app_url.clone(),
+    );
+    
+    let goal_service = GoalService::new(db_pool.clone());
+    
+    let record_service = RecordService::new(db_pool.clone());
+    
+    let preferences_service = PreferencesService::new(db_pool.clone());
+    
+    let stats_service = StatsService::new(db_pool.clone(), preferences_service.clone());
+    
+    let workout_service = WorkoutService::new(db_pool.clone(), goal_service.clone(), record_service.clone());
+    
+    let measurement_service = MeasurementService::new(db_pool.clone(), goal_service.clone());
+    
+    let exercise_service = ExerciseService::new(db_pool.clone());
+    
+    let import_service = ImportService::new(
+        db_pool.clone(),
+        workout_service.clone(),
+        exercise_service.clone(),
+        preferences_service.clone(),
+    );
+    
+    let export_service = ExportService::new(
+        db_pool.clone(),
+        user_service.clone(),
+        workout_service.clone(),
+        goal_service.clone(),
+        measurement_service.clone(),
+        exercise_service.clone(),
+        preferences_service.clone(),
+        derive_secret(&config.jwt_secret, "data-export-links"),
+        config.export_expiration,
+    );
+    export_service.spawn_cleanup();
     
     // Create JWT middleware
-    let jwt_middleware = JwtAuth::new(config.jwt_secret.clone());
+    let jwt_middleware = JwtAuth::new(auth_service.clone());
     
     // Create the OpenAPI document
     let openapi = ApiDoc::openapi();
@@ -77,8 +161,22 @@ async fn main() -> std::io::Result<()> {
             // Register the shared database pool
             .app_data(web::Data::new(db_pool.clone()))
             // Register services
+            .app_data(web::Data::new(auth_service.clone()))
+            .app_data(web::Data::new(mfa_service.clone()))
             .app_data(web::Data::new(user_service.clone()))
             .app_data(web::Data::new(workout_service.clone()))
+            .app_data(web::Data::new(goal_service.clone()))
+            .app_data(web::Data::new(measurement_service.clone()))
+            .app_data(web::Data::new(exercise_service.clone()))
+            .app_data(web::Data::new(record_service.clone()))
+            .app_data(web::Data::new(stats_service.clone()))
+            .app_data(web::Data::new(preferences_service.clone()))
+            .app_data(web::Data::new(export_service.clone()))
+            .app_data(web::Data::new(import_service.clone()))
+            // Report malformed bodies, query strings and paths as JSON errors
+            .app_data(error::json_config())
+            .app_data(error::query_config())
+            .app_data(error::path_config())
             // Register the health check endpoint
             .service(health_check)
             // Serve Swagger UI
@@ -86,29 +184,113 @@ async fn main() -> std::io::Result<()> {
                 SwaggerUi::new("/swagger-ui/{_:.*}")
                     .url("/api-docs/openapi.json", openapi.clone())
             )
-            // Register public API routes
+            // Register API routes. Scopes are matched in order, so the public
+            // auth and export download routes come first and everything else
+            // requires a JWT.
             .service(
                 web::scope("/api/v1")
                     .service(
                         web::scope("/auth")
+                            .wrap(RateLimit::new(auth_limiter.clone(), RateLimitKey::Ip, trust_proxy))
                             .service(api::auth::register)
                             .service(api::auth::login)
+                            .service(api::auth::refresh)
+                            .service(api::auth::forgot_password)
+                            .service(api::auth::reset_password)
+                            .service(api::auth::verify_email)
+                            .service(
+                                web::scope("/mfa")
+                                    .service(api::mfa::verify)
+                                    .service(
+                                        web::scope("")
+                                            .wrap(jwt_middleware.clone())
+                                            .service(api::mfa::setup)
+                                            .service(api::mfa::confirm)
+                                            .service(api::mfa::disable)
+                                            .service(api::mfa::regenerate_recovery_codes)
+                                    )
+                            )
+                            .service(
+                                web::scope("")
+                                    .wrap(jwt_middleware.clone())
+                                    .service(api::auth::logout)
+                                    .service(api::auth::logout_all)
+                            )
                     )
-            )
-            // Register protected API routes with JWT middleware
-            .service(
-                web::scope("/api/v1")
-                    .wrap(jwt_middleware.clone())
                     .service(
-                        web::scope("/users")
-                            .service(api::user::get_profile)
+                        web::scope("/exports")
+                            .wrap(RateLimit::new(auth_limiter.clone(), RateLimitKey::Ip, trust_proxy))
+                            .service(api::export::download_export)
                     )
                     .service(
-                        web::scope("/workouts")
-                            .service(api::workout::create_workout)
-                            .service(api::workout::get_workout)
-                            .service(api::workout::get_workouts)
-                            .service(api::workout::delete_workout)
+                        web::scope("")
+                            // Runs after JwtAuth, which is registered last, so requests count per user
+                            .wrap(RateLimit::new(api_limiter.clone(), RateLimitKey::User, trust_proxy))
+                            .wrap(jwt_middleware.clone())
+                            .service(
+                                web::scope("/users")
+                                    .service(api::user::get_profile)
+                                    .service(api::user::update_profile)
+                                    .service(api::user::change_password)
+                                    .service(api::user::delete_account)
+                                    .service(api::user::get_preferences)
+                                    .service(api::user::update_preferences)
+                                    .service(api::export::request_export)
+                                    .service(api::export::get_export)
+                            )
+                            .service(
+                                web::scope("/workouts")
+                                    .service(api::workout::create_workout)
+                                    // Before get_workout, which would take "export.csv" for an ID
+                                    .service(api::workout::export_workouts_csv)
+                                    .service(api::workout::import_workouts)
+                                    .service(api::workout::get_workout)
+                                    .service(api::workout::get_workout_route)
+                                    .service(api::workout::get_workout_laps)
+                                    .service(api::workout::get_workout_samples)
+                                    .service(api::workout::get_workouts)
+                                    .service(api::workout::delete_workout)
+                                    .service(api::workout::update_workout)
+                                    .service(api::workout::patch_workout)
+                                    .service(api::workout::add_workout_exercise)
+                                    .service(api::workout::update_workout_exercise)
+                                    .service(api::workout::reorder_workout_exercises)
+                                    .service(api::workout::remove_workout_exercise)
+                            )
+                            .service(
+                                web::scope("/goals")
+                                    .service(api::goal::create_goal)
+                                    .service(api::goal::get_goal)
+                                    .service(api::goal::get_goals)
+                                    .service(api::goal::update_goal)
+                                    .service(api::goal::delete_goal)
+                            )
+                            .service(
+                                web::scope("/measurements")
+                                    .service(api::measurement::create_measurement)
+                                    .service(api::measurement::get_measurement)
+                                    .service(api::measurement::get_measurements)
+                                    .service(api::measurement::update_measurement)
+                                    .service(api::measurement::delete_measurement)
+                            )
+                            .service(
+                                web::scope("/exercises")
+                                    .service(api::exercise::list_exercises)
+                                    .service(api::exercise::get_exercise)
+                                    .service(api::exercise::create_exercise)
+                                    .service(api::exercise::get_exercise_records)
+                                    .service(api::exercise::get_exercise_progression)
+                            )
+                            .service(
+                                web::scope("/records")
+                                    .service(api::record::get_records)
+                            )
+                            .service(
+                                web::scope("/stats")
+                                    .service(api::stats::get_summary)
+                                    .service(api::stats::get_streaks)
+                                    .service(api::stats::get_activity)
+                            )
                     )
             )
     })
@@ -116,3 +298,46 @@ async fn main() -> std::io::Result<()> {
     .run()
     .await
 }
+
+/// Run a management command given on the command line
+async fn run_command(db_pool: &DbPool, args: &[String]) -> anyhow::Result<()> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    
+    match args.as_slice() {
+        ["migrate"] | ["migrate", "up"] => run_migrations(db_pool).await,
+        ["migrate", "status"] => {
+            for migration in migration_status(db_pool).await? {
+                let state = match (migration.applied, migration.checksum_mismatch) {
+                    (true, true) => "applied, file changed",
+                    (true, false) => "applied",
+                    (false, _) => "pending",
+                };
+                
+                println!("{:>4}  {:<40} {}", migration.version, migration.description, state);
+            }
+            
+            Ok(())
+        }
+        ["migrate", "revert"] => revert(db_pool, None).await,
+        ["migrate", "revert", target] => {
+            let target = target
+                .parse::<i64>()
+                .map_err(|_| anyhow::anyhow!("Invalid target version: {}", target))?;
+            
+            revert(db_pool, Some(target)).await
+        }
+        _ => Err(anyhow::anyhow!(
+            "Unknown command: {}\nUsage: fitness-progress-tracker [migrate [up | status | revert [TARGET_VERSION]]]",
+            args.join(" ")
+        )),
+    }
+}
+
+/// Revert migrations and print which ones were reverted
+async fn revert(db_pool: &DbPool, target: Option<i64>) -> anyhow::Result<()> {
+    for version in revert_migrations(db_pool, target).await? {
+        println!("Reverted migration {}", version);
+    }
+    
+    Ok(())
+}
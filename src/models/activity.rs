@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use super::preferences::UnitSystem;
+use super::route::pace;
+
+/// A lap of an activity recorded by a device, as stored in the workout_laps table
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct WorkoutLap {
+    /// Number of the lap, starting at 1
+    #[schema(example = 1)]
+    pub lap: i32,
+    pub start_time: DateTime<Utc>,
+    /// Time from start to end including pauses, in seconds
+    #[schema(example = 300)]
+    pub elapsed_time: i32,
+    /// Time spent moving, in seconds
+    #[schema(example = 300)]
+    pub moving_time: i32,
+    /// In km, or miles in imperial units
+    #[schema(example = 1.0)]
+    pub distance: Option<f64>,
+    /// Moving seconds per km or mile
+    #[schema(example = 300)]
+    pub average_pace: Option<i32>,
+    /// In beats per minute
+    #[schema(example = 144)]
+    pub average_heart_rate: Option<i32>,
+    #[schema(example = 149)]
+    pub max_heart_rate: Option<i32>,
+    /// In watts
+    #[schema(example = 250)]
+    pub average_power: Option<i32>,
+    #[schema(example = 260)]
+    pub max_power: Option<i32>,
+    /// In revolutions per minute, or strides per minute when running
+    #[schema(example = 85)]
+    pub average_cadence: Option<i32>,
+    #[schema(example = 75)]
+    pub calories: Option<i32>,
+    /// Total climb, in m
+    #[schema(example = 5.0)]
+    pub elevation_gain: Option<f64>,
+}
+
+impl WorkoutLap {
+    /// Convert the distance stored in km and derive the pace in the same units
+    pub fn convert_units(&mut self, units: UnitSystem) {
+        self.average_pace = self
+            .distance
+            .and_then(|km| pace(self.moving_time as f64, km, units));
+        self.distance = self.distance.map(|km| units.distance(km));
+    }
+}
+
+/// Sensor readings at a point in time during an activity
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, ToSchema)]
+pub struct WorkoutSample {
+    pub time: DateTime<Utc>,
+    /// In beats per minute
+    #[schema(example = 142)]
+    pub heart_rate: Option<i32>,
+    /// In watts
+    #[schema(example = 250)]
+    pub power: Option<i32>,
+    /// In revolutions per minute, or strides per minute when running
+    #[schema(example = 85)]
+    pub cadence: Option<i32>,
+}
@@ -0,0 +1,166 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+use utoipa::{IntoParams, ToSchema};
+
+/// Muscle groups used for primary and secondary muscles
+pub const MUSCLE_GROUPS: &[&str] = &[
+    "abdominals", "abductors", "adductors", "biceps", "calves", "chest", "forearms",
+    "full_body", "glutes", "hamstrings", "hip_flexors", "lats", "lower_back", "neck",
+    "obliques", "quadriceps", "shoulders", "traps", "triceps", "upper_back",
+];
+
+/// Equipment an exercise can be performed with
+pub const EQUIPMENT: &[&str] = &[
+    "band", "barbell", "bicycle", "bodyweight", "cable", "dumbbell", "elliptical", "ez_bar",
+    "foam_roller", "jump_rope", "kettlebell", "machine", "medicine_ball", "none", "other",
+    "rowing_machine", "ski_erg", "smith_machine", "stair_climber", "stationary_bike",
+    "trap_bar", "treadmill",
+];
+
+/// Whether an exercise works several joints or a single one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum Mechanics {
+    Compound,
+    Isolation,
+}
+
+/// How performance of an exercise is recorded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum MeasurementType {
+    /// Sets of reps with an external load
+    WeightReps,
+    /// Sets of reps with bodyweight only
+    Reps,
+    /// Held or performed for a duration
+    Time,
+    /// Covered over a distance
+    Distance,
+}
+
+/// Exercise model that maps to the exercises table
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Exercise {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    /// Owner of a custom exercise, `null` for the shared catalog
+    pub owner_id: Option<Uuid>,
+    /// Stable identifier of built-in library exercises
+    pub slug: Option<String>,
+    pub primary_muscles: Vec<String>,
+    pub secondary_muscles: Vec<String>,
+    pub equipment: Option<String>,
+    pub mechanics: Option<Mechanics>,
+    pub measurement_type: MeasurementType,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Create custom exercise request
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct CreateExerciseRequest {
+    #[validate(length(min = 1, max = 100))]
+    #[schema(example = "Landmine Press")]
+    pub name: String,
+
+    #[schema(example = "Single-arm press with a barbell anchored in a corner")]
+    pub description: Option<String>,
+
+    #[validate(length(min = 1, max = 50))]
+    #[schema(example = "strength")]
+    pub category: Option<String>,
+
+    #[serde(default)]
+    #[validate(custom = "validate_muscle_groups")]
+    #[schema(example = json!(["shoulders", "chest"]))]
+    pub primary_muscles: Vec<String>,
+
+    #[serde(default)]
+    #[validate(custom = "validate_muscle_groups")]
+    #[schema(example = json!(["triceps"]))]
+    pub secondary_muscles: Vec<String>,
+
+    #[validate(custom = "validate_equipment")]
+    #[schema(example = "barbell")]
+    pub equipment: Option<String>,
+
+    pub mechanics: Option<Mechanics>,
+
+    /// Defaults to `weight_reps`
+    pub measurement_type: Option<MeasurementType>,
+}
+
+/// Filter and pagination options for listing exercises
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExerciseFilter {
+    /// Only include exercises in this category, e.g. "strength"
+    pub category: Option<String>,
+    /// Case-insensitive search on the exercise name
+    pub search: Option<String>,
+    /// Only include exercises with this primary muscle group, e.g. "chest"
+    pub muscle: Option<String>,
+    /// Only include exercises using this equipment, e.g. "dumbbell"
+    pub equipment: Option<String>,
+    /// Only include compound or isolation exercises
+    pub mechanics: Option<Mechanics>,
+    /// Only include exercises recorded this way
+    pub measurement_type: Option<MeasurementType>,
+    /// Page number, starting at 1
+    #[validate(range(min = 1, max = 10000))]
+    #[param(default = 1)]
+    pub page: Option<i64>,
+    /// Number of exercises per page
+    #[validate(range(min = 1, max = 100))]
+    #[param(default = 20)]
+    pub per_page: Option<i64>,
+}
+
+impl ExerciseFilter {
+    pub const DEFAULT_PER_PAGE: i64 = 20;
+
+    pub fn page(&self) -> i64 {
+        self.page.unwrap_or(1)
+    }
+
+    pub fn per_page(&self) -> i64 {
+        self.per_page.unwrap_or(Self::DEFAULT_PER_PAGE)
+    }
+}
+
+/// Paginated list of exercises
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ExerciseListResponse {
+    pub exercises: Vec<Exercise>,
+    #[schema(example = 1)]
+    pub page: i64,
+    #[schema(example = 20)]
+    pub per_page: i64,
+    /// Total number of exercises matching the filter
+    #[schema(example = 57)]
+    pub total: i64,
+}
+
+fn validate_muscle_groups(muscles: &[String]) -> Result<(), ValidationError> {
+    if muscles.iter().all(|muscle| MUSCLE_GROUPS.contains(&muscle.as_str())) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("unknown_muscle_group"))
+    }
+}
+
+fn validate_equipment(equipment: &str) -> Result<(), ValidationError> {
+    if EQUIPMENT.contains(&equipment) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("unknown_equipment"))
+    }
+}
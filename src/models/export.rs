@@ -0,0 +1,42 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use utoipa::{IntoParams, ToSchema};
+
+/// Progress of a data export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ExportStatus {
+    /// Waiting for the background task
+    Pending,
+    /// The archive is being generated
+    Running,
+    /// The archive can be downloaded until the export expires
+    Completed,
+    Failed,
+}
+
+/// Status of a data export
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DataExportResponse {
+    pub id: Uuid,
+    pub status: ExportStatus,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    /// When the download link stops working and the archive is deleted
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Link to the ZIP archive, relative to the server, once the export is completed.
+    /// It works without authentication until `expires_at`.
+    #[schema(example = "/api/v1/exports/123e4567-e89b-12d3-a456-426614174000/download?expires=1742637600&signature=5d41402abc4b2a76b9719d911017c592")]
+    pub download_url: Option<String>,
+}
+
+/// Signed download link parameters
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportDownloadQuery {
+    /// Unix time the link expires at
+    pub expires: i64,
+    pub signature: String,
+}
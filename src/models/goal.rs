@@ -0,0 +1,332 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::{Validate, ValidationError, ValidationErrors};
+use utoipa::ToSchema;
+
+/// Metric source a goal can be linked to for automatic progress tracking
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum GoalMetric {
+    /// Latest bodyweight from measurements, in kg
+    BodyWeight,
+    /// Latest body fat percentage from measurements
+    BodyFat,
+    /// Total distance of an exercise since the goal started, in km
+    ExerciseDistance,
+    /// Heaviest weight ever logged for an exercise, in kg
+    ExerciseMaxWeight,
+    /// Number of workouts since the goal started
+    WorkoutCount,
+    /// Number of workouts in the last 7 days
+    WorkoutsPerWeek,
+}
+
+impl GoalMetric {
+    /// Whether the metric is computed for a single exercise
+    pub fn requires_exercise(&self) -> bool {
+        matches!(self, GoalMetric::ExerciseDistance | GoalMetric::ExerciseMaxWeight)
+    }
+
+    /// Whether progress is measured from the value at goal creation
+    /// rather than from zero (e.g. losing or gaining bodyweight)
+    pub fn tracks_from_baseline(&self) -> bool {
+        matches!(self, GoalMetric::BodyWeight | GoalMetric::BodyFat)
+    }
+}
+
+/// Goal model that maps to the goals table
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Goal {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub target_value: Option<f64>,
+    pub current_value: Option<f64>,
+    pub unit: Option<String>, // e.g. "kg", "km", "minutes"
+    pub start_date: DateTime<Utc>,
+    pub target_date: Option<DateTime<Utc>>,
+    pub completed: bool,
+    pub metric: Option<GoalMetric>,
+    pub exercise_id: Option<Uuid>,
+    pub start_value: Option<f64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Goal {
+    /// Check the fields that depend on each other, e.g. after an update was
+    /// applied to the stored goal
+    pub fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+
+        if let Err(error) = validate_goal_dates(Some(self.start_date), self.target_date) {
+            errors.add("target_date", error);
+        }
+
+        if self.metric.is_some_and(|metric| metric.requires_exercise()) && self.exercise_id.is_none() {
+            errors.add("exercise_id", ValidationError::new("exercise_id_required_for_metric"));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Create goal request
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+#[validate(schema(function = "validate_create_goal"))]
+pub struct CreateGoalRequest {
+    #[validate(length(min = 1, max = 100))]
+    #[schema(example = "Run 100 km")]
+    pub name: String,
+
+    #[schema(example = "Cover 100 km before the end of the quarter")]
+    pub description: Option<String>,
+
+    #[validate(range(min = 0.0))]
+    #[schema(example = 100.0)]
+    pub target_value: Option<f64>,
+
+    #[validate(range(min = 0.0))]
+    #[schema(example = 12.5)]
+    pub current_value: Option<f64>,
+
+    #[validate(length(max = 50))]
+    #[schema(example = "km")]
+    pub unit: Option<String>,
+
+    pub start_date: DateTime<Utc>,
+
+    pub target_date: Option<DateTime<Utc>>,
+
+    /// Metric to track automatically; `current_value` is ignored when set
+    pub metric: Option<GoalMetric>,
+
+    /// Exercise the metric applies to, required for exercise metrics
+    pub exercise_id: Option<Uuid>,
+
+    /// Value progress is measured from; captured automatically for bodyweight and body fat
+    #[validate(range(min = 0.0))]
+    #[schema(example = 80.0)]
+    pub start_value: Option<f64>,
+}
+
+/// Update goal request
+///
+/// Fields that are omitted keep their current value, and optional fields
+/// sent as `null` are cleared.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+#[validate(schema(function = "validate_update_goal"))]
+pub struct UpdateGoalRequest {
+    #[validate(length(min = 1, max = 100))]
+    #[schema(example = "Run 120 km")]
+    pub name: Option<String>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<String>)]
+    pub description: Option<Option<String>>,
+
+    #[validate(range(min = 0.0))]
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<f64>, example = 120.0)]
+    pub target_value: Option<Option<f64>>,
+
+    #[validate(range(min = 0.0))]
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<f64>, example = 42.0)]
+    pub current_value: Option<Option<f64>>,
+
+    #[validate(length(max = 50))]
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<String>)]
+    pub unit: Option<Option<String>>,
+
+    pub start_date: Option<DateTime<Utc>>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<DateTime<Utc>>)]
+    pub target_date: Option<Option<DateTime<Utc>>>,
+
+    pub completed: Option<bool>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<GoalMetric>)]
+    pub metric: Option<Option<GoalMetric>>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<Uuid>)]
+    pub exercise_id: Option<Option<Uuid>>,
+
+    #[validate(range(min = 0.0))]
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<f64>)]
+    pub start_value: Option<Option<f64>>,
+}
+
+impl UpdateGoalRequest {
+    /// Apply the fields that were sent to a stored goal
+    pub fn apply(self, goal: &mut Goal) {
+        if let Some(name) = self.name {
+            goal.name = name;
+        }
+        if let Some(description) = self.description {
+            goal.description = description;
+        }
+        if let Some(target_value) = self.target_value {
+            goal.target_value = target_value;
+        }
+        if let Some(current_value) = self.current_value {
+            goal.current_value = current_value;
+        }
+        if let Some(unit) = self.unit {
+            goal.unit = unit;
+        }
+        if let Some(start_date) = self.start_date {
+            goal.start_date = start_date;
+        }
+        if let Some(target_date) = self.target_date {
+            goal.target_date = target_date;
+        }
+        if let Some(completed) = self.completed {
+            goal.completed = completed;
+        }
+        if let Some(metric) = self.metric {
+            goal.metric = metric;
+        }
+        if let Some(exercise_id) = self.exercise_id {
+            goal.exercise_id = exercise_id;
+        }
+        if let Some(start_value) = self.start_value {
+            goal.start_value = start_value;
+        }
+    }
+}
+
+/// Deserialize a field sent as `null` into `Some(None)`, so it can be told
+/// apart from an omitted field
+pub(crate) fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Goal response with computed progress information
+#[derive(Debug, Serialize, ToSchema)]
+pub struct GoalResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub target_value: Option<f64>,
+    pub current_value: Option<f64>,
+    pub unit: Option<String>,
+    pub start_date: DateTime<Utc>,
+    pub target_date: Option<DateTime<Utc>>,
+    pub completed: bool,
+    pub metric: Option<GoalMetric>,
+    pub exercise_id: Option<Uuid>,
+    pub start_value: Option<f64>,
+    /// Progress towards the target, between 0 and 100
+    #[schema(example = 12.5)]
+    pub progress_percentage: Option<f64>,
+    /// Whole days left until the target date, 0 once it has passed
+    #[schema(example = 42)]
+    pub days_remaining: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Goal> for GoalResponse {
+    fn from(goal: Goal) -> Self {
+        let progress_percentage =
+            progress_percentage(goal.start_value, goal.current_value, goal.target_value);
+        let days_remaining = goal
+            .target_date
+            .map(|target_date| (target_date - Utc::now()).num_days().max(0));
+
+        Self {
+            id: goal.id,
+            name: goal.name,
+            description: goal.description,
+            target_value: goal.target_value,
+            current_value: goal.current_value,
+            unit: goal.unit,
+            start_date: goal.start_date,
+            target_date: goal.target_date,
+            completed: goal.completed,
+            metric: goal.metric,
+            exercise_id: goal.exercise_id,
+            start_value: goal.start_value,
+            progress_percentage,
+            days_remaining,
+            created_at: goal.created_at,
+            updated_at: goal.updated_at,
+        }
+    }
+}
+
+/// Calculate progress as a percentage of the way from the start value
+/// (or zero) to the target, clamped to 0..=100
+pub fn progress_percentage(
+    start_value: Option<f64>,
+    current_value: Option<f64>,
+    target_value: Option<f64>,
+) -> Option<f64> {
+    let (current, target) = match (current_value, target_value) {
+        (Some(current), Some(target)) => (current, target),
+        _ => return None,
+    };
+
+    let progress = match start_value {
+        Some(start) if start != target => (current - start) / (target - start),
+        _ if target > 0.0 => current / target,
+        _ => return None,
+    };
+
+    Some((progress * 100.0).clamp(0.0, 100.0))
+}
+
+/// Whether the current value has reached the target. Goals whose target is
+/// below their start value (e.g. losing weight) are reached from above.
+pub fn is_target_reached(start_value: Option<f64>, current_value: Option<f64>, target_value: Option<f64>) -> bool {
+    match (start_value, current_value, target_value) {
+        (Some(start), Some(current), Some(target)) if target < start => current <= target,
+        (_, Some(current), Some(target)) => current >= target,
+        _ => false,
+    }
+}
+
+fn validate_goal_dates(
+    start_date: Option<DateTime<Utc>>,
+    target_date: Option<DateTime<Utc>>,
+) -> Result<(), ValidationError> {
+    if let (Some(start_date), Some(target_date)) = (start_date, target_date) {
+        if target_date < start_date {
+            return Err(ValidationError::new("target_date_before_start_date"));
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_create_goal(req: &CreateGoalRequest) -> Result<(), ValidationError> {
+    if let Some(metric) = req.metric {
+        if metric.requires_exercise() && req.exercise_id.is_none() {
+            return Err(ValidationError::new("exercise_id_required_for_metric"));
+        }
+    }
+
+    validate_goal_dates(Some(req.start_date), req.target_date)
+}
+
+fn validate_update_goal(req: &UpdateGoalRequest) -> Result<(), ValidationError> {
+    validate_goal_dates(req.start_date, req.target_date.flatten())
+}
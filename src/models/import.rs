@@ -0,0 +1,108 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use utoipa::{IntoParams, ToSchema};
+
+use super::preferences::UnitSelection;
+use super::route::RouteSummary;
+
+/// Options for importing workout history
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ImportQuery {
+    /// Only preview the import without saving anything
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Units of CSV weight and distance columns that do not name their units, defaults to `metric`
+    pub units: Option<UnitSelection>,
+    /// Create custom exercises for names that match no exercise, defaults to true.
+    /// Otherwise those exercises are left out.
+    pub create_exercises: Option<bool>,
+}
+
+/// A row or track point of the file that could not be imported
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ImportRowError {
+    /// Line in the file, counting the header as line 1, or the session number in FIT files
+    #[schema(example = 14)]
+    pub line: u64,
+    #[schema(example = "Invalid weight: 12kg")]
+    pub message: String,
+}
+
+/// What happens to the sets of an exercise name in the file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExerciseMatchAction {
+    /// Logged as an existing exercise
+    Matched,
+    /// Logged as a new custom exercise
+    Create,
+    /// Left out because custom exercises are not created
+    Skip,
+}
+
+/// How an exercise name in the file maps onto the exercise catalog
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ExerciseMatch {
+    /// Name in the file
+    #[schema(example = "Bench Press (Barbell)")]
+    pub name: String,
+    pub action: ExerciseMatchAction,
+    /// Matched exercise, or the custom exercise created for the name
+    pub exercise_id: Option<Uuid>,
+    #[schema(example = "Barbell Bench Press")]
+    pub exercise_name: Option<String>,
+    /// Similarity of the names from 0 to 1, for matched exercises
+    #[schema(example = 1.0)]
+    pub score: Option<f64>,
+}
+
+/// What happens to a workout in the file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportedWorkoutStatus {
+    /// Imported, or would be in a dry run
+    Import,
+    /// Skipped because a workout with the same name and start time exists
+    Duplicate,
+    /// Skipped because none of its exercises are imported
+    Empty,
+}
+
+/// A workout found in the file
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ImportedWorkoutPreview {
+    #[schema(example = "Push Day")]
+    pub name: String,
+    pub date: DateTime<Utc>,
+    pub status: ImportedWorkoutStatus,
+    /// Exercise entries that are imported
+    pub exercises: usize,
+    /// Sets that are imported
+    pub sets: usize,
+    /// ID of the new workout, unless this is a dry run
+    pub workout_id: Option<Uuid>,
+    /// Distance, times and per-km splits of the GPS route, for GPX, TCX and FIT files
+    pub route: Option<RouteSummary>,
+    /// Laps recorded by the device, for FIT files
+    pub laps: usize,
+    /// Heart rate, power and cadence samples recorded by the device, for FIT files
+    pub samples: usize,
+}
+
+/// Result or preview of a workout history import
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WorkoutImportResponse {
+    pub dry_run: bool,
+    /// Data rows in the file, track points in GPS files or records in FIT files
+    pub rows: usize,
+    /// Workouts imported, or that would be imported in a dry run
+    pub imported: usize,
+    /// Workouts skipped because they already exist
+    pub duplicates: usize,
+    pub workouts: Vec<ImportedWorkoutPreview>,
+    pub exercises: Vec<ExerciseMatch>,
+    /// Rows that were left out
+    pub errors: Vec<ImportRowError>,
+}
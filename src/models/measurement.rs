@@ -0,0 +1,113 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+use utoipa::{IntoParams, ToSchema};
+
+use super::goal::nullable;
+use super::preferences::{UnitSelection, UnitSystem};
+
+/// Measurement model that maps to the measurements table
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Measurement {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub date: DateTime<Utc>,
+    pub weight: Option<f64>, // in kg
+    pub body_fat: Option<f64>, // percentage
+    pub muscle_mass: Option<f64>, // in kg
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Measurement {
+    /// Convert the stored kg of weight and muscle mass
+    pub fn convert_units(&mut self, units: UnitSystem) {
+        self.weight = self.weight.map(|weight| units.weight(weight));
+        self.muscle_mass = self.muscle_mass.map(|muscle_mass| units.weight(muscle_mass));
+    }
+}
+
+/// Create measurement request
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct CreateMeasurementRequest {
+    pub date: DateTime<Utc>,
+
+    #[validate(range(min = 0.0, max = 999.99))]
+    #[schema(example = 72.4)]
+    pub weight: Option<f64>,
+
+    #[validate(range(min = 0.0, max = 99.99))]
+    #[schema(example = 18.5)]
+    pub body_fat: Option<f64>,
+
+    #[validate(range(min = 0.0, max = 999.99))]
+    #[schema(example = 34.1)]
+    pub muscle_mass: Option<f64>,
+
+    #[schema(example = "Morning, before breakfast")]
+    pub notes: Option<String>,
+}
+
+/// Update measurement request
+///
+/// Fields that are omitted keep their current value, and optional fields
+/// sent as `null` are cleared.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct UpdateMeasurementRequest {
+    pub date: Option<DateTime<Utc>>,
+
+    #[validate(range(min = 0.0, max = 999.99))]
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<f64>, example = 72.1)]
+    pub weight: Option<Option<f64>>,
+
+    #[validate(range(min = 0.0, max = 99.99))]
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<f64>, example = 18.2)]
+    pub body_fat: Option<Option<f64>>,
+
+    #[validate(range(min = 0.0, max = 999.99))]
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<f64>, example = 34.3)]
+    pub muscle_mass: Option<Option<f64>>,
+
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<String>)]
+    pub notes: Option<Option<String>>,
+}
+
+impl UpdateMeasurementRequest {
+    /// Apply the fields that were sent to a stored measurement
+    pub fn apply(self, measurement: &mut Measurement) {
+        if let Some(date) = self.date {
+            measurement.date = date;
+        }
+        if let Some(weight) = self.weight {
+            measurement.weight = weight;
+        }
+        if let Some(body_fat) = self.body_fat {
+            measurement.body_fat = body_fat;
+        }
+        if let Some(muscle_mass) = self.muscle_mass {
+            measurement.muscle_mass = muscle_mass;
+        }
+        if let Some(notes) = self.notes {
+            measurement.notes = notes;
+        }
+    }
+}
+
+/// Date range filter for listing measurements
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MeasurementFilter {
+    /// Only include measurements taken at or after this time
+    pub from: Option<DateTime<Utc>>,
+    /// Only include measurements taken at or before this time
+    pub to: Option<DateTime<Utc>>,
+    /// Units for weight and muscle mass, defaults to `metric`
+    pub units: Option<UnitSelection>,
+}
@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use utoipa::ToSchema;
+
+/// New TOTP secret, to be added to an authenticator app and confirmed with a code
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MfaSetupResponse {
+    /// Base32 secret for entering the key by hand
+    #[schema(example = "JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP")]
+    pub secret: String,
+    /// `otpauth://` URI, usually shown as a QR code
+    #[schema(example = "otpauth://totp/Fitness%20Progress%20Tracker:user%40example.com?secret=JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP&issuer=Fitness%20Progress%20Tracker")]
+    pub otpauth_uri: String,
+}
+
+/// A code from the authenticator app, or a recovery code
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct MfaCodeRequest {
+    #[validate(length(min = 1, max = 32))]
+    #[schema(example = "123456")]
+    pub code: String,
+}
+
+/// Disable two-factor authentication
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct MfaDisableRequest {
+    #[schema(example = "password123")]
+    pub password: String,
+
+    /// A code from the authenticator app, or a recovery code
+    #[validate(length(min = 1, max = 32))]
+    #[schema(example = "123456")]
+    pub code: String,
+}
+
+/// Complete a login that requires a second factor
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct MfaVerifyRequest {
+    /// Token from the login response
+    #[validate(length(min = 1))]
+    pub challenge_token: String,
+
+    /// A code from the authenticator app, or a recovery code
+    #[validate(length(min = 1, max = 32))]
+    #[schema(example = "123456")]
+    pub code: String,
+}
+
+/// Single-use recovery codes. They are only shown once.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RecoveryCodesResponse {
+    #[schema(example = json!(["7kq2m-xw9pd", "h4tnb-3rcjz"]))]
+    pub recovery_codes: Vec<String>,
+}
+
+/// Returned by login instead of tokens when the user has two-factor authentication enabled
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MfaChallengeResponse {
+    /// Always `true`, to tell this response apart from a successful login
+    #[schema(example = true)]
+    pub mfa_required: bool,
+    /// Short-lived token for `POST /auth/mfa/verify`
+    pub challenge_token: String,
+    /// Lifetime of the challenge token in seconds
+    #[schema(example = 300)]
+    pub expires_in: u64,
+}
@@ -1,11 +1,62 @@
 // Export all model modules
+pub mod activity;
+pub mod exercise;
+pub mod export;
+pub mod goal;
+pub mod import;
+pub mod measurement;
+pub mod mfa;
+pub mod preferences;
+pub mod record;
+pub mod route;
+pub mod session;
+pub mod stats;
 pub mod user;
 pub mod workout;
+#[cfg(test)]
+mod tests;
 
 // Re-export common model types for convenience
-pub use user::{User, UserRegisterRequest, UserLoginRequest, UserProfileResponse, Claims};
+pub use user::{
+    User, UserRegisterRequest, UserLoginRequest, UserProfileResponse, Claims,
+    ForgotPasswordRequest, ResetPasswordRequest, VerifyEmailRequest, EmailTokenPurpose,
+    UpdateProfileRequest, ChangePasswordRequest, DeleteAccountRequest,
+};
+pub use exercise::{
+    Exercise, Mechanics, MeasurementType,
+    CreateExerciseRequest, ExerciseFilter, ExerciseListResponse,
+};
 pub use workout::{
-    Workout, Exercise, WorkoutExercise,
-    CreateWorkoutRequest, WorkoutExerciseInput,
+    Workout, WorkoutExercise, WorkoutSet, SetType, SetAggregate,
+    CreateWorkoutRequest, WorkoutExerciseInput, WorkoutSetInput,
+    PatchWorkoutRequest, UpdateWorkoutExerciseRequest, ReorderWorkoutExercisesRequest,
     WorkoutDetailsResponse, WorkoutExerciseDetails,
+    WorkoutFilter, WorkoutSort, WorkoutCursor, WorkoutPage, WorkoutInclude, WorkoutList,
+};
+pub use export::{ExportStatus, DataExportResponse, ExportDownloadQuery};
+pub use import::{
+    ImportQuery, ImportRowError, ExerciseMatchAction, ExerciseMatch, ImportedWorkoutStatus, ImportedWorkoutPreview,
+    WorkoutImportResponse,
+};
+pub use goal::{Goal, GoalMetric, CreateGoalRequest, UpdateGoalRequest, GoalResponse};
+pub use measurement::{
+    Measurement, CreateMeasurementRequest, UpdateMeasurementRequest, MeasurementFilter,
+};
+pub use session::{RevokeReason, SessionId, RefreshTokenRequest, TokenResponse, LoginResponse, LoginResult};
+pub use mfa::{
+    MfaSetupResponse, MfaCodeRequest, MfaDisableRequest, MfaVerifyRequest, RecoveryCodesResponse,
+    MfaChallengeResponse,
+};
+pub use preferences::{
+    UnitSystem, WeekStart, UserPreferences, UpdatePreferencesRequest, UnitSelection, UnitsQuery,
+};
+pub use record::{PersonalRecord, RecordType, RecordFilter, ExerciseRecordsResponse};
+pub use activity::{WorkoutLap, WorkoutSample};
+pub use route::{
+    RoutePoint, RouteTotals, RouteSplit, RouteSummary, RouteGeometry, RouteProperties, RouteFeature,
+};
+pub use stats::{
+    OneRepMaxFormula, Bucket, ProgressionFilter, ExerciseProgressionResponse, ProgressionPoint,
+    SummaryFilter, StatsSummary, CategoryBreakdown, MuscleGroupBreakdown,
+    StreakFilter, StreakStats, ActivityFilter, ActivityCalendar, ActivityDay,
 };
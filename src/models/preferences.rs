@@ -0,0 +1,182 @@
+use chrono::Weekday;
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+use utoipa::{IntoParams, ToSchema};
+
+/// Kilograms in a pound
+const KG_PER_LB: f64 = 0.45359237;
+
+/// Kilometres in a mile
+const KM_PER_MILE: f64 = 1.609344;
+
+/// Units weights and distances are shown in. Storage is always metric.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum UnitSystem {
+    /// kg and km
+    #[default]
+    Metric,
+    /// lb and miles
+    Imperial,
+}
+
+impl UnitSystem {
+    /// Convert a weight stored in kg
+    pub fn weight(&self, kg: f64) -> f64 {
+        match self {
+            UnitSystem::Metric => kg,
+            UnitSystem::Imperial => round2(kg / KG_PER_LB),
+        }
+    }
+
+    /// Convert a distance stored in km
+    pub fn distance(&self, km: f64) -> f64 {
+        match self {
+            UnitSystem::Metric => km,
+            UnitSystem::Imperial => round2(km / KM_PER_MILE),
+        }
+    }
+
+    /// Convert a weight in these units to kg
+    pub fn weight_to_kg(&self, weight: f64) -> f64 {
+        match self {
+            UnitSystem::Metric => weight,
+            UnitSystem::Imperial => weight * KG_PER_LB,
+        }
+    }
+
+    /// Convert a distance in these units to km
+    pub fn distance_to_km(&self, distance: f64) -> f64 {
+        match self {
+            UnitSystem::Metric => distance,
+            UnitSystem::Imperial => distance * KM_PER_MILE,
+        }
+    }
+}
+
+/// First day of the week, for weekly stats
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum WeekStart {
+    #[default]
+    Monday,
+    Saturday,
+    Sunday,
+}
+
+impl WeekStart {
+    pub fn weekday(&self) -> Weekday {
+        match self {
+            WeekStart::Monday => Weekday::Mon,
+            WeekStart::Saturday => Weekday::Sat,
+            WeekStart::Sunday => Weekday::Sun,
+        }
+    }
+
+    /// Days from the start of the week to the following Monday, to shift dates
+    /// before PostgreSQL's Monday-based `date_trunc('week', ...)`
+    pub fn days_to_monday(&self) -> i32 {
+        (7 - self.weekday().num_days_from_monday() as i32) % 7
+    }
+}
+
+/// A user's preferences. Users who never changed them get the defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct UserPreferences {
+    pub unit_system: UnitSystem,
+    /// IANA timezone days and weeks are computed in
+    #[schema(example = "Europe/Berlin")]
+    pub timezone: String,
+    pub week_start: WeekStart,
+    /// Default rest between sets, in seconds
+    #[schema(example = 90)]
+    pub rest_timer_seconds: i32,
+    /// BCP 47 language tag
+    #[schema(example = "en-GB")]
+    pub locale: String,
+}
+
+impl Default for UserPreferences {
+    fn default() -> Self {
+        Self {
+            unit_system: UnitSystem::Metric,
+            timezone: "UTC".to_string(),
+            week_start: WeekStart::Monday,
+            rest_timer_seconds: 90,
+            locale: "en".to_string(),
+        }
+    }
+}
+
+/// Partial update of the user's preferences
+///
+/// Fields that are omitted keep their current value.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct UpdatePreferencesRequest {
+    pub unit_system: Option<UnitSystem>,
+
+    #[validate(length(min = 1, max = 64))]
+    #[schema(example = "Europe/Berlin")]
+    pub timezone: Option<String>,
+
+    pub week_start: Option<WeekStart>,
+
+    #[validate(range(min = 0, max = 3600))]
+    #[schema(example = 120)]
+    pub rest_timer_seconds: Option<i32>,
+
+    #[validate(custom = "validate_locale")]
+    #[schema(example = "en-GB")]
+    pub locale: Option<String>,
+}
+
+/// Units requested for a response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UnitSelection {
+    Metric,
+    Imperial,
+    /// The unit system from the user's preferences
+    Preferred,
+}
+
+impl UnitSelection {
+    pub fn resolve(&self, preferred: UnitSystem) -> UnitSystem {
+        match self {
+            UnitSelection::Metric => UnitSystem::Metric,
+            UnitSelection::Imperial => UnitSystem::Imperial,
+            UnitSelection::Preferred => preferred,
+        }
+    }
+}
+
+/// Query parameter for endpoints that can convert units
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UnitsQuery {
+    /// Units for weights and distances, defaults to `metric`
+    pub units: Option<UnitSelection>,
+}
+
+/// Language tags like `en`, `de-AT` or `zh-Hant-TW`
+fn validate_locale(locale: &str) -> Result<(), ValidationError> {
+    let mut parts = locale.split('-');
+    let language = parts.next().unwrap_or_default();
+
+    let valid = locale.len() <= 35
+        && (2..=3).contains(&language.len())
+        && language.bytes().all(|b| b.is_ascii_alphabetic())
+        && parts.all(|part| (1..=8).contains(&part.len()) && part.bytes().all(|b| b.is_ascii_alphanumeric()));
+
+    if !valid {
+        return Err(ValidationError::new("invalid_locale"));
+    }
+
+    Ok(())
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
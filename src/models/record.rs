@@ -0,0 +1,256 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+use utoipa::{IntoParams, ToSchema};
+
+use super::stats::OneRepMaxFormula;
+
+/// Largest value `personal_records.value` can store
+const MAX_RECORD_VALUE: f64 = 9_999_999_999.99;
+
+/// Kind of personal record tracked per exercise
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum RecordType {
+    /// Heaviest weight lifted in a single set, in kg
+    MaxWeight,
+    /// Most reps in a single set at a given weight
+    MaxRepsAtWeight,
+    /// Best one-rep max estimated with the Epley formula, in kg
+    EstimatedOneRepMax,
+    /// Longest distance in a single set, in km
+    LongestDistance,
+    /// Fastest pace over a single set, in seconds per km
+    FastestPace,
+    /// Highest total weight × reps for the exercise in one workout, in kg
+    MaxVolume,
+}
+
+impl RecordType {
+    /// Whether a smaller value is the better one (e.g. pace)
+    pub fn lower_is_better(&self) -> bool {
+        matches!(self, RecordType::FastestPace)
+    }
+}
+
+/// PersonalRecord model that maps to the personal_records table.
+/// Every row is a record at the time it was set; the latest row per
+/// exercise, type and weight is the current record.
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct PersonalRecord {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub exercise_id: Uuid,
+    pub record_type: RecordType,
+    #[schema(example = 102.5)]
+    pub value: f64,
+    pub weight: Option<f64>, // in kg, the weight the record was set at
+    pub reps: Option<i32>,
+    pub previous_value: Option<f64>, // the record this one beat, if any
+    pub workout_id: Uuid,
+    pub workout_set_id: Option<Uuid>, // not set for per-workout records such as volume
+    pub achieved_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Query parameters for listing current records
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RecordFilter {
+    /// Only return records of this type
+    pub record_type: Option<RecordType>,
+}
+
+/// Current records and full record history of an exercise
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ExerciseRecordsResponse {
+    pub exercise_id: Uuid,
+    /// Current record for each type (and weight, for reps at weight)
+    pub current: Vec<PersonalRecord>,
+    /// Every record ever set, newest first
+    pub history: Vec<PersonalRecord>,
+}
+
+/// A completed working set used for record detection
+#[derive(Debug, Clone)]
+pub struct RecordSet {
+    pub workout_id: Uuid,
+    pub workout_set_id: Uuid,
+    pub date: DateTime<Utc>,
+    pub reps: Option<i32>,
+    pub weight: Option<f64>,
+    pub duration: Option<i32>,
+    pub distance: Option<f64>,
+}
+
+/// A record found in a user's set history for one exercise
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectedRecord {
+    pub record_type: RecordType,
+    pub value: f64,
+    pub weight: Option<f64>,
+    pub reps: Option<i32>,
+    pub previous_value: Option<f64>,
+    pub workout_id: Uuid,
+    pub workout_set_id: Option<Uuid>,
+    pub achieved_at: DateTime<Utc>,
+}
+
+/// Walk the set history of one exercise and return every record set in it.
+///
+/// `sets` must be ordered by workout date, with the sets of a workout next to
+/// each other. At most one record per type (and weight, for reps at weight) is
+/// reported per workout: the best one, compared with the record before the workout.
+pub fn detect_records(sets: &[RecordSet]) -> Vec<DetectedRecord> {
+    let mut records = Vec::new();
+    let mut best: Vec<(RecordType, Option<i64>, f64)> = Vec::new();
+
+    let mut start = 0;
+
+    while start < sets.len() {
+        let end = sets[start..]
+            .iter()
+            .position(|set| set.workout_id != sets[start].workout_id)
+            .map_or(sets.len(), |offset| start + offset);
+        let workout_sets = &sets[start..end];
+        start = end;
+
+        let mut session: Vec<((RecordType, Option<i64>), DetectedRecord)> = Vec::new();
+
+        for set in workout_sets {
+            for candidate in set_candidates(set) {
+                offer(&mut session, candidate);
+            }
+        }
+
+        let volume: f64 = workout_sets
+            .iter()
+            .filter_map(|set| Some(set.reps? as f64 * set.weight?))
+            .sum();
+
+        if volume > 0.0 {
+            let first = &workout_sets[0];
+            offer(&mut session, DetectedRecord {
+                record_type: RecordType::MaxVolume,
+                value: round2(volume),
+                weight: None,
+                reps: None,
+                previous_value: None,
+                workout_id: first.workout_id,
+                workout_set_id: None,
+                achieved_at: first.date,
+            });
+        }
+
+        for ((record_type, weight_key), mut record) in session {
+            let previous = best
+                .iter_mut()
+                .find(|(t, w, _)| *t == record_type && *w == weight_key);
+
+            match previous {
+                Some((_, _, value)) if !is_better(record_type, record.value, *value) => {}
+                Some((_, _, value)) => {
+                    record.previous_value = Some(*value);
+                    *value = record.value;
+                    records.push(record);
+                }
+                None => {
+                    best.push((record_type, weight_key, record.value));
+                    records.push(record);
+                }
+            }
+        }
+    }
+
+    records
+}
+
+/// Record values a single set could set
+fn set_candidates(set: &RecordSet) -> Vec<DetectedRecord> {
+    let weight = set.weight.filter(|weight| *weight > 0.0);
+    let reps = set.reps.filter(|reps| *reps > 0);
+    let distance = set.distance.filter(|distance| *distance > 0.0);
+    let duration = set.duration.filter(|duration| *duration > 0);
+
+    let record = |record_type, value: f64| DetectedRecord {
+        record_type,
+        value: round2(value),
+        weight: set.weight,
+        reps: set.reps,
+        previous_value: None,
+        workout_id: set.workout_id,
+        workout_set_id: Some(set.workout_set_id),
+        achieved_at: set.date,
+    };
+
+    let mut candidates = Vec::new();
+
+    if let Some(weight) = weight {
+        candidates.push(record(RecordType::MaxWeight, weight));
+    }
+
+    if let Some(reps) = reps {
+        // Bodyweight sets count as reps at no weight
+        let mut at_weight = record(RecordType::MaxRepsAtWeight, reps as f64);
+        at_weight.weight = weight.map(round2);
+        candidates.push(at_weight);
+    }
+
+    if let Some(one_rep_max) = weight.zip(reps).and_then(|(w, r)| OneRepMaxFormula::Epley.estimate(w, r)) {
+        candidates.push(record(RecordType::EstimatedOneRepMax, one_rep_max));
+    }
+
+    if let Some(distance) = distance {
+        candidates.push(record(RecordType::LongestDistance, distance));
+
+        if let Some(duration) = duration {
+            candidates.push(record(RecordType::FastestPace, duration as f64 / distance));
+        }
+    }
+
+    candidates
+}
+
+/// Keep the better of a candidate and the session's best for the same type and
+/// weight. Values too large to store, like the pace of a very slow set over a
+/// few meters, are skipped.
+fn offer(session: &mut Vec<((RecordType, Option<i64>), DetectedRecord)>, candidate: DetectedRecord) {
+    if candidate.value > MAX_RECORD_VALUE {
+        return;
+    }
+
+    let key = (candidate.record_type, weight_key(&candidate));
+
+    match session.iter_mut().find(|(k, _)| *k == key) {
+        Some((_, current)) => {
+            if is_better(candidate.record_type, candidate.value, current.value) {
+                *current = candidate;
+            }
+        }
+        None => session.push((key, candidate)),
+    }
+}
+
+/// Reps records are kept per weight; all other types have a single record
+fn weight_key(record: &DetectedRecord) -> Option<i64> {
+    match record.record_type {
+        RecordType::MaxRepsAtWeight => record.weight.map(|weight| (weight * 100.0).round() as i64),
+        _ => None,
+    }
+}
+
+fn is_better(record_type: RecordType, value: f64, than: f64) -> bool {
+    if record_type.lower_is_better() {
+        value < than
+    } else {
+        value > than
+    }
+}
+
+/// Round to the two decimals stored in the database
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
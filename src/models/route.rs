@@ -0,0 +1,149 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+use utoipa::ToSchema;
+
+use super::preferences::UnitSystem;
+
+/// A point of a GPS track
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoutePoint {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// In m
+    pub elevation: Option<f64>,
+    pub time: Option<DateTime<Utc>>,
+}
+
+/// Totals of a route, as stored in the workout_routes table
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RouteTotals {
+    /// In km
+    pub distance: f64,
+    /// Time spent moving, in seconds
+    pub moving_time: i32,
+    /// Time from the first to the last point, in seconds
+    pub elapsed_time: i32,
+    /// In m
+    pub elevation_gain: Option<f64>,
+}
+
+impl RouteTotals {
+    /// Summary of the route in the given units, with its splits
+    pub fn summary(&self, splits: Vec<RouteSplit>, units: UnitSystem) -> RouteSummary {
+        RouteSummary {
+            distance: units.distance(self.distance),
+            moving_time: self.moving_time,
+            elapsed_time: self.elapsed_time,
+            elevation_gain: self.elevation_gain,
+            average_pace: pace(self.moving_time as f64, self.distance, units),
+            splits,
+        }
+    }
+}
+
+/// Time taken per km, or per mile in imperial units, in seconds
+pub fn pace(seconds: f64, km: f64, units: UnitSystem) -> Option<i32> {
+    let distance = km / units.distance_to_km(1.0);
+    (seconds > 0.0 && distance > 0.0).then(|| (seconds / distance).round() as i32)
+}
+
+/// A km of a route, or a mile in imperial units. The last split may be shorter.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct RouteSplit {
+    /// Number of the split, starting at 1
+    #[schema(example = 1)]
+    pub split: i32,
+    #[schema(example = 1.0)]
+    pub distance: f64,
+    /// Moving time, in seconds
+    #[schema(example = 318)]
+    pub duration: i32,
+    /// Seconds per km or mile
+    #[schema(example = 318)]
+    pub pace: Option<i32>,
+    /// Elevation at the end of the split minus at its start, in m
+    #[schema(example = 4.5)]
+    pub elevation_change: Option<f64>,
+}
+
+/// Distance, times and splits of a route
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct RouteSummary {
+    /// In km, or miles in imperial units
+    #[schema(example = 5.02)]
+    pub distance: f64,
+    /// Time spent moving, in seconds
+    #[schema(example = 1596)]
+    pub moving_time: i32,
+    /// Time from start to finish including pauses, in seconds
+    #[schema(example = 1680)]
+    pub elapsed_time: i32,
+    /// Total climb, in m
+    #[schema(example = 42.0)]
+    pub elevation_gain: Option<f64>,
+    /// Moving seconds per km or mile
+    #[schema(example = 318)]
+    pub average_pace: Option<i32>,
+    pub splits: Vec<RouteSplit>,
+}
+
+/// GeoJSON geometry of a route
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RouteGeometry {
+    /// Always `LineString`
+    #[serde(rename = "type")]
+    #[schema(example = "LineString")]
+    pub kind: String,
+    /// `[longitude, latitude]` positions, with the elevation in m as a third
+    /// value when every point has one
+    #[schema(example = json!([[13.3777, 52.5163, 34.0], [13.3781, 52.5165, 35.0]]))]
+    pub coordinates: Vec<Vec<f64>>,
+}
+
+/// Properties of a route feature
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RouteProperties {
+    pub workout_id: Uuid,
+    #[serde(flatten)]
+    pub summary: RouteSummary,
+    /// Time of each position, null for points recorded without one
+    pub coordinate_times: Vec<Option<DateTime<Utc>>>,
+}
+
+/// Route of a workout as a GeoJSON feature
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RouteFeature {
+    /// Always `Feature`
+    #[serde(rename = "type")]
+    #[schema(example = "Feature")]
+    pub kind: String,
+    pub geometry: RouteGeometry,
+    pub properties: RouteProperties,
+}
+
+impl RouteFeature {
+    pub fn new(workout_id: Uuid, points: &[RoutePoint], summary: RouteSummary) -> Self {
+        let with_elevation = points.iter().all(|point| point.elevation.is_some());
+        let coordinates = points
+            .iter()
+            .map(|point| match point.elevation {
+                Some(elevation) if with_elevation => vec![point.longitude, point.latitude, elevation],
+                _ => vec![point.longitude, point.latitude],
+            })
+            .collect();
+
+        Self {
+            kind: "Feature".to_string(),
+            geometry: RouteGeometry {
+                kind: "LineString".to_string(),
+                coordinates,
+            },
+            properties: RouteProperties {
+                workout_id,
+                summary,
+                coordinate_times: points.iter().map(|point| point.time).collect(),
+            },
+        }
+    }
+}
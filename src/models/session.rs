@@ -0,0 +1,62 @@
+use super::{MfaChallengeResponse, UserProfileResponse};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+use utoipa::ToSchema;
+
+/// Why a session was revoked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum RevokeReason {
+    /// The user logged out of this session
+    Logout,
+    /// The user logged out of all sessions
+    LogoutAll,
+    /// A refresh token of the session was used twice, so it may have been stolen
+    RefreshTokenReuse,
+    /// The user reset their password
+    PasswordReset,
+    /// The user changed their password in another session
+    PasswordChange,
+}
+
+/// ID of the session an access token belongs to, set on requests by the JWT middleware
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionId(pub Uuid);
+
+/// Refresh token request
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct RefreshTokenRequest {
+    #[validate(length(min = 1))]
+    pub refresh_token: String,
+}
+
+/// A new access token and refresh token
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TokenResponse {
+    /// Short-lived JWT access token, sent as `Authorization: Bearer <token>`
+    pub token: String,
+    /// Single-use token for `POST /auth/refresh`. Each refresh returns a new one.
+    pub refresh_token: String,
+    /// Lifetime of the access token in seconds
+    #[schema(example = 900)]
+    pub expires_in: u64,
+}
+
+/// Response of a successful login
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LoginResponse {
+    pub user: UserProfileResponse,
+    #[serde(flatten)]
+    pub tokens: TokenResponse,
+}
+
+/// Response of the login endpoint: tokens, or a challenge when the user has
+/// two-factor authentication enabled
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum LoginResult {
+    LoggedIn(LoginResponse),
+    MfaRequired(MfaChallengeResponse),
+}
@@ -0,0 +1,421 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+use utoipa::{IntoParams, ToSchema};
+
+use super::preferences::{UnitSelection, UnitSystem};
+
+/// Formula used to estimate a one-rep max from a set
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OneRepMaxFormula {
+    /// weight × (1 + reps / 30)
+    #[default]
+    Epley,
+    /// weight × 36 / (37 − reps)
+    Brzycki,
+    /// weight × reps^0.1
+    Lombardi,
+}
+
+impl OneRepMaxFormula {
+    /// Estimate a one-rep max, or None for sets without weight or reps
+    pub fn estimate(&self, weight: f64, reps: i32) -> Option<f64> {
+        if weight <= 0.0 || reps < 1 {
+            return None;
+        }
+
+        if reps == 1 {
+            return Some(weight);
+        }
+
+        let reps = reps as f64;
+
+        match self {
+            OneRepMaxFormula::Epley => Some(weight * (1.0 + reps / 30.0)),
+            // Undefined from 37 reps on
+            OneRepMaxFormula::Brzycki if reps < 37.0 => Some(weight * 36.0 / (37.0 - reps)),
+            OneRepMaxFormula::Brzycki => None,
+            OneRepMaxFormula::Lombardi => Some(weight * reps.powf(0.1)),
+        }
+    }
+}
+
+/// Length of the periods a series is grouped into
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Bucket {
+    Day,
+    /// Weeks start on the user's first day of the week
+    #[default]
+    Week,
+    Month,
+}
+
+impl Bucket {
+    /// Field name for PostgreSQL's date_trunc
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Bucket::Day => "day",
+            Bucket::Week => "week",
+            Bucket::Month => "month",
+        }
+    }
+}
+
+/// Query parameters for an exercise's progression series
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ProgressionFilter {
+    /// Formula for the estimated one-rep max, defaults to `epley`
+    pub formula: Option<OneRepMaxFormula>,
+    /// Period length, defaults to `week`
+    pub bucket: Option<Bucket>,
+    /// IANA timezone the periods are computed in, defaults to the user's timezone
+    #[validate(length(min = 1, max = 64))]
+    pub timezone: Option<String>,
+    /// Only include workouts at or after this time
+    pub from: Option<DateTime<Utc>>,
+    /// Only include workouts at or before this time
+    pub to: Option<DateTime<Utc>>,
+    /// Units for weights, defaults to `metric`
+    pub units: Option<UnitSelection>,
+}
+
+/// Strength progression of an exercise, one point per period with workouts
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ExerciseProgressionResponse {
+    pub exercise_id: Uuid,
+    pub formula: OneRepMaxFormula,
+    pub bucket: Bucket,
+    #[schema(example = "Europe/Berlin")]
+    pub timezone: String,
+    pub points: Vec<ProgressionPoint>,
+}
+
+impl ExerciseProgressionResponse {
+    /// Convert the weights and volumes of all points from kg
+    pub fn convert_units(&mut self, units: UnitSystem) {
+        for point in &mut self.points {
+            point.estimated_one_rep_max = point.estimated_one_rep_max.map(|weight| units.weight(weight));
+            point.top_set_weight = point.top_set_weight.map(|weight| units.weight(weight));
+            point.total_volume = units.weight(point.total_volume);
+        }
+    }
+}
+
+/// Progression of an exercise over one period
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ProgressionPoint {
+    /// First day of the period, in the requested timezone
+    pub period_start: NaiveDate,
+    /// Best estimated one-rep max of any set in the period, in kg
+    #[schema(example = 116.67)]
+    pub estimated_one_rep_max: Option<f64>,
+    /// Weight of the heaviest set, in kg
+    #[schema(example = 100.0)]
+    pub top_set_weight: Option<f64>,
+    /// Reps of the heaviest set
+    #[schema(example = 5)]
+    pub top_set_reps: Option<i32>,
+    /// Total weight × reps, in kg
+    #[schema(example = 1220.0)]
+    pub total_volume: f64,
+    pub sets: i64,
+    pub workouts: i64,
+}
+
+/// Query parameters for the training summary
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SummaryFilter {
+    /// Start of the period, defaults to the user's first workout
+    pub from: Option<DateTime<Utc>>,
+    /// End of the period, defaults to now
+    pub to: Option<DateTime<Utc>>,
+    /// Units for tonnage and distance, defaults to `metric`
+    pub units: Option<UnitSelection>,
+}
+
+/// Training volume and frequency over a period
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StatsSummary {
+    pub from: Option<DateTime<Utc>>,
+    pub to: DateTime<Utc>,
+    #[schema(example = 24)]
+    pub total_workouts: i64,
+    /// In seconds
+    #[schema(example = 86400)]
+    pub total_duration: i64,
+    #[schema(example = 9600)]
+    pub total_calories: i64,
+    /// Total weight × reps of completed working sets, in kg
+    #[schema(example = 152340.0)]
+    pub tonnage: f64,
+    /// In km
+    #[schema(example = 84.5)]
+    pub total_distance: f64,
+    #[schema(example = 3.5)]
+    pub sessions_per_week: f64,
+    pub by_category: Vec<CategoryBreakdown>,
+    pub by_muscle_group: Vec<MuscleGroupBreakdown>,
+}
+
+impl StatsSummary {
+    /// Convert tonnage from kg and distance from km, including the breakdowns
+    pub fn convert_units(&mut self, units: UnitSystem) {
+        self.tonnage = units.weight(self.tonnage);
+        self.total_distance = units.distance(self.total_distance);
+
+        for category in &mut self.by_category {
+            category.tonnage = units.weight(category.tonnage);
+            category.distance = units.distance(category.distance);
+        }
+
+        for muscle_group in &mut self.by_muscle_group {
+            muscle_group.tonnage = units.weight(muscle_group.tonnage);
+        }
+    }
+}
+
+/// Training volume for one exercise category
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CategoryBreakdown {
+    #[schema(example = "strength")]
+    pub category: Option<String>,
+    pub workouts: i64,
+    pub sets: i64,
+    pub tonnage: f64,
+    pub distance: f64,
+}
+
+/// Training volume for one muscle group, counted from the exercises' primary muscles
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MuscleGroupBreakdown {
+    #[schema(example = "chest")]
+    pub muscle_group: String,
+    pub workouts: i64,
+    pub sets: i64,
+    pub tonnage: f64,
+}
+
+/// Query parameters for workout streaks
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StreakFilter {
+    /// IANA timezone days are computed in, defaults to the user's timezone
+    #[validate(length(min = 1, max = 64))]
+    pub timezone: Option<String>,
+    /// Days without a workout allowed between two workout days before the daily streak breaks, defaults to 0
+    #[validate(range(min = 0, max = 6))]
+    pub rest_days: Option<i64>,
+}
+
+/// Current and longest workout streaks
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StreakStats {
+    #[schema(example = "Europe/Berlin")]
+    pub timezone: String,
+    pub rest_days: i64,
+    /// Days from the first to the last workout day of the ongoing daily streak
+    #[schema(example = 12)]
+    pub current_daily_streak: i64,
+    #[schema(example = 30)]
+    pub longest_daily_streak: i64,
+    /// Consecutive weeks with at least one workout, up to this week or last week
+    #[schema(example = 6)]
+    pub current_weekly_streak: i64,
+    #[schema(example = 20)]
+    pub longest_weekly_streak: i64,
+    pub last_workout_date: Option<NaiveDate>,
+}
+
+/// Current and longest run of a streak
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Streak {
+    pub current: i64,
+    pub longest: i64,
+}
+
+/// Longest number of days the activity calendar can cover
+pub const MAX_ACTIVITY_DAYS: i64 = 731;
+
+/// Query parameters for the activity calendar
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ActivityFilter {
+    /// IANA timezone days are computed in, defaults to the user's timezone
+    #[validate(length(min = 1, max = 64))]
+    pub timezone: Option<String>,
+    /// First day of the calendar, defaults to 364 days before `to`
+    pub from: Option<NaiveDate>,
+    /// Last day of the calendar, defaults to today
+    pub to: Option<NaiveDate>,
+    /// Units for volume, defaults to `metric`
+    pub units: Option<UnitSelection>,
+}
+
+/// Per-day activity grid, with an entry for every day in the range
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ActivityCalendar {
+    #[schema(example = "Europe/Berlin")]
+    pub timezone: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub days: Vec<ActivityDay>,
+}
+
+impl ActivityCalendar {
+    /// Convert the volume of every day from kg
+    pub fn convert_units(&mut self, units: UnitSystem) {
+        for day in &mut self.days {
+            day.volume = units.weight(day.volume);
+        }
+    }
+}
+
+/// Activity on a single day
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ActivityDay {
+    pub date: NaiveDate,
+    #[schema(example = 1)]
+    pub workouts: i64,
+    /// In seconds
+    #[schema(example = 3600)]
+    pub duration: i64,
+    /// Total weight × reps of completed working sets, in kg
+    #[schema(example = 6420.0)]
+    pub volume: f64,
+}
+
+/// Compute a daily streak. Workout days (sorted, without duplicates) belong to
+/// the same streak while at most `rest_days` days without a workout separate them,
+/// and a streak's length is the number of days from its first to its last workout
+/// day. The current streak is still alive if today could continue it.
+pub fn daily_streak(days: &[NaiveDate], today: NaiveDate, rest_days: i64) -> Streak {
+    streak(days, today, 1, rest_days)
+}
+
+/// Compute a weekly streak: consecutive weeks, starting on `first_day`, with at
+/// least one workout. The current streak is still alive until the end of this week.
+pub fn weekly_streak(days: &[NaiveDate], today: NaiveDate, first_day: Weekday) -> Streak {
+    let week_start = |day: &NaiveDate| {
+        let days_into_week = (day.weekday().num_days_from_monday() + 7 - first_day.num_days_from_monday()) % 7;
+        *day - Duration::days(days_into_week as i64)
+    };
+
+    let mut weeks: Vec<NaiveDate> = days.iter().map(week_start).collect();
+    weeks.dedup();
+
+    streak(&weeks, week_start(&today), 7, 0)
+}
+
+/// Streak over periods of `step` days, allowing `allowed_gap` empty periods in between
+fn streak(periods: &[NaiveDate], today: NaiveDate, step: i64, allowed_gap: i64) -> Streak {
+    let mut result = Streak::default();
+    let mut start = match periods.first() {
+        Some(first) => *first,
+        None => return result,
+    };
+
+    for pair in periods.windows(2) {
+        if (pair[1] - pair[0]).num_days() > (allowed_gap + 1) * step {
+            result.longest = result.longest.max(periods_between(start, pair[0], step));
+            start = pair[1];
+        }
+    }
+
+    let last = *periods.last().expect("periods is not empty");
+    let length = periods_between(start, last, step);
+    result.longest = result.longest.max(length);
+
+    if (today - last).num_days() <= (allowed_gap + 1) * step {
+        result.current = length;
+    }
+
+    result
+}
+
+fn periods_between(first: NaiveDate, last: NaiveDate, step: i64) -> i64 {
+    (last - first).num_days() / step + 1
+}
+
+/// Average number of workouts per week between two points in time.
+/// Periods shorter than a week count as one week.
+pub fn sessions_per_week(workouts: i64, from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    let weeks = (to - from).num_seconds() as f64 / (7.0 * 24.0 * 60.0 * 60.0);
+
+    round2(workouts as f64 / weeks.max(1.0))
+}
+
+/// A completed working set, already assigned to its period
+#[derive(Debug, Clone)]
+pub struct ProgressionSet {
+    pub period_start: NaiveDate,
+    pub workout_id: Uuid,
+    pub reps: Option<i32>,
+    pub weight: Option<f64>,
+}
+
+/// Build one progression point per period. `sets` must be ordered by period.
+pub fn progression_points(sets: &[ProgressionSet], formula: OneRepMaxFormula) -> Vec<ProgressionPoint> {
+    let mut points: Vec<ProgressionPoint> = Vec::new();
+    let mut workout_ids: Vec<Uuid> = Vec::new();
+
+    for set in sets {
+        if points.last().map(|point| point.period_start) != Some(set.period_start) {
+            workout_ids.clear();
+            points.push(ProgressionPoint {
+                period_start: set.period_start,
+                estimated_one_rep_max: None,
+                top_set_weight: None,
+                top_set_reps: None,
+                total_volume: 0.0,
+                sets: 0,
+                workouts: 0,
+            });
+        }
+
+        let point = points.last_mut().expect("a point was pushed for this period");
+
+        point.sets += 1;
+        if !workout_ids.contains(&set.workout_id) {
+            workout_ids.push(set.workout_id);
+            point.workouts += 1;
+        }
+
+        if let (Some(reps), Some(weight)) = (set.reps, set.weight) {
+            point.total_volume += reps as f64 * weight;
+
+            if let Some(estimate) = formula.estimate(weight, reps) {
+                let is_best = match point.estimated_one_rep_max {
+                    Some(best) => estimate > best,
+                    None => true,
+                };
+                if is_best {
+                    point.estimated_one_rep_max = Some(estimate);
+                }
+            }
+        }
+
+        // Heaviest set wins, then the one with most reps
+        let top = (point.top_set_weight.unwrap_or(0.0), point.top_set_reps.unwrap_or(0));
+        let candidate = (set.weight.unwrap_or(0.0), set.reps.unwrap_or(0));
+        if set.weight.is_some() && (candidate.0 > top.0 || (candidate.0 == top.0 && candidate.1 > top.1)) {
+            point.top_set_weight = set.weight;
+            point.top_set_reps = set.reps;
+        }
+    }
+
+    for point in &mut points {
+        point.estimated_one_rep_max = point.estimated_one_rep_max.map(round2);
+        point.total_volume = round2(point.total_volume);
+    }
+
+    points
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
@@ -0,0 +1,379 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests {
+    use crate::models::goal::{is_target_reached, progress_percentage, Goal, UpdateGoalRequest};
+    use crate::models::measurement::{Measurement, UpdateMeasurementRequest};
+    use crate::models::preferences::{UnitSystem, WeekStart};
+    use crate::models::record::{detect_records, DetectedRecord, RecordSet, RecordType};
+    use crate::models::user::lockout_duration;
+    use crate::models::stats::{
+        daily_streak, progression_points, sessions_per_week, weekly_streak, OneRepMaxFormula, ProgressionSet,
+    };
+    use crate::models::workout::{
+        SetAggregate, SetType, UpdateWorkoutExerciseRequest, WorkoutCursor, WorkoutFilter, WorkoutSetInput, WorkoutSort,
+    };
+    use chrono::{DateTime, Duration, NaiveDate, Utc, Weekday};
+    use uuid::Uuid;
+    use validator::Validate;
+
+    fn set(reps: i32, weight: f64, set_type: SetType) -> WorkoutSetInput {
+        WorkoutSetInput {
+            reps: Some(reps),
+            weight: Some(weight),
+            duration: None,
+            distance: None,
+            rpe: None,
+            rir: None,
+            set_type: Some(set_type),
+            completed: None,
+        }
+    }
+
+    #[test]
+    fn test_goal_progress_from_zero() {
+        // Without a start value progress is measured from zero
+        assert_eq!(progress_percentage(None, Some(25.0), Some(100.0)), Some(25.0));
+        assert_eq!(progress_percentage(None, Some(150.0), Some(100.0)), Some(100.0));
+        assert_eq!(progress_percentage(None, None, Some(100.0)), None);
+
+        assert!(is_target_reached(None, Some(100.0), Some(100.0)));
+        assert!(!is_target_reached(None, Some(99.0), Some(100.0)));
+    }
+
+    #[test]
+    fn test_goal_progress_towards_lower_target() {
+        // Losing weight from 90 kg to 80 kg
+        assert_eq!(progress_percentage(Some(90.0), Some(85.0), Some(80.0)), Some(50.0));
+        assert_eq!(progress_percentage(Some(90.0), Some(92.0), Some(80.0)), Some(0.0));
+
+        assert!(is_target_reached(Some(90.0), Some(79.5), Some(80.0)));
+        assert!(!is_target_reached(Some(90.0), Some(85.0), Some(80.0)));
+    }
+
+    #[test]
+    fn test_update_goal_against_stored_values() {
+        let start = Utc::now();
+        let mut goal = Goal {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            name: "Run 100 km".to_string(),
+            description: Some("Before summer".to_string()),
+            target_value: Some(100.0),
+            current_value: None,
+            unit: Some("km".to_string()),
+            start_date: start,
+            target_date: Some(start + Duration::days(90)),
+            completed: false,
+            metric: None,
+            exercise_id: None,
+            start_value: None,
+            created_at: start,
+            updated_at: start,
+        };
+
+        // Omitted fields are kept and null clears a field
+        let req: UpdateGoalRequest = serde_json::from_str(r#"{"description": null, "target_value": 120}"#).unwrap();
+        req.apply(&mut goal);
+        assert_eq!(goal.description, None);
+        assert_eq!(goal.target_value, Some(120.0));
+        assert_eq!(goal.unit.as_deref(), Some("km"));
+        assert!(goal.validate().is_ok());
+
+        // A target date alone is checked against the stored start date
+        let target_date = (start - Duration::days(1)).to_rfc3339();
+        let req: UpdateGoalRequest = serde_json::from_value(serde_json::json!({ "target_date": target_date })).unwrap();
+        req.apply(&mut goal);
+        assert!(goal.validate().unwrap_err().field_errors().contains_key("target_date"));
+    }
+
+    #[test]
+    fn test_update_measurement_clears_null_fields() {
+        let now = Utc::now();
+        let mut measurement = Measurement {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            date: now,
+            weight: Some(72.4),
+            body_fat: Some(18.5),
+            muscle_mass: Some(34.1),
+            notes: Some("Morning".to_string()),
+            created_at: now,
+            updated_at: now,
+        };
+
+        let req: UpdateMeasurementRequest =
+            serde_json::from_str(r#"{"weight": 72.1, "body_fat": null, "notes": null}"#).unwrap();
+        assert!(req.validate().is_ok());
+        req.apply(&mut measurement);
+        assert_eq!(measurement.weight, Some(72.1));
+        assert_eq!(measurement.body_fat, None);
+        assert_eq!(measurement.muscle_mass, Some(34.1));
+        assert_eq!(measurement.notes, None);
+
+        let req: UpdateMeasurementRequest = serde_json::from_str(r#"{"body_fat": 120}"#).unwrap();
+        assert!(req.validate().is_err());
+    }
+
+    #[test]
+    fn test_set_aggregate_from_sets() {
+        // Pyramid with a warm-up and a failed last set
+        let mut failed = set(3, 100.0, SetType::Failure);
+        failed.completed = Some(false);
+        let sets = vec![
+            set(10, 40.0, SetType::Warmup),
+            set(8, 80.0, SetType::Working),
+            set(6, 90.0, SetType::Working),
+            set(8, 90.0, SetType::Working),
+            failed,
+        ];
+
+        let aggregate = SetAggregate::from_sets(&sets);
+        assert_eq!(aggregate.sets, Some(3));
+        assert_eq!(aggregate.reps, Some(8));
+        assert_eq!(aggregate.weight, Some(90.0));
+        assert_eq!(aggregate.duration, None);
+    }
+
+    #[test]
+    fn test_set_aggregate_to_sets() {
+        let aggregate = SetAggregate {
+            sets: Some(3),
+            reps: Some(12),
+            weight: Some(20.5),
+            ..Default::default()
+        };
+        let sets = aggregate.to_sets();
+        assert_eq!(sets.len(), 3);
+        assert!(sets.iter().all(|set| set.reps == Some(12) && set.weight == Some(20.5)));
+
+        // Cardio entries without a set count become a single set
+        let cardio = SetAggregate {
+            duration: Some(600),
+            distance: Some(5.5),
+            ..Default::default()
+        };
+        assert_eq!(cardio.to_sets().len(), 1);
+        assert!(SetAggregate::default().to_sets().is_empty());
+    }
+
+    #[test]
+    fn test_update_workout_exercise_bounds() {
+        let request = |body: serde_json::Value| -> UpdateWorkoutExerciseRequest { serde_json::from_value(body).unwrap() };
+        assert!(request(serde_json::json!({ "sets": 5, "reps": 10, "weight": 9999.99 })).validate().is_ok());
+
+        // Set counts that would expand into huge numbers of rows and values the columns can't store
+        assert!(request(serde_json::json!({ "sets": 2000000000 })).validate().is_err());
+        assert!(request(serde_json::json!({ "weight": 10000.0 })).validate().is_err());
+        assert!(request(serde_json::json!({ "workout_sets": [{ "distance": 10000.0 }] })).validate().is_err());
+
+        // Totals derived from the sets have to fit the entry too
+        let sets = vec![serde_json::json!({ "distance": 5000.0 }); 3];
+        assert!(request(serde_json::json!({ "workout_sets": sets })).validate().is_err());
+        let sets = vec![serde_json::json!({ "reps": 1 }); 101];
+        assert!(request(serde_json::json!({ "workout_sets": sets })).validate().is_err());
+    }
+
+    #[test]
+    fn test_workout_cursor_round_trip() {
+        // Stored timestamps have microsecond precision
+        let cursor = WorkoutCursor {
+            date: DateTime::from_timestamp_micros(1_700_000_000_123_456).unwrap(),
+            id: Uuid::new_v4(),
+            sort: WorkoutSort::DateAsc,
+        };
+        let encoded = cursor.encode();
+        assert!(encoded.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(WorkoutCursor::decode(&encoded), Some(cursor));
+
+        // A cursor only continues the order it was created for
+        let filter = |sort: &str| -> WorkoutFilter {
+            serde_json::from_value(serde_json::json!({ "sort": sort, "cursor": encoded })).unwrap()
+        };
+        assert!(filter("date_asc").validate().is_ok());
+        assert!(filter("date_desc").validate().is_err());
+
+        assert_eq!(WorkoutCursor::decode("not a cursor"), None);
+        assert_eq!(WorkoutCursor::decode(&encoded[1..]), None);
+    }
+
+    #[test]
+    fn test_detect_records() {
+        let start = Utc::now();
+        let lift = |workout_id: Uuid, days: i64, reps: i32, weight: f64| RecordSet {
+            workout_id,
+            workout_set_id: Uuid::new_v4(),
+            date: start + Duration::days(days),
+            reps: Some(reps),
+            weight: Some(weight),
+            duration: None,
+            distance: None,
+        };
+
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+        let sets = vec![
+            lift(first, 0, 5, 100.0),
+            lift(first, 0, 8, 90.0),
+            lift(second, 7, 5, 100.0),
+            lift(second, 7, 3, 105.0),
+        ];
+
+        let records = detect_records(&sets);
+        let in_second: Vec<_> = records.iter().filter(|r| r.workout_id == second).collect();
+
+        // Matching 5 x 100 again is not a record, but the heavier single is
+        let max_weight = in_second.iter().find(|r| r.record_type == RecordType::MaxWeight).unwrap();
+        assert_eq!(max_weight.value, 105.0);
+        assert_eq!(max_weight.previous_value, Some(100.0));
+        assert!(!in_second
+            .iter()
+            .any(|r| r.record_type == RecordType::MaxRepsAtWeight && r.weight == Some(100.0)));
+
+        // Volume 920 and estimated 1RM 115.5 don't beat 1220 and 116.67
+        assert!(!in_second.iter().any(|r| r.record_type == RecordType::MaxVolume));
+        assert!(!in_second.iter().any(|r| r.record_type == RecordType::EstimatedOneRepMax));
+    }
+
+    #[test]
+    fn test_detect_pace_records() {
+        let run = |distance: f64, duration: i32| RecordSet {
+            workout_id: Uuid::new_v4(),
+            workout_set_id: Uuid::new_v4(),
+            date: Utc::now(),
+            reps: None,
+            weight: None,
+            duration: Some(duration),
+            distance: Some(distance),
+        };
+
+        // 5 km in 25 minutes, then a faster but shorter 3 km
+        let records = detect_records(&[run(5.0, 1500), run(3.0, 840)]);
+        let pace: Vec<f64> = records
+            .iter()
+            .filter(|r| r.record_type == RecordType::FastestPace)
+            .map(|r| r.value)
+            .collect();
+        assert_eq!(pace, vec![300.0, 280.0]);
+        assert_eq!(records.iter().filter(|r| r.record_type == RecordType::LongestDistance).count(), 1);
+
+        // Paces up to the largest value the column can store are kept, slower ones skipped
+        let pace = |records: &[DetectedRecord]| {
+            records.iter().find(|r| r.record_type == RecordType::FastestPace).map(|r| r.value)
+        };
+        assert_eq!(pace(&detect_records(&[run(0.01, 99_999_999)])), Some(9_999_999_900.0));
+        assert_eq!(pace(&detect_records(&[run(0.01, 100_000_000)])), None);
+
+        // The same for the volume of a workout
+        let lift = |workout_id, reps| RecordSet {
+            workout_id,
+            workout_set_id: Uuid::new_v4(),
+            date: Utc::now(),
+            reps: Some(reps),
+            weight: Some(9999.99),
+            duration: None,
+            distance: None,
+        };
+        let workout_id = Uuid::new_v4();
+        let records = detect_records(&vec![lift(workout_id, 10_000); 101]);
+        assert!(records.iter().all(|r| r.record_type != RecordType::MaxVolume));
+        assert!(records.iter().any(|r| r.record_type == RecordType::MaxWeight));
+    }
+
+    #[test]
+    fn test_one_rep_max_formulas() {
+        assert_eq!(OneRepMaxFormula::Epley.estimate(100.0, 1), Some(100.0));
+        assert_eq!(OneRepMaxFormula::Epley.estimate(100.0, 6), Some(120.0));
+        assert!((OneRepMaxFormula::Brzycki.estimate(100.0, 10).unwrap() - 133.33).abs() < 0.01);
+        assert!((OneRepMaxFormula::Lombardi.estimate(100.0, 10).unwrap() - 125.89).abs() < 0.01);
+        assert_eq!(OneRepMaxFormula::Brzycki.estimate(100.0, 37), None);
+        assert_eq!(OneRepMaxFormula::Epley.estimate(0.0, 5), None);
+    }
+
+    #[test]
+    fn test_progression_points() {
+        let week = |day: u32| NaiveDate::from_ymd_opt(2025, 3, day).unwrap();
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        let set = |period_start, workout_id, reps, weight| ProgressionSet {
+            period_start,
+            workout_id,
+            reps: Some(reps),
+            weight: Some(weight),
+        };
+
+        let sets = vec![
+            set(week(3), first, 5, 100.0),
+            set(week(3), first, 8, 90.0),
+            set(week(10), second, 3, 105.0),
+        ];
+
+        let points = progression_points(&sets, OneRepMaxFormula::Epley);
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].estimated_one_rep_max, Some(116.67));
+        assert_eq!(points[0].top_set_weight, Some(100.0));
+        assert_eq!(points[0].top_set_reps, Some(5));
+        assert_eq!(points[0].total_volume, 1220.0);
+        assert_eq!((points[0].sets, points[0].workouts), (2, 1));
+        assert_eq!(points[1].estimated_one_rep_max, Some(115.5));
+    }
+
+    #[test]
+    fn test_sessions_per_week() {
+        let to = Utc::now();
+        assert_eq!(sessions_per_week(12, to - Duration::weeks(4), to), 3.0);
+        // Less than a week counts as a whole week
+        assert_eq!(sessions_per_week(2, to - Duration::days(2), to), 2.0);
+    }
+
+    #[test]
+    fn test_daily_streaks_with_rest_days() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 3, d).unwrap();
+        // Mon 3, Tue 4, Thu 6, Sat 8, then a long break and Mon 17
+        let days = vec![day(3), day(4), day(6), day(8), day(17)];
+
+        let strict = daily_streak(&days, day(17), 0);
+        assert_eq!((strict.current, strict.longest), (1, 2));
+
+        let relaxed = daily_streak(&days, day(19), 1);
+        assert_eq!((relaxed.current, relaxed.longest), (1, 6));
+
+        // Two days after the last workout the strict streak is over
+        assert_eq!(daily_streak(&days, day(19), 0).current, 0);
+    }
+
+    #[test]
+    fn test_weekly_streaks() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 3, d).unwrap();
+        // Weeks starting Mar 3, 10 and 17, then nothing in the week of Mar 24
+        let days = vec![day(3), day(9), day(12), day(21)];
+
+        assert_eq!(weekly_streak(&days, day(23), Weekday::Mon).current, 3);
+        assert_eq!(weekly_streak(&days, day(28), Weekday::Mon).current, 3);
+        assert_eq!(weekly_streak(&days, day(31), Weekday::Mon).current, 0);
+        assert_eq!(weekly_streak(&days, day(31), Weekday::Mon).longest, 3);
+
+        // Sunday Mar 2 and Monday Mar 3 are in different weeks only when weeks start on Monday
+        let days = vec![day(2), day(3)];
+        assert_eq!(weekly_streak(&days, day(3), Weekday::Mon).current, 2);
+        assert_eq!(weekly_streak(&days, day(3), Weekday::Sun).current, 1);
+    }
+
+    #[test]
+    fn test_unit_conversion() {
+        assert_eq!(UnitSystem::Metric.weight(100.0), 100.0);
+        assert_eq!(UnitSystem::Imperial.weight(100.0), 220.46);
+        assert_eq!(UnitSystem::Imperial.distance(10.0), 6.21);
+
+        assert_eq!(WeekStart::Monday.days_to_monday(), 0);
+        assert_eq!(WeekStart::Sunday.days_to_monday(), 1);
+        assert_eq!(WeekStart::Saturday.days_to_monday(), 2);
+    }
+
+    #[test]
+    fn test_lockout_duration_doubles_up_to_max() {
+        assert_eq!(lockout_duration(4), None);
+        assert_eq!(lockout_duration(5), Some(Duration::seconds(60)));
+        assert_eq!(lockout_duration(7), Some(Duration::seconds(240)));
+        assert_eq!(lockout_duration(11), Some(Duration::seconds(3600)));
+        assert_eq!(lockout_duration(100), Some(Duration::seconds(3600)));
+    }
+}
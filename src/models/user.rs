@@ -1,10 +1,34 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 use validator::Validate;
 use utoipa::ToSchema;
 
+/// Failed logins in a row after which the account is locked
+pub const LOCKOUT_THRESHOLD: i32 = 5;
+
+/// Lock duration for the first lockout. It doubles with every further failure.
+const LOCKOUT_BASE_SECONDS: i64 = 60;
+
+/// Longest lock duration
+const LOCKOUT_MAX_SECONDS: i64 = 3600;
+
+/// How long to lock an account after `failed_attempts` failed logins in a row,
+/// or `None` while it is below the threshold
+pub fn lockout_duration(failed_attempts: i32) -> Option<Duration> {
+    let lockouts = failed_attempts - LOCKOUT_THRESHOLD;
+
+    if lockouts < 0 {
+        return None;
+    }
+
+    // Past 6 doublings the base is well over the maximum
+    let seconds = (LOCKOUT_BASE_SECONDS << lockouts.min(6)).min(LOCKOUT_MAX_SECONDS);
+
+    Some(Duration::seconds(seconds))
+}
+
 /// User model that maps to the users table in the database
 #[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct User {
@@ -16,6 +40,8 @@ pub struct User {
     pub password_hash: String,
     pub first_name: Option<String>,
     pub last_name: Option<String>,
+    pub email_verified: bool,
+    pub mfa_enabled: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -52,6 +78,87 @@ pub struct UserLoginRequest {
     pub password: String,
 }
 
+/// Partial update of the user's profile
+///
+/// Fields that are omitted keep their current value. Changing the email marks
+/// it as unverified and sends a new verification link.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct UpdateProfileRequest {
+    #[validate(email)]
+    #[schema(example = "user@example.com")]
+    pub email: Option<String>,
+    
+    #[validate(length(min = 3, max = 50))]
+    #[schema(example = "johndoe")]
+    pub username: Option<String>,
+    
+    #[validate(length(max = 100))]
+    #[schema(example = "John")]
+    pub first_name: Option<String>,
+    
+    #[validate(length(max = 100))]
+    #[schema(example = "Doe")]
+    pub last_name: Option<String>,
+}
+
+/// Change password request
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ChangePasswordRequest {
+    #[schema(example = "password123")]
+    pub current_password: String,
+    
+    #[validate(length(min = 8))]
+    #[schema(example = "newpassword123")]
+    pub new_password: String,
+}
+
+/// Delete account request. The password is required again, and a two-factor
+/// code when two-factor authentication is enabled.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct DeleteAccountRequest {
+    #[schema(example = "password123")]
+    pub password: String,
+    
+    /// A code from the authenticator app, or a recovery code
+    #[validate(length(min = 1, max = 32))]
+    #[schema(example = "123456")]
+    pub code: Option<String>,
+}
+
+/// Forgot password request
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ForgotPasswordRequest {
+    #[validate(email)]
+    #[schema(example = "user@example.com")]
+    pub email: String,
+}
+
+/// Reset password request, with the token from the password reset email
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ResetPasswordRequest {
+    #[validate(length(min = 1))]
+    pub token: String,
+    
+    #[validate(length(min = 8))]
+    #[schema(example = "newpassword123")]
+    pub password: String,
+}
+
+/// Verify email request, with the token from the verification email
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct VerifyEmailRequest {
+    #[validate(length(min = 1))]
+    pub token: String,
+}
+
+/// What a token sent by email can be used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+pub enum EmailTokenPurpose {
+    PasswordReset,
+    EmailVerification,
+}
+
 /// User profile response
 #[derive(Debug, Serialize, ToSchema)]
 pub struct UserProfileResponse {
@@ -60,6 +167,10 @@ pub struct UserProfileResponse {
     pub username: String,
     pub first_name: Option<String>,
     pub last_name: Option<String>,
+    /// Whether the user followed the link in the verification email
+    pub email_verified: bool,
+    /// Whether login requires a second factor
+    pub mfa_enabled: bool,
     pub created_at: DateTime<Utc>,
 }
 
@@ -71,6 +182,8 @@ impl From<User> for UserProfileResponse {
             username: user.username,
             first_name: user.first_name,
             last_name: user.last_name,
+            email_verified: user.email_verified,
+            mfa_enabled: user.mfa_enabled,
             created_at: user.created_at,
         }
     }
@@ -80,6 +193,8 @@ impl From<User> for UserProfileResponse {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String, // User ID
+    pub sid: String, // Session ID, checked for revocation on every request
+    pub jti: String, // Unique token ID
     pub exp: usize,  // Expiration time
     pub iat: usize,  // Issued at
 }
@@ -2,8 +2,11 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
-use validator::Validate;
-use utoipa::ToSchema;
+use validator::{Validate, ValidationError};
+use utoipa::{IntoParams, ToSchema};
+
+use super::exercise::Exercise;
+use super::preferences::{UnitSelection, UnitSystem};
 
 /// Workout model that maps to the workouts table
 #[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
@@ -19,17 +22,6 @@ pub struct Workout {
     pub updated_at: DateTime<Utc>,
 }
 
-/// Exercise model that maps to the exercises table
-#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
-pub struct Exercise {
-    pub id: Uuid,
-    pub name: String,
-    pub description: Option<String>,
-    pub category: Option<String>,
-    pub created_at: DateTime<Utc>,
-    pub updated_at: DateTime<Utc>,
-}
-
 /// WorkoutExercise model that maps to the workout_exercises table
 #[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct WorkoutExercise {
@@ -42,6 +34,36 @@ pub struct WorkoutExercise {
     pub duration: Option<i32>, // in seconds
     pub distance: Option<f64>, // in km
     pub notes: Option<String>,
+    pub position: i32, // order within the workout, starting at 0
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Kind of set within an exercise entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum SetType {
+    Warmup,
+    Working,
+    Drop,
+    Failure,
+}
+
+/// WorkoutSet model that maps to the workout_sets table
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct WorkoutSet {
+    pub id: Uuid,
+    pub workout_exercise_id: Uuid,
+    pub set_index: i32, // order within the entry, starting at 0
+    pub reps: Option<i32>,
+    pub weight: Option<f64>, // in kg
+    pub duration: Option<i32>, // in seconds
+    pub distance: Option<f64>, // in km
+    pub rpe: Option<f64>, // rate of perceived exertion, 1 to 10
+    pub rir: Option<i32>, // reps in reserve
+    pub set_type: SetType,
+    pub completed: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -64,31 +86,386 @@ pub struct CreateWorkoutRequest {
     #[schema(example = 350)]
     pub calories_burned: Option<i32>,
     
+    #[validate]
     pub exercises: Vec<WorkoutExerciseInput>,
 }
 
 /// Workout exercise input for creating a workout
+///
+/// When `workout_sets` is sent, the aggregate `sets`, `reps`, `weight`, `duration`
+/// and `distance` fields are derived from it. Otherwise the aggregate fields are
+/// expanded into identical working sets.
 #[derive(Debug, Deserialize, Validate, ToSchema)]
+#[validate(schema(function = "validate_workout_exercise_input"))]
 pub struct WorkoutExerciseInput {
     pub exercise_id: Uuid,
     
+    #[validate(range(min = 0, max = 100))]
     #[schema(example = 3)]
     pub sets: Option<i32>,
     
+    #[validate(range(min = 0, max = 10000))]
     #[schema(example = 12)]
     pub reps: Option<i32>,
     
+    #[validate(range(min = 0.0, max = 9999.99))]
     #[schema(example = 20.5)]
     pub weight: Option<f64>,
     
+    #[validate(range(min = 0))]
     #[schema(example = 600)]
     pub duration: Option<i32>,
     
+    #[validate(range(min = 0.0, max = 9999.99))]
     #[schema(example = 5.5)]
     pub distance: Option<f64>,
     
     #[schema(example = "Felt good, increased weight")]
     pub notes: Option<String>,
+    
+    /// Individual sets, in the order they were performed
+    #[serde(default)]
+    #[validate]
+    pub workout_sets: Vec<WorkoutSetInput>,
+}
+
+impl WorkoutExerciseInput {
+    /// Aggregate fields of the entry, derived from the individual sets when they are sent
+    pub fn aggregate(&self) -> SetAggregate {
+        if self.workout_sets.is_empty() {
+            SetAggregate {
+                sets: self.sets,
+                reps: self.reps,
+                weight: self.weight,
+                duration: self.duration,
+                distance: self.distance,
+            }
+        } else {
+            SetAggregate::from_sets(&self.workout_sets)
+        }
+    }
+    
+    /// Individual sets of the entry, expanded from the aggregate fields when none are sent
+    pub fn resolved_sets(&self) -> Vec<WorkoutSetInput> {
+        if self.workout_sets.is_empty() {
+            self.aggregate().to_sets()
+        } else {
+            self.workout_sets.clone()
+        }
+    }
+}
+
+/// A single set of an exercise entry
+#[derive(Debug, Clone, Deserialize, Validate, ToSchema)]
+pub struct WorkoutSetInput {
+    #[validate(range(min = 0, max = 10000))]
+    #[schema(example = 8)]
+    pub reps: Option<i32>,
+    
+    #[validate(range(min = 0.0, max = 9999.99))]
+    #[schema(example = 60.0)]
+    pub weight: Option<f64>,
+    
+    #[validate(range(min = 0))]
+    pub duration: Option<i32>,
+    
+    #[validate(range(min = 0.0, max = 9999.99))]
+    pub distance: Option<f64>,
+    
+    /// Rate of perceived exertion, from 1 to 10
+    #[validate(range(min = 1.0, max = 10.0))]
+    #[schema(example = 8.5)]
+    pub rpe: Option<f64>,
+    
+    /// Reps in reserve
+    #[validate(range(min = 0))]
+    #[schema(example = 2)]
+    pub rir: Option<i32>,
+    
+    /// Defaults to `working`
+    pub set_type: Option<SetType>,
+    
+    /// Defaults to true
+    pub completed: Option<bool>,
+}
+
+impl WorkoutSetInput {
+    /// Whether the set counts towards the aggregate fields, i.e. it is completed and not a warm-up
+    pub fn counts(&self) -> bool {
+        self.completed.unwrap_or(true) && self.set_type != Some(SetType::Warmup)
+    }
+}
+
+/// Aggregate sets/reps/weight of an exercise entry, kept for clients
+/// that predate per-set logging
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SetAggregate {
+    pub sets: Option<i32>,
+    pub reps: Option<i32>,
+    pub weight: Option<f64>,
+    pub duration: Option<i32>,
+    pub distance: Option<f64>,
+}
+
+impl SetAggregate {
+    /// Summarise individual sets. Only completed sets that are not warm-ups count:
+    /// `sets` is their number, `reps` and `weight` come from the heaviest one and
+    /// `duration` and `distance` are totals.
+    pub fn from_sets(sets: &[WorkoutSetInput]) -> Self {
+        let counted: Vec<&WorkoutSetInput> = sets
+            .iter()
+            .filter(|set| set.counts())
+            .collect();
+        
+        let top_set = counted.iter().max_by(|a, b| {
+            a.weight
+                .unwrap_or(0.0)
+                .total_cmp(&b.weight.unwrap_or(0.0))
+                .then(a.reps.unwrap_or(0).cmp(&b.reps.unwrap_or(0)))
+        });
+        
+        let duration = counted.iter().filter_map(|set| set.duration).reduce(|a, b| a.saturating_add(b));
+        let distance = counted.iter().filter_map(|set| set.distance).reduce(|a, b| a + b);
+        
+        Self {
+            sets: Some(counted.len() as i32),
+            reps: top_set.and_then(|set| set.reps),
+            weight: top_set.and_then(|set| set.weight),
+            duration,
+            distance,
+        }
+    }
+    
+    /// Expand the aggregate into `sets` identical working sets, or a single
+    /// set when only reps, weight, duration or distance are known
+    pub fn to_sets(self) -> Vec<WorkoutSetInput> {
+        let has_values = self.reps.is_some()
+            || self.weight.is_some()
+            || self.duration.is_some()
+            || self.distance.is_some();
+        
+        let count = match self.sets {
+            Some(sets) => sets.max(0),
+            None if has_values => 1,
+            None => 0,
+        };
+        
+        (0..count)
+            .map(|_| WorkoutSetInput {
+                reps: self.reps,
+                weight: self.weight,
+                duration: self.duration,
+                distance: self.distance,
+                rpe: None,
+                rir: None,
+                set_type: Some(SetType::Working),
+                completed: Some(true),
+            })
+            .collect()
+    }
+}
+
+/// Partial update of a workout's own fields
+///
+/// Fields that are omitted keep their current value.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct PatchWorkoutRequest {
+    #[validate(length(min = 1, max = 100))]
+    #[schema(example = "Evening Cardio")]
+    pub name: Option<String>,
+    
+    pub description: Option<String>,
+    
+    pub date: Option<DateTime<Utc>>,
+    
+    #[schema(example = 2100)]
+    pub duration: Option<i32>,
+    
+    #[schema(example = 400)]
+    pub calories_burned: Option<i32>,
+}
+
+/// Partial update of a single exercise entry in a workout
+///
+/// Fields that are omitted keep their current value.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+#[validate(schema(function = "validate_update_workout_exercise"))]
+pub struct UpdateWorkoutExerciseRequest {
+    pub exercise_id: Option<Uuid>,
+    
+    #[validate(range(min = 0, max = 100))]
+    #[schema(example = 4)]
+    pub sets: Option<i32>,
+    
+    #[validate(range(min = 0, max = 10000))]
+    #[schema(example = 10)]
+    pub reps: Option<i32>,
+    
+    #[validate(range(min = 0.0, max = 9999.99))]
+    #[schema(example = 22.5)]
+    pub weight: Option<f64>,
+    
+    #[validate(range(min = 0))]
+    pub duration: Option<i32>,
+    
+    #[validate(range(min = 0.0, max = 9999.99))]
+    pub distance: Option<f64>,
+    
+    pub notes: Option<String>,
+    
+    /// Replaces all sets of the entry; the aggregate fields are then derived from it
+    #[validate]
+    pub workout_sets: Option<Vec<WorkoutSetInput>>,
+}
+
+/// New order of the exercise entries in a workout
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ReorderWorkoutExercisesRequest {
+    /// Every entry ID of the workout, in the desired order
+    pub workout_exercise_ids: Vec<Uuid>,
+}
+
+/// Order of a workout list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkoutSort {
+    /// Newest first
+    #[default]
+    DateDesc,
+    /// Oldest first
+    DateAsc,
+}
+
+/// Related data to embed in each workout of a list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkoutInclude {
+    /// Full workout details with exercises and sets
+    Exercises,
+}
+
+/// Filter, sort and pagination options for listing workouts
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[validate(schema(function = "validate_workout_filter"))]
+pub struct WorkoutFilter {
+    /// Only include workouts at or after this time
+    pub from: Option<DateTime<Utc>>,
+    /// Only include workouts at or before this time
+    pub to: Option<DateTime<Utc>>,
+    /// Case-insensitive search on the workout name
+    pub name: Option<String>,
+    /// Only include workouts containing this exercise
+    pub exercise_id: Option<Uuid>,
+    /// Only include workouts containing an exercise of this category, e.g. "strength"
+    pub category: Option<String>,
+    /// Minimum duration in seconds
+    #[validate(range(min = 0))]
+    pub min_duration: Option<i32>,
+    /// Maximum duration in seconds
+    #[validate(range(min = 0))]
+    pub max_duration: Option<i32>,
+    /// Sort order, defaults to `date_desc`
+    pub sort: Option<WorkoutSort>,
+    /// Embed related data, e.g. `exercises` to return full workout details
+    pub include: Option<WorkoutInclude>,
+    /// Units for weights and distances of embedded details, defaults to `metric`
+    pub units: Option<UnitSelection>,
+    /// Number of workouts to return
+    #[validate(range(min = 1, max = 100))]
+    #[param(default = 20)]
+    pub limit: Option<i64>,
+    /// Cursor from the `X-Next-Cursor` header of the previous page, with the same sort order
+    #[validate(custom = "validate_cursor")]
+    pub cursor: Option<String>,
+}
+
+impl WorkoutFilter {
+    pub const DEFAULT_LIMIT: i64 = 20;
+    
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(Self::DEFAULT_LIMIT)
+    }
+}
+
+/// Position in a workout list, just after the workout with this date and ID.
+/// The cursor only continues a list in the order it was created for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkoutCursor {
+    pub date: DateTime<Utc>,
+    pub id: Uuid,
+    pub sort: WorkoutSort,
+}
+
+impl WorkoutCursor {
+    /// Encode as an opaque string for clients
+    pub fn encode(&self) -> String {
+        let sort = match self.sort {
+            WorkoutSort::DateDesc => "d",
+            WorkoutSort::DateAsc => "a",
+        };
+        
+        format!("{}_{}_{}", self.date.timestamp_micros(), self.id.simple(), sort)
+            .bytes()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+    
+    /// Decode a string produced by `encode`
+    pub fn decode(cursor: &str) -> Option<Self> {
+        if cursor.len() % 2 != 0 || !cursor.is_ascii() {
+            return None;
+        }
+        
+        let bytes = (0..cursor.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&cursor[i..i + 2], 16).ok())
+            .collect::<Option<Vec<u8>>>()?;
+        let decoded = String::from_utf8(bytes).ok()?;
+        let (micros, rest) = decoded.split_once('_')?;
+        let (id, sort) = rest.split_once('_')?;
+        let sort = match sort {
+            "d" => WorkoutSort::DateDesc,
+            "a" => WorkoutSort::DateAsc,
+            _ => return None,
+        };
+        
+        Some(Self {
+            date: DateTime::from_timestamp_micros(micros.parse().ok()?)?,
+            id: Uuid::parse_str(id).ok()?,
+            sort,
+        })
+    }
+}
+
+/// Workouts of a list, with details when requested through `include`
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum WorkoutList {
+    Workouts(Vec<Workout>),
+    Details(Vec<WorkoutDetailsResponse>),
+}
+
+impl WorkoutList {
+    /// Convert the weights and distances of embedded details, see
+    /// `WorkoutDetailsResponse::convert_units`
+    pub fn convert_units(&mut self, units: UnitSystem) {
+        if let WorkoutList::Details(workouts) = self {
+            for workout in workouts {
+                workout.convert_units(units);
+            }
+        }
+    }
+}
+
+/// A page of workouts
+#[derive(Debug)]
+pub struct WorkoutPage {
+    pub workouts: WorkoutList,
+    /// Number of workouts matching the filters, across all pages
+    pub total: i64,
+    /// Cursor for the next page, if there is one
+    pub next_cursor: Option<String>,
 }
 
 /// Workout details response
@@ -100,9 +477,25 @@ pub struct WorkoutDetailsResponse {
     pub date: DateTime<Utc>,
     pub duration: Option<i32>,
     pub calories_burned: Option<i32>,
+    pub updated_at: DateTime<Utc>,
     pub exercises: Vec<WorkoutExerciseDetails>,
 }
 
+impl WorkoutDetailsResponse {
+    /// Convert the stored kg and km of all exercise entries and sets
+    pub fn convert_units(&mut self, units: UnitSystem) {
+        for exercise in &mut self.exercises {
+            exercise.weight = exercise.weight.map(|weight| units.weight(weight));
+            exercise.distance = exercise.distance.map(|distance| units.distance(distance));
+            
+            for set in &mut exercise.workout_sets {
+                set.weight = set.weight.map(|weight| units.weight(weight));
+                set.distance = set.distance.map(|distance| units.distance(distance));
+            }
+        }
+    }
+}
+
 /// Workout exercise details for response
 #[derive(Debug, Serialize, ToSchema)]
 pub struct WorkoutExerciseDetails {
@@ -114,4 +507,67 @@ pub struct WorkoutExerciseDetails {
     pub duration: Option<i32>,
     pub distance: Option<f64>,
     pub notes: Option<String>,
+    pub position: i32,
+    pub workout_sets: Vec<WorkoutSet>,
+}
+
+fn validate_cursor(cursor: &str) -> Result<(), ValidationError> {
+    match WorkoutCursor::decode(cursor) {
+        Some(_) => Ok(()),
+        None => Err(ValidationError::new("invalid_cursor")),
+    }
+}
+
+fn validate_workout_exercise_input(input: &WorkoutExerciseInput) -> Result<(), ValidationError> {
+    validate_set_totals(&input.workout_sets)
+}
+
+fn validate_update_workout_exercise(req: &UpdateWorkoutExerciseRequest) -> Result<(), ValidationError> {
+    match &req.workout_sets {
+        Some(workout_sets) => validate_set_totals(workout_sets),
+        None => Ok(()),
+    }
+}
+
+/// An entry has at most 100 sets, and the total duration and distance derived
+/// from them have to fit the entry
+fn validate_set_totals(sets: &[WorkoutSetInput]) -> Result<(), ValidationError> {
+    if sets.len() > 100 {
+        return Err(ValidationError::new("too_many_sets"));
+    }
+    
+    let counted = sets.iter().filter(|set| set.counts());
+    let duration: i64 = counted.clone().filter_map(|set| set.duration).map(i64::from).sum();
+    let distance: f64 = counted.filter_map(|set| set.distance).sum();
+    
+    if duration > i64::from(i32::MAX) {
+        return Err(ValidationError::new("total_duration_too_large"));
+    }
+    if distance > 9999.99 {
+        return Err(ValidationError::new("total_distance_too_large"));
+    }
+    
+    Ok(())
+}
+
+fn validate_workout_filter(filter: &WorkoutFilter) -> Result<(), ValidationError> {
+    if let (Some(min_duration), Some(max_duration)) = (filter.min_duration, filter.max_duration) {
+        if min_duration > max_duration {
+            return Err(ValidationError::new("min_duration_above_max_duration"));
+        }
+    }
+    
+    if let (Some(from), Some(to)) = (filter.from, filter.to) {
+        if to < from {
+            return Err(ValidationError::new("to_before_from"));
+        }
+    }
+    
+    if let Some(cursor) = filter.cursor.as_deref().and_then(WorkoutCursor::decode) {
+        if cursor.sort != filter.sort.unwrap_or_default() {
+            return Err(ValidationError::new("cursor_sort_mismatch"));
+        }
+    }
+    
+    Ok(())
 }
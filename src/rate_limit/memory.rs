@@ -0,0 +1,51 @@
+use super::{Quota, RateLimitStore};
+use anyhow::Result;
+use futures::future::BoxFuture;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Buckets kept before full ones are pruned
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Token buckets in process memory, for single-instance deployments
+#[derive(Default)]
+pub struct MemoryStore {
+    /// Per key, the time at which the bucket will be full again. Requests
+    /// move it forward by one refill interval; it can be at most
+    /// `Quota::burst_tolerance` ahead of now for a request to be allowed.
+    buckets: Mutex<HashMap<String, Instant>>,
+}
+
+impl MemoryStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take a token at `now`, see `RateLimitStore::acquire`
+    pub fn acquire_at(&self, key: &str, quota: &Quota, now: Instant) -> Option<Duration> {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+        let full_at = buckets.get(key).copied().unwrap_or(now).max(now);
+        let allowed_until = now + quota.burst_tolerance();
+
+        if full_at > allowed_until {
+            return Some(full_at - allowed_until);
+        }
+
+        if buckets.len() >= PRUNE_THRESHOLD {
+            buckets.retain(|_, full_at| *full_at > now);
+        }
+
+        buckets.insert(key.to_string(), full_at + quota.interval());
+
+        None
+    }
+}
+
+impl RateLimitStore for MemoryStore {
+    fn acquire<'a>(&'a self, key: &'a str, quota: &'a Quota) -> BoxFuture<'a, Result<Option<Duration>>> {
+        Box::pin(async move { Ok(self.acquire_at(key, quota, Instant::now())) })
+    }
+}
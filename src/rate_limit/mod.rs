@@ -0,0 +1,137 @@
+pub mod memory;
+pub mod postgres;
+#[cfg(test)]
+mod tests;
+
+use crate::db::DbPool;
+use crate::error::AppError;
+use anyhow::{anyhow, Result};
+use futures::future::BoxFuture;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub use memory::MemoryStore;
+pub use postgres::PostgresStore;
+
+/// How many requests a client may make per period. Buckets hold `requests`
+/// tokens and refill continuously over `period`, so short bursts are allowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    pub requests: u32,
+    pub period: Duration,
+}
+
+impl Quota {
+    /// Time it takes to refill one token
+    pub fn interval(&self) -> Duration {
+        self.period / self.requests
+    }
+
+    /// How far ahead of now a bucket may be booked while it still has a token left
+    pub fn burst_tolerance(&self) -> Duration {
+        self.interval() * (self.requests - 1)
+    }
+}
+
+impl FromStr for Quota {
+    type Err = anyhow::Error;
+
+    /// Parse `REQUESTS/SECONDS`, e.g. `10/60` for ten requests per minute
+    fn from_str(s: &str) -> Result<Self> {
+        let (requests, seconds) = s
+            .split_once('/')
+            .ok_or_else(|| anyhow!("Invalid rate limit '{}', expected REQUESTS/SECONDS", s))?;
+        let requests: u32 = requests.trim().parse()?;
+        let seconds: u64 = seconds.trim().parse()?;
+
+        if requests == 0 || seconds == 0 {
+            return Err(anyhow!("Invalid rate limit '{}', requests and seconds must be positive", s));
+        }
+
+        Ok(Self {
+            requests,
+            period: Duration::from_secs(seconds),
+        })
+    }
+}
+
+/// Where token buckets are kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreKind {
+    /// In process memory. Each instance counts requests on its own.
+    Memory,
+    /// In Postgres, shared by all instances
+    Postgres,
+}
+
+impl FromStr for StoreKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "memory" => Ok(StoreKind::Memory),
+            "postgres" => Ok(StoreKind::Postgres),
+            _ => Err(anyhow!("Invalid rate limit store '{}', expected memory or postgres", s)),
+        }
+    }
+}
+
+/// Token bucket storage, shared by all rate limiters
+pub trait RateLimitStore: Send + Sync {
+    /// Take a token from the bucket for `key`. Returns `None` when the request is
+    /// allowed, or how long to wait until the next token is available.
+    fn acquire<'a>(&'a self, key: &'a str, quota: &'a Quota) -> BoxFuture<'a, Result<Option<Duration>>>;
+}
+
+/// Create the token bucket store of the given kind
+pub fn store(kind: StoreKind, db_pool: &DbPool) -> Arc<dyn RateLimitStore> {
+    match kind {
+        StoreKind::Memory => Arc::new(MemoryStore::new()),
+        StoreKind::Postgres => Arc::new(PostgresStore::new(db_pool.clone())),
+    }
+}
+
+/// A named quota applied to clients. Disabled when there is no quota.
+#[derive(Clone)]
+pub struct RateLimiter {
+    name: &'static str,
+    quota: Option<Quota>,
+    store: Arc<dyn RateLimitStore>,
+}
+
+impl RateLimiter {
+    /// Create a limiter. `name` keeps the buckets of different limiters apart.
+    pub fn new(name: &'static str, quota: Option<Quota>, store: Arc<dyn RateLimitStore>) -> Self {
+        Self { name, quota, store }
+    }
+
+    /// Count a request from `client`, failing with `429 Too Many Requests` when
+    /// its bucket is empty
+    pub async fn check(&self, client: &str) -> crate::error::Result<()> {
+        let Some(quota) = &self.quota else {
+            return Ok(());
+        };
+
+        let key = format!("{}:{}", self.name, client);
+
+        match self.store.acquire(&key, quota).await? {
+            None => Ok(()),
+            Some(wait) => Err(AppError::TooManyRequests {
+                message: "Too many requests".to_string(),
+                retry_after: retry_after_seconds(wait),
+            }),
+        }
+    }
+}
+
+/// Round a wait up to whole seconds for the `Retry-After` header
+pub fn retry_after_seconds(wait: Duration) -> u64 {
+    let seconds = wait.as_secs();
+
+    if wait.subsec_nanos() > 0 || seconds == 0 {
+        seconds + 1
+    } else {
+        seconds
+    }
+}
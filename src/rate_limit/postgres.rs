@@ -0,0 +1,88 @@
+use super::{Quota, RateLimitStore};
+use crate::db::DbPool;
+use anyhow::Result;
+use chrono::Utc;
+use futures::future::BoxFuture;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Seconds between removals of full buckets from the table
+const CLEANUP_INTERVAL: u64 = 300;
+
+/// Token buckets in Postgres, shared by every instance of the API
+pub struct PostgresStore {
+    db_pool: DbPool,
+    /// Unix time of the last cleanup
+    last_cleanup: AtomicU64,
+}
+
+impl PostgresStore {
+    /// Create a store on the `rate_limits` table
+    pub fn new(db_pool: DbPool) -> Self {
+        Self {
+            db_pool,
+            last_cleanup: AtomicU64::new(0),
+        }
+    }
+
+    async fn acquire_token(&self, key: &str, quota: &Quota) -> Result<Option<Duration>> {
+        let interval = quota.interval().as_secs_f64();
+        let tolerance = quota.burst_tolerance().as_secs_f64();
+
+        let now = Utc::now().timestamp() as u64;
+        let last_cleanup = self.last_cleanup.load(Ordering::Relaxed);
+
+        // Only one request per interval does the cleanup
+        if now >= last_cleanup + CLEANUP_INTERVAL
+            && self.last_cleanup.compare_exchange(last_cleanup, now, Ordering::Relaxed, Ordering::Relaxed).is_ok()
+        {
+            sqlx::query!("DELETE FROM rate_limits WHERE full_at < NOW()")
+                .execute(&self.db_pool)
+                .await?;
+        }
+
+        // Same algorithm as `MemoryStore`. The row is only updated when the
+        // request is allowed, so concurrent requests cannot overdraw a bucket.
+        let allowed = sqlx::query!(
+            r#"
+            INSERT INTO rate_limits (key, full_at)
+            VALUES ($1, NOW() + make_interval(secs => $2))
+            ON CONFLICT (key) DO UPDATE
+            SET full_at = GREATEST(rate_limits.full_at, NOW()) + make_interval(secs => $2)
+            WHERE rate_limits.full_at <= NOW() + make_interval(secs => $3)
+            "#,
+            key,
+            interval,
+            tolerance
+        )
+        .execute(&self.db_pool)
+        .await?
+        .rows_affected()
+            > 0;
+
+        if allowed {
+            return Ok(None);
+        }
+
+        let wait = sqlx::query_scalar!(
+            r#"
+            SELECT EXTRACT(EPOCH FROM full_at - NOW())::FLOAT8 - $2 AS "wait!"
+            FROM rate_limits
+            WHERE key = $1
+            "#,
+            key,
+            tolerance
+        )
+        .fetch_optional(&self.db_pool)
+        .await?
+        .unwrap_or(0.0);
+
+        Ok(Some(Duration::from_secs_f64(wait.max(0.0))))
+    }
+}
+
+impl RateLimitStore for PostgresStore {
+    fn acquire<'a>(&'a self, key: &'a str, quota: &'a Quota) -> BoxFuture<'a, Result<Option<Duration>>> {
+        Box::pin(self.acquire_token(key, quota))
+    }
+}
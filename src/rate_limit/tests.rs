@@ -0,0 +1,42 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests {
+    use crate::rate_limit::{retry_after_seconds, MemoryStore, Quota};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_parse_quota() {
+        let quota: Quota = "10/60".parse().unwrap();
+
+        assert_eq!(quota.requests, 10);
+        assert_eq!(quota.interval(), Duration::from_secs(6));
+        assert_eq!(quota.burst_tolerance(), Duration::from_secs(54));
+        assert!("0/60".parse::<Quota>().is_err());
+        assert!("10".parse::<Quota>().is_err());
+    }
+
+    #[test]
+    fn test_memory_store_bucket() {
+        let store = MemoryStore::new();
+        let quota: Quota = "3/60".parse().unwrap();
+        let start = Instant::now();
+
+        // A full bucket allows a burst, then one request per refill interval
+        for _ in 0..3 {
+            assert_eq!(store.acquire_at("client", &quota, start), None);
+        }
+        assert_eq!(store.acquire_at("client", &quota, start), Some(Duration::from_secs(20)));
+        assert_eq!(store.acquire_at("other", &quota, start), None);
+
+        let later = start + Duration::from_secs(20);
+        assert_eq!(store.acquire_at("client", &quota, later), None);
+        assert_eq!(store.acquire_at("client", &quota, later), Some(Duration::from_secs(20)));
+    }
+
+    #[test]
+    fn test_retry_after_rounds_up() {
+        assert_eq!(retry_after_seconds(Duration::from_millis(1500)), 2);
+        assert_eq!(retry_after_seconds(Duration::from_secs(20)), 20);
+        assert_eq!(retry_after_seconds(Duration::ZERO), 1);
+    }
+}
@@ -0,0 +1,247 @@
+use crate::db::DbPool;
+use crate::error::{AppError, Result};
+use crate::models::{RevokeReason, TokenResponse};
+use crate::utils::{generate_token, validate_token, generate_opaque_token, hash_token};
+use chrono::{Duration, Utc};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+/// Service for login sessions, access tokens and refresh tokens
+#[derive(Clone)]
+pub struct AuthService {
+    db_pool: DbPool,
+    jwt_secret: String,
+    access_token_expiration: u64,
+    refresh_token_expiration: u64,
+}
+
+impl AuthService {
+    /// Create a new AuthService instance
+    pub fn new(
+        db_pool: DbPool,
+        jwt_secret: String,
+        access_token_expiration: u64,
+        refresh_token_expiration: u64,
+    ) -> Self {
+        Self {
+            db_pool,
+            jwt_secret,
+            access_token_expiration,
+            refresh_token_expiration,
+        }
+    }
+
+    /// Start a new session for a user who just logged in
+    pub async fn create_session(&self, user_id: Uuid) -> Result<TokenResponse> {
+        let mut tx = self.db_pool.begin().await?;
+
+        let session_id = Uuid::new_v4();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO sessions (id, user_id)
+            VALUES ($1, $2)
+            "#,
+            session_id,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let tokens = self.issue_tokens(&mut tx, user_id, session_id).await?;
+
+        tx.commit().await?;
+
+        Ok(tokens)
+    }
+
+    /// Exchange a refresh token for a new access token and refresh token.
+    ///
+    /// Refresh tokens are single use. Presenting one that was already exchanged
+    /// means it leaked, so the whole session is revoked.
+    pub async fn refresh(&self, refresh_token: &str) -> Result<TokenResponse> {
+        let mut tx = self.db_pool.begin().await?;
+
+        let token = sqlx::query!(
+            r#"
+            SELECT rt.id, rt.session_id, rt.expires_at, rt.used_at, s.user_id, s.revoked_at
+            FROM refresh_tokens rt
+            JOIN sessions s ON s.id = rt.session_id
+            WHERE rt.token_hash = $1
+            FOR UPDATE OF rt, s
+            "#,
+            hash_token(refresh_token)
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::Unauthorized("Invalid refresh token".to_string()))?;
+
+        if token.revoked_at.is_some() {
+            return Err(AppError::Unauthorized("Session has been revoked".to_string()));
+        }
+
+        let now = Utc::now();
+
+        if token.used_at.is_some() {
+            Self::revoke_session(&mut tx, token.session_id, RevokeReason::RefreshTokenReuse).await?;
+            tx.commit().await?;
+
+            return Err(AppError::Unauthorized("Refresh token has already been used".to_string()));
+        }
+
+        if token.expires_at <= now {
+            return Err(AppError::Unauthorized("Refresh token has expired".to_string()));
+        }
+
+        sqlx::query!(
+            "UPDATE refresh_tokens SET used_at = $2 WHERE id = $1",
+            token.id,
+            now
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // Used tokens are only kept around to detect reuse until they expire
+        sqlx::query!(
+            "DELETE FROM refresh_tokens WHERE session_id = $1 AND expires_at <= $2",
+            token.session_id,
+            now
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE sessions SET last_refreshed_at = $2 WHERE id = $1",
+            token.session_id,
+            now
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let tokens = self.issue_tokens(&mut tx, token.user_id, token.session_id).await?;
+
+        tx.commit().await?;
+
+        Ok(tokens)
+    }
+
+    /// Revoke a single session of the user
+    pub async fn logout(&self, user_id: Uuid, session_id: Uuid) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE sessions
+            SET revoked_at = NOW(), revoked_reason = $3
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+            "#,
+            session_id,
+            user_id,
+            RevokeReason::Logout as _
+        )
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Revoke every session of the user
+    pub async fn logout_all(&self, user_id: Uuid) -> Result<()> {
+        let mut conn = self.db_pool.acquire().await?;
+
+        Self::revoke_user_sessions(&mut conn, user_id, None, RevokeReason::LogoutAll).await
+    }
+
+    /// Revoke every active session of a user except `keep`, e.g. after a password reset
+    pub async fn revoke_user_sessions(
+        conn: &mut PgConnection,
+        user_id: Uuid,
+        keep: Option<Uuid>,
+        reason: RevokeReason,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE sessions
+            SET revoked_at = NOW(), revoked_reason = $2
+            WHERE user_id = $1 AND revoked_at IS NULL AND ($3::UUID IS NULL OR id <> $3)
+            "#,
+            user_id,
+            reason as _,
+            keep
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Validate an access token and check that its session is still active.
+    /// Returns the user ID and session ID.
+    pub async fn authenticate(&self, token: &str) -> Result<(Uuid, Uuid)> {
+        let invalid = || AppError::Unauthorized("Invalid token".to_string());
+
+        let claims = validate_token(token, &self.jwt_secret).map_err(|_| invalid())?;
+        let user_id = Uuid::parse_str(&claims.sub).map_err(|_| invalid())?;
+        let session_id = Uuid::parse_str(&claims.sid).map_err(|_| invalid())?;
+
+        let active = sqlx::query_scalar!(
+            r#"
+            SELECT revoked_at IS NULL AS "active!"
+            FROM sessions
+            WHERE id = $1 AND user_id = $2
+            "#,
+            session_id,
+            user_id
+        )
+        .fetch_optional(&self.db_pool)
+        .await?
+        .unwrap_or(false);
+
+        if !active {
+            return Err(AppError::Unauthorized("Session has been revoked".to_string()));
+        }
+
+        Ok((user_id, session_id))
+    }
+
+    /// Create an access token and store a new refresh token for a session
+    async fn issue_tokens(&self, conn: &mut PgConnection, user_id: Uuid, session_id: Uuid) -> Result<TokenResponse> {
+        let refresh_token = generate_opaque_token();
+        let expires_at = Utc::now() + Duration::seconds(self.refresh_token_expiration as i64);
+
+        sqlx::query!(
+            r#"
+            INSERT INTO refresh_tokens (id, session_id, token_hash, expires_at)
+            VALUES ($1, $2, $3, $4)
+            "#,
+            Uuid::new_v4(),
+            session_id,
+            hash_token(&refresh_token),
+            expires_at
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        let token = generate_token(user_id, session_id, &self.jwt_secret, self.access_token_expiration)?;
+
+        Ok(TokenResponse {
+            token,
+            refresh_token,
+            expires_in: self.access_token_expiration,
+        })
+    }
+
+    async fn revoke_session(conn: &mut PgConnection, session_id: Uuid, reason: RevokeReason) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE sessions
+            SET revoked_at = NOW(), revoked_reason = $2
+            WHERE id = $1 AND revoked_at IS NULL
+            "#,
+            session_id,
+            reason as _
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,191 @@
+use crate::db::DbPool;
+use crate::utils::like_pattern;
+use crate::models::{
+    Exercise, Mechanics, MeasurementType,
+    CreateExerciseRequest, ExerciseFilter, ExerciseListResponse,
+};
+use crate::error::{AppError, Result};
+use chrono::Utc;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+/// Service for browsing the exercise catalog and managing custom exercises
+#[derive(Clone)]
+pub struct ExerciseService {
+    db_pool: DbPool,
+}
+
+impl ExerciseService {
+    /// Create a new ExerciseService instance
+    pub fn new(db_pool: DbPool) -> Self {
+        Self { db_pool }
+    }
+
+    /// List the exercises visible to a user: the shared catalog plus their own custom exercises
+    pub async fn list_exercises(&self, user_id: Uuid, filter: ExerciseFilter) -> Result<ExerciseListResponse> {
+        let page = filter.page();
+        let per_page = filter.per_page();
+        let search = filter.search.as_deref().map(like_pattern);
+
+        let total = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!"
+            FROM exercises
+            WHERE (owner_id IS NULL OR owner_id = $1)
+              AND ($2::VARCHAR IS NULL OR LOWER(category) = LOWER($2))
+              AND ($3::VARCHAR IS NULL OR name ILIKE $3)
+              AND ($4::VARCHAR IS NULL OR $4 = ANY(primary_muscles))
+              AND ($5::VARCHAR IS NULL OR equipment = $5)
+              AND ($6::VARCHAR IS NULL OR mechanics = $6)
+              AND ($7::VARCHAR IS NULL OR measurement_type = $7)
+            "#,
+            user_id,
+            filter.category,
+            search,
+            filter.muscle,
+            filter.equipment,
+            filter.mechanics as _,
+            filter.measurement_type as _
+        )
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        let exercises = sqlx::query_as!(
+            Exercise,
+            r#"
+            SELECT id, name, description, category, owner_id, slug, primary_muscles, secondary_muscles, equipment,
+                   mechanics AS "mechanics: Mechanics", measurement_type AS "measurement_type: MeasurementType",
+                   created_at, updated_at
+            FROM exercises
+            WHERE (owner_id IS NULL OR owner_id = $1)
+              AND ($2::VARCHAR IS NULL OR LOWER(category) = LOWER($2))
+              AND ($3::VARCHAR IS NULL OR name ILIKE $3)
+              AND ($4::VARCHAR IS NULL OR $4 = ANY(primary_muscles))
+              AND ($5::VARCHAR IS NULL OR equipment = $5)
+              AND ($6::VARCHAR IS NULL OR mechanics = $6)
+              AND ($7::VARCHAR IS NULL OR measurement_type = $7)
+            ORDER BY name ASC, id ASC
+            LIMIT $8 OFFSET $9
+            "#,
+            user_id,
+            filter.category,
+            search,
+            filter.muscle,
+            filter.equipment,
+            filter.mechanics as _,
+            filter.measurement_type as _,
+            per_page,
+            (page - 1) * per_page
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(ExerciseListResponse {
+            exercises,
+            page,
+            per_page,
+            total,
+        })
+    }
+
+    /// Get all custom exercises owned by the user
+    pub async fn get_custom_exercises(&self, user_id: Uuid) -> Result<Vec<Exercise>> {
+        let exercises = sqlx::query_as!(
+            Exercise,
+            r#"
+            SELECT id, name, description, category, owner_id, slug, primary_muscles, secondary_muscles, equipment,
+                   mechanics AS "mechanics: Mechanics", measurement_type AS "measurement_type: MeasurementType",
+                   created_at, updated_at
+            FROM exercises
+            WHERE owner_id = $1
+            ORDER BY name ASC, id ASC
+            "#,
+            user_id
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(exercises)
+    }
+
+    /// Get every exercise visible to the user, e.g. to match names against
+    pub async fn get_visible_exercises(&self, user_id: Uuid) -> Result<Vec<Exercise>> {
+        let exercises = sqlx::query_as!(
+            Exercise,
+            r#"
+            SELECT id, name, description, category, owner_id, slug, primary_muscles, secondary_muscles, equipment,
+                   mechanics AS "mechanics: Mechanics", measurement_type AS "measurement_type: MeasurementType",
+                   created_at, updated_at
+            FROM exercises
+            WHERE owner_id IS NULL OR owner_id = $1
+            ORDER BY name ASC, id ASC
+            "#,
+            user_id
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(exercises)
+    }
+
+    /// Get an exercise by ID if it is visible to the user
+    pub async fn get_exercise(&self, user_id: Uuid, exercise_id: Uuid) -> Result<Exercise> {
+        let exercise = sqlx::query_as!(
+            Exercise,
+            r#"
+            SELECT id, name, description, category, owner_id, slug, primary_muscles, secondary_muscles, equipment,
+                   mechanics AS "mechanics: Mechanics", measurement_type AS "measurement_type: MeasurementType",
+                   created_at, updated_at
+            FROM exercises
+            WHERE id = $1 AND (owner_id IS NULL OR owner_id = $2)
+            "#,
+            exercise_id,
+            user_id
+        )
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Exercise not found".to_string()))?;
+
+        Ok(exercise)
+    }
+
+    /// Create a custom exercise owned by the user
+    pub async fn create_exercise(&self, user_id: Uuid, req: CreateExerciseRequest) -> Result<Exercise> {
+        let mut conn = self.db_pool.acquire().await?;
+
+        Self::insert_exercise(&mut conn, user_id, &req).await
+    }
+
+    /// Create a custom exercise within the caller's transaction
+    pub async fn insert_exercise(conn: &mut PgConnection, user_id: Uuid, req: &CreateExerciseRequest) -> Result<Exercise> {
+        let now = Utc::now();
+
+        let exercise = sqlx::query_as!(
+            Exercise,
+            r#"
+            INSERT INTO exercises (id, name, description, category, owner_id, primary_muscles, secondary_muscles,
+                                   equipment, mechanics, measurement_type, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            RETURNING id, name, description, category, owner_id, slug, primary_muscles, secondary_muscles, equipment,
+                      mechanics AS "mechanics: Mechanics", measurement_type AS "measurement_type: MeasurementType",
+                      created_at, updated_at
+            "#,
+            Uuid::new_v4(),
+            req.name,
+            req.description,
+            req.category,
+            user_id,
+            &req.primary_muscles,
+            &req.secondary_muscles,
+            req.equipment,
+            req.mechanics as _,
+            req.measurement_type.unwrap_or(MeasurementType::WeightReps) as _,
+            now,
+            now
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(exercise)
+    }
+}
@@ -0,0 +1,363 @@
+use crate::db::DbPool;
+use crate::error::{AppError, Result};
+use crate::export::{build_archive, ExportData};
+use crate::models::{DataExportResponse, ExportStatus, MeasurementFilter};
+use crate::services::{ExerciseService, GoalService, MeasurementService, PreferencesService, UserService, WorkoutService};
+use crate::utils::{sign, verify_signature};
+use actix_web::{rt, web};
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use log::{error, info};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Exports that are still pending or running after this long are considered
+/// lost, e.g. because the server restarted, and marked as failed
+const STALE_EXPORT_MINUTES: i32 = 60;
+
+/// Failed exports are deleted after this long
+const FAILED_EXPORT_RETENTION_DAYS: i32 = 7;
+
+/// How often old exports are cleaned up, in seconds
+const CLEANUP_INTERVAL: u64 = 15 * 60;
+
+/// Service for exporting all of a user's data as a ZIP archive
+#[derive(Clone)]
+pub struct ExportService {
+    db_pool: DbPool,
+    user_service: UserService,
+    workout_service: WorkoutService,
+    goal_service: GoalService,
+    measurement_service: MeasurementService,
+    exercise_service: ExerciseService,
+    preferences_service: PreferencesService,
+    /// Key download links are signed with
+    signing_secret: String,
+    /// How long a completed export can be downloaded, in seconds
+    expiration: u64,
+}
+
+struct ExportRow {
+    id: Uuid,
+    status: ExportStatus,
+    created_at: DateTime<Utc>,
+    completed_at: Option<DateTime<Utc>>,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+impl ExportService {
+    /// Create a new ExportService instance
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        db_pool: DbPool,
+        user_service: UserService,
+        workout_service: WorkoutService,
+        goal_service: GoalService,
+        measurement_service: MeasurementService,
+        exercise_service: ExerciseService,
+        preferences_service: PreferencesService,
+        signing_secret: String,
+        expiration: u64,
+    ) -> Self {
+        Self {
+            db_pool,
+            user_service,
+            workout_service,
+            goal_service,
+            measurement_service,
+            exercise_service,
+            preferences_service,
+            signing_secret,
+            expiration,
+        }
+    }
+
+    /// Periodically delete the archives of expired exports, fail lost exports
+    /// and delete old failed ones, so they don't stay in the database until the
+    /// user requests another export
+    pub fn spawn_cleanup(&self) {
+        let service = self.clone();
+        rt::spawn(async move {
+            let mut interval = rt::time::interval(Duration::from_secs(CLEANUP_INTERVAL));
+            loop {
+                interval.tick().await;
+                if let Err(e) = service.cleanup().await {
+                    error!("Failed to clean up data exports: {:#}", e);
+                }
+            }
+        });
+    }
+
+    async fn cleanup(&self) -> Result<()> {
+        let deleted = sqlx::query!(
+            "UPDATE data_exports SET archive = NULL WHERE expires_at <= NOW() AND archive IS NOT NULL"
+        )
+        .execute(&self.db_pool)
+        .await?
+        .rows_affected();
+
+        if deleted > 0 {
+            info!("Deleted {} expired data export archives", deleted);
+        }
+
+        let failed = sqlx::query!(
+            r#"
+            UPDATE data_exports
+            SET status = $1
+            WHERE status IN ('pending', 'running')
+              AND created_at < NOW() - make_interval(mins => $2)
+            "#,
+            ExportStatus::Failed as _,
+            STALE_EXPORT_MINUTES
+        )
+        .execute(&self.db_pool)
+        .await?
+        .rows_affected();
+
+        if failed > 0 {
+            info!("Marked {} stale data exports as failed", failed);
+        }
+
+        let deleted = sqlx::query!(
+            r#"
+            DELETE FROM data_exports
+            WHERE status = $1 AND created_at < NOW() - make_interval(days => $2)
+            "#,
+            ExportStatus::Failed as _,
+            FAILED_EXPORT_RETENTION_DAYS
+        )
+        .execute(&self.db_pool)
+        .await?
+        .rows_affected();
+
+        if deleted > 0 {
+            info!("Deleted {} old failed data exports", deleted);
+        }
+
+        Ok(())
+    }
+
+    /// Start exporting the user's data in the background. Only one export runs
+    /// per user; while one is in progress it is returned instead.
+    pub async fn request_export(&self, user_id: Uuid) -> Result<DataExportResponse> {
+        let mut tx = self.db_pool.begin().await?;
+
+        sqlx::query!(
+            "DELETE FROM data_exports WHERE user_id = $1 AND expires_at <= NOW()",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE data_exports
+            SET status = $2
+            WHERE user_id = $1 AND status IN ('pending', 'running')
+              AND created_at < NOW() - make_interval(mins => $3)
+            "#,
+            user_id,
+            ExportStatus::Failed as _,
+            STALE_EXPORT_MINUTES
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let created = sqlx::query_as!(
+            ExportRow,
+            r#"
+            INSERT INTO data_exports (id, user_id)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id) WHERE status IN ('pending', 'running') DO NOTHING
+            RETURNING id, status AS "status: ExportStatus", created_at, completed_at, expires_at
+            "#,
+            Uuid::new_v4(),
+            user_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        let is_new = created.is_some();
+
+        let export = match created {
+            Some(export) => export,
+            None => sqlx::query_as!(
+                ExportRow,
+                r#"
+                SELECT id, status AS "status: ExportStatus", created_at, completed_at, expires_at
+                FROM data_exports
+                WHERE user_id = $1 AND status IN ('pending', 'running')
+                "#,
+                user_id
+            )
+            .fetch_one(&mut *tx)
+            .await?,
+        };
+
+        tx.commit().await?;
+
+        if is_new {
+            let service = self.clone();
+            let export_id = export.id;
+            rt::spawn(async move { service.run_export(user_id, export_id).await });
+        }
+
+        Ok(self.to_response(export))
+    }
+
+    /// Get the status of one of the user's exports
+    pub async fn get_export(&self, user_id: Uuid, export_id: Uuid) -> Result<DataExportResponse> {
+        let export = sqlx::query_as!(
+            ExportRow,
+            r#"
+            SELECT id, status AS "status: ExportStatus", created_at, completed_at, expires_at
+            FROM data_exports
+            WHERE id = $1 AND user_id = $2
+            "#,
+            export_id,
+            user_id
+        )
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Export not found".to_string()))?;
+
+        Ok(self.to_response(export))
+    }
+
+    /// Get the archive of a completed export through a signed download link
+    pub async fn download(&self, export_id: Uuid, expires: i64, signature: &str) -> Result<Vec<u8>> {
+        let invalid = || AppError::NotFound("Invalid or expired download link".to_string());
+
+        if expires <= Utc::now().timestamp()
+            || !verify_signature(&download_message(export_id, expires), signature, &self.signing_secret)
+        {
+            return Err(invalid());
+        }
+
+        sqlx::query_scalar!(
+            r#"
+            SELECT archive AS "archive!"
+            FROM data_exports
+            WHERE id = $1 AND status = $2 AND expires_at > NOW() AND archive IS NOT NULL
+            "#,
+            export_id,
+            ExportStatus::Completed as _
+        )
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(invalid)
+    }
+
+    /// Generate the archive and store it, marking the export as failed on errors
+    async fn run_export(&self, user_id: Uuid, export_id: Uuid) {
+        let result = match self.set_status(export_id, ExportStatus::Running).await {
+            Ok(()) => self.generate(user_id).await,
+            Err(e) => Err(e),
+        };
+
+        let stored = match result {
+            Ok(archive) => {
+                info!("Data export {} completed ({} bytes)", export_id, archive.len());
+                self.complete(export_id, archive).await
+            }
+            Err(e) => {
+                error!("Data export {} failed: {:#}", export_id, e);
+                self.set_status(export_id, ExportStatus::Failed).await
+            }
+        };
+
+        if let Err(e) = stored {
+            error!("Failed to update data export {}: {:#}", export_id, e);
+        }
+    }
+
+    /// Gather the user's data and build the ZIP archive
+    async fn generate(&self, user_id: Uuid) -> Result<Vec<u8>> {
+        let profile = self.user_service.get_profile(user_id).await?;
+        let preferences = self.preferences_service.get(user_id).await?;
+
+        let workouts = self.workout_service.get_all_workouts(user_id).await?;
+
+        let goals = self.goal_service.get_goals(user_id).await?;
+        let measurements = self
+            .measurement_service
+            .get_measurements(user_id, MeasurementFilter { from: None, to: None, units: None })
+            .await?;
+        let exercises = self.exercise_service.get_custom_exercises(user_id).await?;
+
+        let data = ExportData {
+            exported_at: Utc::now(),
+            profile,
+            preferences,
+            workouts,
+            goals,
+            measurements,
+            exercises,
+        };
+
+        // Compression is CPU bound, so keep it off the async workers
+        let archive = web::block(move || build_archive(&data))
+            .await
+            .map_err(|e| anyhow!("Export task was cancelled: {}", e))??;
+
+        Ok(archive)
+    }
+
+    async fn set_status(&self, export_id: Uuid, status: ExportStatus) -> Result<()> {
+        sqlx::query!(
+            "UPDATE data_exports SET status = $2 WHERE id = $1",
+            export_id,
+            status as _
+        )
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn complete(&self, export_id: Uuid, archive: Vec<u8>) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE data_exports
+            SET status = $2, archive = $3, completed_at = NOW(),
+                expires_at = NOW() + make_interval(secs => $4)
+            WHERE id = $1
+            "#,
+            export_id,
+            ExportStatus::Completed as _,
+            archive,
+            self.expiration as f64
+        )
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    fn to_response(&self, export: ExportRow) -> DataExportResponse {
+        let download_url = match (export.status, export.expires_at) {
+            (ExportStatus::Completed, Some(expires_at)) if expires_at > Utc::now() => {
+                let expires = expires_at.timestamp();
+                let signature = sign(&download_message(export.id, expires), &self.signing_secret);
+                Some(format!(
+                    "/api/v1/exports/{}/download?expires={}&signature={}",
+                    export.id, expires, signature
+                ))
+            }
+            _ => None,
+        };
+
+        DataExportResponse {
+            id: export.id,
+            status: export.status,
+            created_at: export.created_at,
+            completed_at: export.completed_at,
+            expires_at: export.expires_at,
+            download_url,
+        }
+    }
+}
+
+/// The signed part of a download link
+fn download_message(export_id: Uuid, expires: i64) -> String {
+    format!("export:{}:{}", export_id, expires)
+}
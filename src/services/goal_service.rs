@@ -0,0 +1,416 @@
+use crate::db::DbPool;
+use crate::models::{Goal, GoalMetric, CreateGoalRequest, UpdateGoalRequest, GoalResponse};
+use crate::models::goal::is_target_reached;
+use crate::error::{AppError, Result};
+use chrono::{Duration, Utc};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+/// Service for handling goal-related operations
+#[derive(Clone)]
+pub struct GoalService {
+    db_pool: DbPool,
+}
+
+impl GoalService {
+    /// Create a new GoalService instance
+    pub fn new(db_pool: DbPool) -> Self {
+        Self { db_pool }
+    }
+
+    /// Create a new goal
+    pub async fn create_goal(&self, user_id: Uuid, req: CreateGoalRequest) -> Result<GoalResponse> {
+        let mut tx = self.db_pool.begin().await?;
+        let now = Utc::now();
+
+        if let Some(exercise_id) = req.exercise_id {
+            Self::ensure_exercise_visible(&mut tx, user_id, exercise_id).await?;
+        }
+
+        // Tracked goals get their current value from the metric source
+        let current_value = if req.metric.is_some() { None } else { req.current_value };
+
+        // NUMERIC columns are cast to FLOAT8 so they map onto f64
+        let goal = sqlx::query_as!(
+            Goal,
+            r#"
+            INSERT INTO goals (id, user_id, name, description, target_value, current_value, unit, start_date, target_date,
+                               completed, metric, exercise_id, start_value, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5::FLOAT8, $6::FLOAT8, $7, $8, $9, FALSE, $10, $11, $12::FLOAT8, $13, $14)
+            RETURNING id, user_id, name, description, target_value::FLOAT8 AS "target_value", current_value::FLOAT8 AS "current_value",
+                      unit, start_date, target_date, completed, metric AS "metric: GoalMetric", exercise_id,
+                      start_value::FLOAT8 AS "start_value", created_at, updated_at
+            "#,
+            Uuid::new_v4(),
+            user_id,
+            req.name,
+            req.description,
+            req.target_value,
+            current_value,
+            req.unit,
+            req.start_date,
+            req.target_date,
+            req.metric as _,
+            req.exercise_id,
+            req.start_value,
+            now,
+            now
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let goal = Self::refresh_goal(&mut tx, goal).await?;
+
+        tx.commit().await?;
+
+        Ok(goal.into())
+    }
+
+    /// Get a goal by ID
+    pub async fn get_goal(&self, user_id: Uuid, goal_id: Uuid) -> Result<GoalResponse> {
+        let goal = sqlx::query_as!(
+            Goal,
+            r#"
+            SELECT id, user_id, name, description, target_value::FLOAT8 AS "target_value", current_value::FLOAT8 AS "current_value",
+                   unit, start_date, target_date, completed, metric AS "metric: GoalMetric", exercise_id,
+                   start_value::FLOAT8 AS "start_value", created_at, updated_at
+            FROM goals
+            WHERE id = $1 AND user_id = $2
+            "#,
+            goal_id,
+            user_id
+        )
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Goal not found".to_string()))?;
+
+        Ok(goal.into())
+    }
+
+    /// Get all goals for a user
+    pub async fn get_goals(&self, user_id: Uuid) -> Result<Vec<GoalResponse>> {
+        let goals = sqlx::query_as!(
+            Goal,
+            r#"
+            SELECT id, user_id, name, description, target_value::FLOAT8 AS "target_value", current_value::FLOAT8 AS "current_value",
+                   unit, start_date, target_date, completed, metric AS "metric: GoalMetric", exercise_id,
+                   start_value::FLOAT8 AS "start_value", created_at, updated_at
+            FROM goals
+            WHERE user_id = $1
+            ORDER BY completed ASC, target_date ASC NULLS LAST, created_at DESC
+            "#,
+            user_id
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(goals.into_iter().map(GoalResponse::from).collect())
+    }
+
+    /// Update a goal
+    ///
+    /// The request is applied to the stored goal before it is validated, so
+    /// e.g. a new target date is checked against the stored start date.
+    pub async fn update_goal(&self, user_id: Uuid, goal_id: Uuid, req: UpdateGoalRequest) -> Result<GoalResponse> {
+        let mut tx = self.db_pool.begin().await?;
+
+        let mut goal = sqlx::query_as!(
+            Goal,
+            r#"
+            SELECT id, user_id, name, description, target_value::FLOAT8 AS "target_value", current_value::FLOAT8 AS "current_value",
+                   unit, start_date, target_date, completed, metric AS "metric: GoalMetric", exercise_id,
+                   start_value::FLOAT8 AS "start_value", created_at, updated_at
+            FROM goals
+            WHERE id = $1 AND user_id = $2
+            FOR UPDATE
+            "#,
+            goal_id,
+            user_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Goal not found".to_string()))?;
+
+        let tracked = (goal.metric, goal.exercise_id);
+        let start_value_sent = req.start_value.is_some();
+        req.apply(&mut goal);
+        goal.validate()?;
+
+        if (goal.metric, goal.exercise_id) != tracked {
+            if let Some(exercise_id) = goal.exercise_id {
+                Self::ensure_exercise_visible(&mut tx, user_id, exercise_id).await?;
+            }
+
+            // A baseline taken from another metric or exercise says nothing
+            // about progress on the new one, so it is captured again
+            if !start_value_sent {
+                goal.start_value = None;
+            }
+        }
+
+        goal.updated_at = Utc::now();
+
+        sqlx::query!(
+            r#"
+            UPDATE goals
+            SET name = $2,
+                description = $3,
+                target_value = $4::FLOAT8,
+                current_value = $5::FLOAT8,
+                unit = $6,
+                start_date = $7,
+                target_date = $8,
+                completed = $9,
+                metric = $10,
+                exercise_id = $11,
+                start_value = $12::FLOAT8,
+                updated_at = $13
+            WHERE id = $1
+            "#,
+            goal.id,
+            goal.name,
+            goal.description,
+            goal.target_value,
+            goal.current_value,
+            goal.unit,
+            goal.start_date,
+            goal.target_date,
+            goal.completed,
+            goal.metric as _,
+            goal.exercise_id,
+            goal.start_value,
+            goal.updated_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let goal = Self::refresh_goal(&mut tx, goal).await?;
+
+        tx.commit().await?;
+
+        Ok(goal.into())
+    }
+
+    /// Delete a goal
+    pub async fn delete_goal(&self, user_id: Uuid, goal_id: Uuid) -> Result<()> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM goals
+            WHERE id = $1 AND user_id = $2
+            "#,
+            goal_id,
+            user_id
+        )
+        .execute(&self.db_pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Goal not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Recompute `current_value` and `completed` for every tracked goal of a user.
+    ///
+    /// Called by the workout and measurement services inside their own
+    /// transactions so goal progress is updated atomically with the write.
+    pub async fn refresh_tracked_goals(&self, conn: &mut PgConnection, user_id: Uuid) -> Result<()> {
+        let goals = sqlx::query_as!(
+            Goal,
+            r#"
+            SELECT id, user_id, name, description, target_value::FLOAT8 AS "target_value", current_value::FLOAT8 AS "current_value",
+                   unit, start_date, target_date, completed, metric AS "metric: GoalMetric", exercise_id,
+                   start_value::FLOAT8 AS "start_value", created_at, updated_at
+            FROM goals
+            WHERE user_id = $1 AND metric IS NOT NULL
+            "#,
+            user_id
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        for goal in goals {
+            Self::refresh_goal(conn, goal).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Check that the exercise a goal tracks exists and is visible to the user
+    async fn ensure_exercise_visible(conn: &mut PgConnection, user_id: Uuid, exercise_id: Uuid) -> Result<()> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT id
+            FROM exercises
+            WHERE id = $1 AND (owner_id IS NULL OR owner_id = $2)
+            "#,
+            exercise_id,
+            user_id
+        )
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::Unprocessable("Exercise not found".to_string()))?;
+
+        Ok(())
+    }
+
+    /// Recompute a single goal from its metric source, returning the updated goal.
+    /// Goals without a metric are returned unchanged.
+    async fn refresh_goal(conn: &mut PgConnection, mut goal: Goal) -> Result<Goal> {
+        let metric = match goal.metric {
+            Some(metric) => metric,
+            None => return Ok(goal),
+        };
+
+        let current_value = Self::metric_value(conn, &goal, metric).await?;
+
+        // Baseline metrics measure progress from the first known value
+        let start_value = match goal.start_value {
+            None if metric.tracks_from_baseline() => current_value,
+            start_value => start_value,
+        };
+
+        let completed = is_target_reached(start_value, current_value, goal.target_value);
+
+        if current_value == goal.current_value && start_value == goal.start_value && completed == goal.completed {
+            return Ok(goal);
+        }
+
+        let updated_at = Utc::now();
+
+        sqlx::query!(
+            r#"
+            UPDATE goals
+            SET current_value = $2::FLOAT8, start_value = $3::FLOAT8, completed = $4, updated_at = $5
+            WHERE id = $1
+            "#,
+            goal.id,
+            current_value,
+            start_value,
+            completed,
+            updated_at
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        goal.current_value = current_value;
+        goal.start_value = start_value;
+        goal.completed = completed;
+        goal.updated_at = updated_at;
+
+        Ok(goal)
+    }
+
+    /// Compute the current value of a goal's metric
+    async fn metric_value(conn: &mut PgConnection, goal: &Goal, metric: GoalMetric) -> Result<Option<f64>> {
+        let value = match metric {
+            GoalMetric::BodyWeight => {
+                sqlx::query_scalar!(
+                    r#"
+                    SELECT weight::FLOAT8
+                    FROM measurements
+                    WHERE user_id = $1 AND weight IS NOT NULL
+                    ORDER BY date DESC
+                    LIMIT 1
+                    "#,
+                    goal.user_id
+                )
+                .fetch_optional(&mut *conn)
+                .await?
+                .flatten()
+            }
+            GoalMetric::BodyFat => {
+                sqlx::query_scalar!(
+                    r#"
+                    SELECT body_fat::FLOAT8
+                    FROM measurements
+                    WHERE user_id = $1 AND body_fat IS NOT NULL
+                    ORDER BY date DESC
+                    LIMIT 1
+                    "#,
+                    goal.user_id
+                )
+                .fetch_optional(&mut *conn)
+                .await?
+                .flatten()
+            }
+            GoalMetric::ExerciseDistance => {
+                let distance = sqlx::query_scalar!(
+                    r#"
+                    SELECT SUM(we.distance)::FLOAT8
+                    FROM workout_exercises we
+                    JOIN workouts w ON w.id = we.workout_id
+                    WHERE w.user_id = $1
+                      AND we.exercise_id = $2
+                      AND w.date >= $3
+                      AND ($4::TIMESTAMPTZ IS NULL OR w.date <= $4)
+                    "#,
+                    goal.user_id,
+                    goal.exercise_id,
+                    goal.start_date,
+                    goal.target_date
+                )
+                .fetch_one(&mut *conn)
+                .await?;
+
+                Some(distance.unwrap_or(0.0))
+            }
+            GoalMetric::ExerciseMaxWeight => {
+                sqlx::query_scalar!(
+                    r#"
+                    SELECT MAX(we.weight)::FLOAT8
+                    FROM workout_exercises we
+                    JOIN workouts w ON w.id = we.workout_id
+                    WHERE w.user_id = $1 AND we.exercise_id = $2
+                    "#,
+                    goal.user_id,
+                    goal.exercise_id
+                )
+                .fetch_one(&mut *conn)
+                .await?
+            }
+            GoalMetric::WorkoutCount => {
+                let count = sqlx::query_scalar!(
+                    r#"
+                    SELECT COUNT(*) AS "count!"
+                    FROM workouts
+                    WHERE user_id = $1
+                      AND date >= $2
+                      AND ($3::TIMESTAMPTZ IS NULL OR date <= $3)
+                    "#,
+                    goal.user_id,
+                    goal.start_date,
+                    goal.target_date
+                )
+                .fetch_one(&mut *conn)
+                .await?;
+
+                Some(count as f64)
+            }
+            GoalMetric::WorkoutsPerWeek => {
+                let now = Utc::now();
+                let count = sqlx::query_scalar!(
+                    r#"
+                    SELECT COUNT(*) AS "count!"
+                    FROM workouts
+                    WHERE user_id = $1
+                      AND date >= $2
+                      AND date > $3
+                      AND date <= $4
+                    "#,
+                    goal.user_id,
+                    goal.start_date,
+                    now - Duration::days(7),
+                    now
+                )
+                .fetch_one(&mut *conn)
+                .await?;
+
+                Some(count as f64)
+            }
+        };
+
+        Ok(value)
+    }
+}
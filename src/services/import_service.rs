@@ -0,0 +1,303 @@
+use crate::db::DbPool;
+use crate::error::{AppError, Result};
+use crate::import::{self, csv, ExerciseMatcher, ImportDate, ImportedExercise, ImportedFile};
+use crate::models::{
+    CreateExerciseRequest, CreateWorkoutRequest, ExerciseMatch, ExerciseMatchAction, ImportQuery,
+    ImportedWorkoutPreview, ImportedWorkoutStatus, UnitSelection, UnitSystem, WorkoutExerciseInput,
+    WorkoutImportResponse,
+};
+use crate::services::{ExerciseService, PreferencesService, WorkoutService};
+use chrono::{DateTime, Duration, DurationRound, NaiveDateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// Service for importing workout history from other apps, and exporting it in
+/// a format they read
+#[derive(Clone)]
+pub struct ImportService {
+    db_pool: DbPool,
+    workout_service: WorkoutService,
+    exercise_service: ExerciseService,
+    preferences_service: PreferencesService,
+}
+
+/// Options that apply to the whole import
+struct ImportOptions<'a> {
+    timezone: &'a str,
+    dry_run: bool,
+    create_exercises: bool,
+}
+
+impl ImportService {
+    /// Create a new ImportService instance
+    pub fn new(
+        db_pool: DbPool,
+        workout_service: WorkoutService,
+        exercise_service: ExerciseService,
+        preferences_service: PreferencesService,
+    ) -> Self {
+        Self {
+            db_pool,
+            workout_service,
+            exercise_service,
+            preferences_service,
+        }
+    }
+
+    /// Export all of the user's workouts as CSV with one row per set, with
+    /// dates in the user's timezone
+    pub async fn export_csv(&self, user_id: Uuid, units: Option<UnitSelection>) -> Result<Vec<u8>> {
+        let preferences = self.preferences_service.get(user_id).await?;
+        let units = units.map_or(UnitSystem::Metric, |units| units.resolve(preferences.unit_system));
+
+        let mut workouts = self.workout_service.get_all_workouts(user_id).await?;
+        for workout in &mut workouts {
+            workout.convert_units(units);
+        }
+
+        let dates: Vec<DateTime<Utc>> = workouts.iter().map(|workout| workout.date).collect();
+        let dates = self.preferences_service.utc_to_local(&preferences.timezone, &dates).await?;
+
+        Ok(csv::write(&workouts, &dates)?)
+    }
+
+    /// Import workouts from a CSV file like Strong's or Hevy's export, or
+    /// cardio workouts with their routes from a GPX, TCX or FIT file, or only
+    /// preview the import in a dry run
+    pub async fn import_file(&self, user_id: Uuid, data: &[u8], query: ImportQuery) -> Result<WorkoutImportResponse> {
+        let preferences = self.preferences_service.get(user_id).await?;
+        let units = query.units.map_or(UnitSystem::Metric, |units| units.resolve(preferences.unit_system));
+
+        let file = import::read(data, units).map_err(AppError::BadRequest)?;
+
+        let options = ImportOptions {
+            timezone: &preferences.timezone,
+            dry_run: query.dry_run,
+            create_exercises: query.create_exercises.unwrap_or(true),
+        };
+        self.import(user_id, file, options).await
+    }
+
+    /// Match the exercises of imported workouts, skip workouts that already
+    /// exist and save the rest together with any new custom exercises
+    async fn import(&self, user_id: Uuid, file: ImportedFile, options: ImportOptions<'_>) -> Result<WorkoutImportResponse> {
+        let dates = self.resolve_dates(&file, options.timezone).await?;
+
+        // Workouts with the same name starting in the same minute count as the same
+        let mut seen = self.existing_workouts(user_id, &dates).await?;
+
+        let exercises = self.exercise_service.get_visible_exercises(user_id).await?;
+        let matcher = ExerciseMatcher::new(&exercises);
+        let mut matches: Vec<ExerciseMatch> = Vec::new();
+        let mut new_exercises: Vec<(usize, CreateExerciseRequest)> = Vec::new();
+        let mut match_index: HashMap<&str, usize> = HashMap::new();
+
+        let mut previews = Vec::with_capacity(file.workouts.len());
+        // Entries of each workout that is imported, with the index of their exercise match
+        let mut imported: Vec<(usize, Vec<(usize, &ImportedExercise)>)> = Vec::new();
+
+        for (workout, date) in file.workouts.iter().zip(&dates) {
+            let mut preview = ImportedWorkoutPreview {
+                name: workout.name.clone(),
+                date: *date,
+                status: ImportedWorkoutStatus::Import,
+                exercises: 0,
+                sets: 0,
+                workout_id: None,
+                route: workout
+                    .route
+                    .as_ref()
+                    .map(|route| route.totals.summary(route.splits.clone(), UnitSystem::Metric)),
+                laps: workout.laps.len(),
+                samples: workout.samples.len(),
+            };
+
+            if !seen.insert(duplicate_key(*date, &workout.name)) {
+                preview.status = ImportedWorkoutStatus::Duplicate;
+                previews.push(preview);
+                continue;
+            }
+
+            let mut entries = Vec::new();
+            for entry in &workout.exercises {
+                let index = *match_index.entry(entry.name.as_str()).or_insert_with(|| {
+                    matches.push(match matcher.find(&entry.name) {
+                        Some((exercise, score)) => ExerciseMatch {
+                            name: entry.name.clone(),
+                            action: ExerciseMatchAction::Matched,
+                            exercise_id: Some(exercise.id),
+                            exercise_name: Some(exercise.name.clone()),
+                            score: Some(score),
+                        },
+                        None => ExerciseMatch {
+                            name: entry.name.clone(),
+                            action: if options.create_exercises {
+                                ExerciseMatchAction::Create
+                            } else {
+                                ExerciseMatchAction::Skip
+                            },
+                            exercise_id: None,
+                            exercise_name: options.create_exercises.then(|| entry.name.clone()),
+                            score: None,
+                        },
+                    });
+
+                    let index = matches.len() - 1;
+                    if matches[index].action == ExerciseMatchAction::Create {
+                        new_exercises.push((index, new_exercise(entry)));
+                    }
+                    index
+                });
+
+                if matches[index].action != ExerciseMatchAction::Skip {
+                    preview.exercises += 1;
+                    preview.sets += entry.sets.len();
+                    entries.push((index, entry));
+                }
+            }
+
+            if entries.is_empty() {
+                preview.status = ImportedWorkoutStatus::Empty;
+            } else {
+                imported.push((previews.len(), entries));
+            }
+            previews.push(preview);
+        }
+
+        if !options.dry_run && !imported.is_empty() {
+            let mut tx = self.db_pool.begin().await?;
+
+            for (index, req) in &new_exercises {
+                let exercise = ExerciseService::insert_exercise(&mut tx, user_id, req).await?;
+                matches[*index].exercise_id = Some(exercise.id);
+            }
+
+            let workouts: Vec<CreateWorkoutRequest> = imported
+                .iter()
+                .map(|(preview_index, entries)| {
+                    let workout = &file.workouts[*preview_index];
+                    CreateWorkoutRequest {
+                        name: workout.name.clone(),
+                        description: workout.notes.clone(),
+                        date: dates[*preview_index],
+                        duration: workout.duration,
+                        calories_burned: workout.calories_burned,
+                        exercises: entries
+                            .iter()
+                            .filter_map(|(match_index, entry)| {
+                                Some(WorkoutExerciseInput {
+                                    exercise_id: matches[*match_index].exercise_id?,
+                                    sets: None,
+                                    reps: None,
+                                    weight: None,
+                                    duration: None,
+                                    distance: None,
+                                    notes: entry.notes.clone(),
+                                    workout_sets: entry.sets.clone(),
+                                })
+                            })
+                            .collect(),
+                    }
+                })
+                .collect();
+
+            let workout_ids = self.workout_service.import_workouts(&mut tx, user_id, &workouts).await?;
+            for ((preview_index, _), workout_id) in imported.iter().zip(&workout_ids) {
+                if let Some(route) = &file.workouts[*preview_index].route {
+                    WorkoutService::insert_route(&mut tx, *workout_id, &route.totals, &route.points).await?;
+                }
+                WorkoutService::insert_laps(&mut tx, *workout_id, &file.workouts[*preview_index].laps).await?;
+                WorkoutService::insert_samples(&mut tx, *workout_id, &file.workouts[*preview_index].samples).await?;
+            }
+            tx.commit().await?;
+
+            for ((preview_index, _), workout_id) in imported.iter().zip(workout_ids) {
+                previews[*preview_index].workout_id = Some(workout_id);
+            }
+        }
+
+        let count = |status| previews.iter().filter(|preview| preview.status == status).count();
+
+        Ok(WorkoutImportResponse {
+            dry_run: options.dry_run,
+            rows: file.rows,
+            imported: count(ImportedWorkoutStatus::Import),
+            duplicates: count(ImportedWorkoutStatus::Duplicate),
+            workouts: previews,
+            exercises: matches,
+            errors: file.errors,
+        })
+    }
+
+    /// Start time of each workout in UTC, reading local times in the user's timezone
+    async fn resolve_dates(&self, file: &ImportedFile, timezone: &str) -> Result<Vec<DateTime<Utc>>> {
+        let local: Vec<NaiveDateTime> = file
+            .workouts
+            .iter()
+            .filter_map(|workout| match workout.date {
+                ImportDate::Local(date) => Some(date),
+                ImportDate::Utc(_) => None,
+            })
+            .collect();
+
+        let converted = self.preferences_service.local_to_utc(timezone, &local).await?;
+        let converted: HashMap<NaiveDateTime, DateTime<Utc>> = local.into_iter().zip(converted).collect();
+
+        file.workouts
+            .iter()
+            .map(|workout| match workout.date {
+                ImportDate::Utc(date) => Ok(date),
+                ImportDate::Local(date) => converted
+                    .get(&date)
+                    .copied()
+                    .ok_or_else(|| AppError::BadRequest(format!("Invalid local time: {}", date))),
+            })
+            .collect()
+    }
+
+    /// Keys of the user's workouts around the given dates, to detect duplicates
+    async fn existing_workouts(&self, user_id: Uuid, dates: &[DateTime<Utc>]) -> Result<HashSet<(DateTime<Utc>, String)>> {
+        let (Some(from), Some(to)) = (dates.iter().min(), dates.iter().max()) else {
+            return Ok(HashSet::new());
+        };
+
+        let workouts = sqlx::query!(
+            r#"
+            SELECT date, name
+            FROM workouts
+            WHERE user_id = $1 AND date >= $2 AND date <= $3
+            "#,
+            user_id,
+            *from - Duration::minutes(1),
+            *to + Duration::minutes(1)
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(workouts
+            .into_iter()
+            .map(|workout| duplicate_key(workout.date, &workout.name))
+            .collect())
+    }
+}
+
+/// Key under which workouts count as duplicates: the start minute and the name
+fn duplicate_key(date: DateTime<Utc>, name: &str) -> (DateTime<Utc>, String) {
+    let minute = date.duration_trunc(Duration::minutes(1)).unwrap_or(date);
+
+    (minute, name.trim().to_lowercase())
+}
+
+/// Custom exercise for an exercise name that matches no exercise
+fn new_exercise(entry: &ImportedExercise) -> CreateExerciseRequest {
+    CreateExerciseRequest {
+        name: entry.name.clone(),
+        description: None,
+        category: None,
+        primary_muscles: Vec::new(),
+        secondary_muscles: Vec::new(),
+        equipment: None,
+        mechanics: None,
+        measurement_type: Some(entry.measurement_type()),
+    }
+}
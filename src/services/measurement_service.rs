@@ -0,0 +1,180 @@
+use crate::db::DbPool;
+use crate::models::{Measurement, CreateMeasurementRequest, UpdateMeasurementRequest, MeasurementFilter};
+use crate::services::GoalService;
+use crate::error::{AppError, Result};
+use chrono::Utc;
+use uuid::Uuid;
+
+/// Service for handling body measurement operations
+#[derive(Clone)]
+pub struct MeasurementService {
+    db_pool: DbPool,
+    goal_service: GoalService,
+}
+
+impl MeasurementService {
+    /// Create a new MeasurementService instance
+    pub fn new(db_pool: DbPool, goal_service: GoalService) -> Self {
+        Self { db_pool, goal_service }
+    }
+
+    /// Record a new measurement
+    pub async fn create_measurement(&self, user_id: Uuid, req: CreateMeasurementRequest) -> Result<Measurement> {
+        let mut tx = self.db_pool.begin().await?;
+        let now = Utc::now();
+
+        let measurement = sqlx::query_as!(
+            Measurement,
+            r#"
+            INSERT INTO measurements (id, user_id, date, weight, body_fat, muscle_mass, notes, created_at, updated_at)
+            VALUES ($1, $2, $3, $4::FLOAT8, $5::FLOAT8, $6::FLOAT8, $7, $8, $9)
+            RETURNING id, user_id, date, weight::FLOAT8 AS "weight", body_fat::FLOAT8 AS "body_fat",
+                      muscle_mass::FLOAT8 AS "muscle_mass", notes, created_at, updated_at
+            "#,
+            Uuid::new_v4(),
+            user_id,
+            req.date,
+            req.weight,
+            req.body_fat,
+            req.muscle_mass,
+            req.notes,
+            now,
+            now
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        // Update progress of goals tracked from measurements
+        self.goal_service.refresh_tracked_goals(&mut tx, user_id).await?;
+
+        tx.commit().await?;
+
+        Ok(measurement)
+    }
+
+    /// Get a measurement by ID
+    pub async fn get_measurement(&self, user_id: Uuid, measurement_id: Uuid) -> Result<Measurement> {
+        let measurement = sqlx::query_as!(
+            Measurement,
+            r#"
+            SELECT id, user_id, date, weight::FLOAT8 AS "weight", body_fat::FLOAT8 AS "body_fat",
+                   muscle_mass::FLOAT8 AS "muscle_mass", notes, created_at, updated_at
+            FROM measurements
+            WHERE id = $1 AND user_id = $2
+            "#,
+            measurement_id,
+            user_id
+        )
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Measurement not found".to_string()))?;
+
+        Ok(measurement)
+    }
+
+    /// Get all measurements for a user, optionally limited to a date range
+    pub async fn get_measurements(&self, user_id: Uuid, filter: MeasurementFilter) -> Result<Vec<Measurement>> {
+        let measurements = sqlx::query_as!(
+            Measurement,
+            r#"
+            SELECT id, user_id, date, weight::FLOAT8 AS "weight", body_fat::FLOAT8 AS "body_fat",
+                   muscle_mass::FLOAT8 AS "muscle_mass", notes, created_at, updated_at
+            FROM measurements
+            WHERE user_id = $1
+              AND ($2::TIMESTAMPTZ IS NULL OR date >= $2)
+              AND ($3::TIMESTAMPTZ IS NULL OR date <= $3)
+            ORDER BY date DESC
+            "#,
+            user_id,
+            filter.from,
+            filter.to
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(measurements)
+    }
+
+    /// Update a measurement
+    pub async fn update_measurement(
+        &self,
+        user_id: Uuid,
+        measurement_id: Uuid,
+        req: UpdateMeasurementRequest,
+    ) -> Result<Measurement> {
+        let mut tx = self.db_pool.begin().await?;
+
+        let mut measurement = sqlx::query_as!(
+            Measurement,
+            r#"
+            SELECT id, user_id, date, weight::FLOAT8 AS "weight", body_fat::FLOAT8 AS "body_fat",
+                   muscle_mass::FLOAT8 AS "muscle_mass", notes, created_at, updated_at
+            FROM measurements
+            WHERE id = $1 AND user_id = $2
+            FOR UPDATE
+            "#,
+            measurement_id,
+            user_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Measurement not found".to_string()))?;
+
+        req.apply(&mut measurement);
+        measurement.updated_at = Utc::now();
+
+        sqlx::query!(
+            r#"
+            UPDATE measurements
+            SET date = $2,
+                weight = $3::FLOAT8,
+                body_fat = $4::FLOAT8,
+                muscle_mass = $5::FLOAT8,
+                notes = $6,
+                updated_at = $7
+            WHERE id = $1
+            "#,
+            measurement.id,
+            measurement.date,
+            measurement.weight,
+            measurement.body_fat,
+            measurement.muscle_mass,
+            measurement.notes,
+            measurement.updated_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        self.goal_service.refresh_tracked_goals(&mut tx, user_id).await?;
+
+        tx.commit().await?;
+
+        Ok(measurement)
+    }
+
+    /// Delete a measurement
+    pub async fn delete_measurement(&self, user_id: Uuid, measurement_id: Uuid) -> Result<()> {
+        let mut tx = self.db_pool.begin().await?;
+
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM measurements
+            WHERE id = $1 AND user_id = $2
+            "#,
+            measurement_id,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Measurement not found".to_string()));
+        }
+
+        self.goal_service.refresh_tracked_goals(&mut tx, user_id).await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
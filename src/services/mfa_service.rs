@@ -0,0 +1,351 @@
+use crate::db::DbPool;
+use crate::error::{AppError, Result};
+use crate::models::{MfaSetupResponse, MfaChallengeResponse, MfaDisableRequest, RecoveryCodesResponse};
+use crate::utils::{
+    generate_opaque_token, hash_token, verify_password,
+    generate_totp_secret, totp_uri, verify_totp, generate_recovery_codes, normalize_recovery_code,
+};
+use chrono::{Duration, Utc};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+/// How long a login waits for the second factor, in seconds
+const CHALLENGE_EXPIRATION: u64 = 300;
+
+/// Wrong codes allowed per challenge before the login has to start over
+const CHALLENGE_MAX_ATTEMPTS: i32 = 5;
+
+/// Recovery codes issued when 2FA is enabled or the codes are regenerated
+const RECOVERY_CODE_COUNT: usize = 10;
+
+/// Service for TOTP two-factor authentication
+#[derive(Clone)]
+pub struct MfaService {
+    db_pool: DbPool,
+}
+
+impl MfaService {
+    /// Create a new MfaService instance
+    pub fn new(db_pool: DbPool) -> Self {
+        Self { db_pool }
+    }
+
+    /// Generate a new TOTP secret. It only takes effect once it is confirmed with
+    /// a code, and replaces any earlier unconfirmed secret.
+    pub async fn setup(&self, user_id: Uuid) -> Result<MfaSetupResponse> {
+        let user = sqlx::query!(
+            "SELECT email, mfa_enabled FROM users WHERE id = $1",
+            user_id
+        )
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        if user.mfa_enabled {
+            return Err(AppError::Conflict("Two-factor authentication is already enabled".to_string()));
+        }
+
+        let secret = generate_totp_secret();
+        let otpauth_uri = totp_uri(&secret, &user.email)?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO user_totp (user_id, secret)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id) DO UPDATE
+            SET secret = EXCLUDED.secret, confirmed_at = NULL, last_used_step = NULL, created_at = NOW()
+            "#,
+            user_id,
+            secret
+        )
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(MfaSetupResponse { secret, otpauth_uri })
+    }
+
+    /// Enable 2FA by confirming the secret from `setup` with a first code
+    pub async fn confirm(&self, user_id: Uuid, code: &str) -> Result<RecoveryCodesResponse> {
+        let mut tx = self.db_pool.begin().await?;
+
+        let totp = sqlx::query!(
+            "SELECT secret, confirmed_at FROM user_totp WHERE user_id = $1 FOR UPDATE",
+            user_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("Two-factor authentication has not been set up".to_string()))?;
+
+        if totp.confirmed_at.is_some() {
+            return Err(AppError::Conflict("Two-factor authentication is already enabled".to_string()));
+        }
+
+        let step = verify_totp(&totp.secret, code, Utc::now().timestamp() as u64, None)?
+            .ok_or_else(|| AppError::BadRequest("Invalid two-factor code".to_string()))?;
+
+        sqlx::query!(
+            "UPDATE user_totp SET confirmed_at = NOW(), last_used_step = $2 WHERE user_id = $1",
+            user_id,
+            step
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE users SET mfa_enabled = TRUE, updated_at = NOW() WHERE id = $1",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let recovery_codes = Self::replace_recovery_codes(&mut tx, user_id).await?;
+
+        tx.commit().await?;
+
+        Ok(RecoveryCodesResponse { recovery_codes })
+    }
+
+    /// Disable 2FA after checking the password and a current code
+    pub async fn disable(&self, user_id: Uuid, req: MfaDisableRequest) -> Result<()> {
+        let mut tx = self.db_pool.begin().await?;
+
+        let user = sqlx::query!(
+            "SELECT password_hash, mfa_enabled FROM users WHERE id = $1 FOR UPDATE",
+            user_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        if !user.mfa_enabled {
+            return Err(AppError::BadRequest("Two-factor authentication is not enabled".to_string()));
+        }
+
+        if !verify_password(&req.password, &user.password_hash)? {
+            return Err(AppError::BadRequest("Invalid password".to_string()));
+        }
+
+        if !Self::check_code(&mut tx, user_id, &req.code).await? {
+            return Err(AppError::BadRequest("Invalid two-factor code".to_string()));
+        }
+
+        sqlx::query!("DELETE FROM user_totp WHERE user_id = $1", user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!("DELETE FROM mfa_recovery_codes WHERE user_id = $1", user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!("DELETE FROM mfa_challenges WHERE user_id = $1", user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!(
+            "UPDATE users SET mfa_enabled = FALSE, updated_at = NOW() WHERE id = $1",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Replace all recovery codes after checking a current code
+    pub async fn regenerate_recovery_codes(&self, user_id: Uuid, code: &str) -> Result<RecoveryCodesResponse> {
+        let mut tx = self.db_pool.begin().await?;
+
+        if !Self::check_code(&mut tx, user_id, code).await? {
+            return Err(AppError::BadRequest("Invalid two-factor code".to_string()));
+        }
+
+        let recovery_codes = Self::replace_recovery_codes(&mut tx, user_id).await?;
+
+        tx.commit().await?;
+
+        Ok(RecoveryCodesResponse { recovery_codes })
+    }
+
+    /// Start the second step of a login for a user who entered the right password
+    pub async fn create_challenge(&self, user_id: Uuid) -> Result<MfaChallengeResponse> {
+        let challenge_token = generate_opaque_token();
+        let now = Utc::now();
+
+        // Expired challenges of the user are no longer needed
+        sqlx::query!(
+            "DELETE FROM mfa_challenges WHERE user_id = $1 AND expires_at <= $2",
+            user_id,
+            now
+        )
+        .execute(&self.db_pool)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO mfa_challenges (id, user_id, token_hash, expires_at)
+            VALUES ($1, $2, $3, $4)
+            "#,
+            Uuid::new_v4(),
+            user_id,
+            hash_token(&challenge_token),
+            now + Duration::seconds(CHALLENGE_EXPIRATION as i64)
+        )
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(MfaChallengeResponse {
+            mfa_required: true,
+            challenge_token,
+            expires_in: CHALLENGE_EXPIRATION,
+        })
+    }
+
+    /// Check the code for a login challenge and return the user it belongs to and
+    /// whether the code was accepted. Challenges are single use and only allow a
+    /// few wrong codes.
+    pub async fn verify_challenge(&self, challenge_token: &str, code: &str) -> Result<(Uuid, bool)> {
+        let invalid = || AppError::Unauthorized("Invalid or expired MFA challenge".to_string());
+
+        let mut tx = self.db_pool.begin().await?;
+
+        let challenge = sqlx::query!(
+            r#"
+            SELECT id, user_id, expires_at, attempts
+            FROM mfa_challenges
+            WHERE token_hash = $1
+            FOR UPDATE
+            "#,
+            hash_token(challenge_token)
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(invalid)?;
+
+        if challenge.expires_at <= Utc::now() {
+            sqlx::query!("DELETE FROM mfa_challenges WHERE id = $1", challenge.id)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+
+            return Err(invalid());
+        }
+
+        if !Self::check_code(&mut tx, challenge.user_id, code).await? {
+            if challenge.attempts + 1 >= CHALLENGE_MAX_ATTEMPTS {
+                sqlx::query!("DELETE FROM mfa_challenges WHERE id = $1", challenge.id)
+                    .execute(&mut *tx)
+                    .await?;
+            } else {
+                sqlx::query!(
+                    "UPDATE mfa_challenges SET attempts = attempts + 1 WHERE id = $1",
+                    challenge.id
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+            tx.commit().await?;
+
+            return Ok((challenge.user_id, false));
+        }
+
+        sqlx::query!("DELETE FROM mfa_challenges WHERE id = $1", challenge.id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok((challenge.user_id, true))
+    }
+
+    /// Check a code of a user with 2FA enabled, e.g. to confirm a sensitive action
+    pub async fn verify_code(&self, user_id: Uuid, code: &str) -> Result<bool> {
+        // Keep the TOTP row locked until the used step is stored, so a code works only once
+        let mut tx = self.db_pool.begin().await?;
+
+        let valid = Self::check_code(&mut tx, user_id, code).await?;
+
+        tx.commit().await?;
+
+        Ok(valid)
+    }
+
+    /// Check a TOTP code or, failing that, an unused recovery code. Accepted
+    /// codes are used up.
+    async fn check_code(conn: &mut PgConnection, user_id: Uuid, code: &str) -> Result<bool> {
+        let code = code.trim();
+
+        if code.len() == 6 && code.bytes().all(|b| b.is_ascii_digit()) {
+            let totp = sqlx::query!(
+                r#"
+                SELECT secret, last_used_step
+                FROM user_totp
+                WHERE user_id = $1 AND confirmed_at IS NOT NULL
+                FOR UPDATE
+                "#,
+                user_id
+            )
+            .fetch_optional(&mut *conn)
+            .await?;
+
+            let Some(totp) = totp else {
+                return Ok(false);
+            };
+
+            let step = verify_totp(&totp.secret, code, Utc::now().timestamp() as u64, totp.last_used_step)?;
+
+            if let Some(step) = step {
+                sqlx::query!(
+                    "UPDATE user_totp SET last_used_step = $2 WHERE user_id = $1",
+                    user_id,
+                    step
+                )
+                .execute(&mut *conn)
+                .await?;
+            }
+
+            return Ok(step.is_some());
+        }
+
+        let used = sqlx::query!(
+            r#"
+            UPDATE mfa_recovery_codes
+            SET used_at = NOW()
+            WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+            "#,
+            user_id,
+            hash_token(&normalize_recovery_code(code))
+        )
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+
+        Ok(used > 0)
+    }
+
+    async fn replace_recovery_codes(conn: &mut PgConnection, user_id: Uuid) -> Result<Vec<String>> {
+        sqlx::query!("DELETE FROM mfa_recovery_codes WHERE user_id = $1", user_id)
+            .execute(&mut *conn)
+            .await?;
+
+        let codes = generate_recovery_codes(RECOVERY_CODE_COUNT);
+        let ids: Vec<Uuid> = codes.iter().map(|_| Uuid::new_v4()).collect();
+        let hashes: Vec<String> = codes.iter().map(|code| hash_token(&normalize_recovery_code(code))).collect();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO mfa_recovery_codes (id, user_id, code_hash)
+            SELECT id, $2, code_hash
+            FROM UNNEST($1::UUID[], $3::VARCHAR[]) AS codes(id, code_hash)
+            "#,
+            &ids,
+            user_id,
+            &hashes
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(codes)
+    }
+}
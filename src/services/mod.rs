@@ -1,7 +1,27 @@
 // Export service modules
+pub mod auth_service;
+pub mod exercise_service;
+pub mod export_service;
+pub mod goal_service;
+pub mod import_service;
+pub mod measurement_service;
+pub mod mfa_service;
+pub mod preferences_service;
+pub mod record_service;
+pub mod stats_service;
 pub mod user_service;
 pub mod workout_service;
 
 // Re-export service types
+pub use auth_service::AuthService;
+pub use exercise_service::ExerciseService;
+pub use export_service::ExportService;
+pub use goal_service::GoalService;
+pub use import_service::ImportService;
+pub use measurement_service::MeasurementService;
+pub use mfa_service::MfaService;
+pub use preferences_service::PreferencesService;
+pub use record_service::RecordService;
+pub use stats_service::StatsService;
 pub use user_service::UserService;
 pub use workout_service::WorkoutService;
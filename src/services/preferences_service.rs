@@ -0,0 +1,146 @@
+use crate::db::DbPool;
+use crate::error::{AppError, Result};
+use crate::models::{UserPreferences, UpdatePreferencesRequest, UnitSelection, UnitSystem, WeekStart};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use uuid::Uuid;
+
+/// Service for per-user display preferences
+#[derive(Clone)]
+pub struct PreferencesService {
+    db_pool: DbPool,
+}
+
+impl PreferencesService {
+    /// Create a new PreferencesService instance
+    pub fn new(db_pool: DbPool) -> Self {
+        Self { db_pool }
+    }
+
+    /// Get the user's preferences, or the defaults if they were never changed
+    pub async fn get(&self, user_id: Uuid) -> Result<UserPreferences> {
+        let preferences = sqlx::query_as!(
+            UserPreferences,
+            r#"
+            SELECT unit_system AS "unit_system: UnitSystem", timezone,
+                   week_start AS "week_start: WeekStart", rest_timer_seconds, locale
+            FROM user_preferences
+            WHERE user_id = $1
+            "#,
+            user_id
+        )
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(preferences.unwrap_or_default())
+    }
+
+    /// Change some of the user's preferences
+    pub async fn update(&self, user_id: Uuid, req: UpdatePreferencesRequest) -> Result<UserPreferences> {
+        if let Some(timezone) = &req.timezone {
+            self.ensure_timezone(timezone).await?;
+        }
+
+        let mut tx = self.db_pool.begin().await?;
+
+        // Start from the column defaults for users without a row yet
+        sqlx::query!(
+            "INSERT INTO user_preferences (user_id) VALUES ($1) ON CONFLICT (user_id) DO NOTHING",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let preferences = sqlx::query_as!(
+            UserPreferences,
+            r#"
+            UPDATE user_preferences
+            SET unit_system = COALESCE($2, unit_system),
+                timezone = COALESCE($3, timezone),
+                week_start = COALESCE($4, week_start),
+                rest_timer_seconds = COALESCE($5, rest_timer_seconds),
+                locale = COALESCE($6, locale),
+                updated_at = NOW()
+            WHERE user_id = $1
+            RETURNING unit_system AS "unit_system: UnitSystem", timezone,
+                      week_start AS "week_start: WeekStart", rest_timer_seconds, locale
+            "#,
+            user_id,
+            req.unit_system as _,
+            req.timezone,
+            req.week_start as _,
+            req.rest_timer_seconds,
+            req.locale
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(preferences)
+    }
+
+    /// Units a response should use. Values stay metric unless other units are
+    /// requested; `preferred` looks up the user's unit system.
+    pub async fn units(&self, user_id: Uuid, selection: Option<UnitSelection>) -> Result<UnitSystem> {
+        let Some(selection) = selection else {
+            return Ok(UnitSystem::Metric);
+        };
+
+        let preferred = match selection {
+            UnitSelection::Preferred => self.get(user_id).await?.unit_system,
+            _ => UnitSystem::Metric,
+        };
+
+        Ok(selection.resolve(preferred))
+    }
+
+    /// Convert local times in a timezone to UTC
+    pub async fn local_to_utc(&self, timezone: &str, times: &[NaiveDateTime]) -> Result<Vec<DateTime<Utc>>> {
+        let times = sqlx::query_scalar!(
+            r#"
+            SELECT (t.local AT TIME ZONE $2) AS "utc!"
+            FROM UNNEST($1::TIMESTAMP[]) WITH ORDINALITY AS t(local, i)
+            ORDER BY t.i
+            "#,
+            times,
+            timezone
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(times)
+    }
+
+    /// Convert UTC times to local times in a timezone
+    pub async fn utc_to_local(&self, timezone: &str, times: &[DateTime<Utc>]) -> Result<Vec<NaiveDateTime>> {
+        let times = sqlx::query_scalar!(
+            r#"
+            SELECT (t.utc AT TIME ZONE $2) AS "local!"
+            FROM UNNEST($1::TIMESTAMPTZ[]) WITH ORDINALITY AS t(utc, i)
+            ORDER BY t.i
+            "#,
+            times,
+            timezone
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(times)
+    }
+
+    /// Check that a timezone name is known to the database
+    pub async fn ensure_timezone(&self, timezone: &str) -> Result<()> {
+        let known = sqlx::query_scalar!(
+            r#"SELECT EXISTS (SELECT 1 FROM pg_timezone_names WHERE name = $1) AS "known!""#,
+            timezone
+        )
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        if !known {
+            return Err(AppError::BadRequest(format!("Unknown timezone: {}", timezone)));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,238 @@
+use crate::db::DbPool;
+use crate::models::{PersonalRecord, RecordType, RecordFilter, ExerciseRecordsResponse};
+use crate::models::record::{detect_records, RecordSet};
+use crate::error::{AppError, Result};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+/// Service for detecting and querying personal records
+#[derive(Clone)]
+pub struct RecordService {
+    db_pool: DbPool,
+}
+
+impl RecordService {
+    /// Create a new RecordService instance
+    pub fn new(db_pool: DbPool) -> Self {
+        Self { db_pool }
+    }
+
+    /// Get the user's current records across all exercises
+    pub async fn get_records(&self, user_id: Uuid, filter: RecordFilter) -> Result<Vec<PersonalRecord>> {
+        self.current_records(user_id, None, filter.record_type).await
+    }
+
+    /// Get the current records and record history of an exercise
+    pub async fn get_exercise_records(&self, user_id: Uuid, exercise_id: Uuid) -> Result<ExerciseRecordsResponse> {
+        sqlx::query!(
+            r#"
+            SELECT id
+            FROM exercises
+            WHERE id = $1 AND (owner_id IS NULL OR owner_id = $2)
+            "#,
+            exercise_id,
+            user_id
+        )
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Exercise not found".to_string()))?;
+
+        let current = self.current_records(user_id, Some(exercise_id), None).await?;
+
+        let history = sqlx::query_as!(
+            PersonalRecord,
+            r#"
+            SELECT id, user_id, exercise_id, record_type AS "record_type: RecordType", value::FLOAT8 AS "value!",
+                   weight::FLOAT8 AS "weight", reps, previous_value::FLOAT8 AS "previous_value",
+                   workout_id, workout_set_id, achieved_at, created_at
+            FROM personal_records
+            WHERE user_id = $1 AND exercise_id = $2
+            ORDER BY achieved_at DESC, record_type, weight
+            "#,
+            user_id,
+            exercise_id
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(ExerciseRecordsResponse {
+            exercise_id,
+            current,
+            history,
+        })
+    }
+
+    /// Get the records set in a workout
+    pub async fn get_workout_records(&self, conn: &mut PgConnection, workout_id: Uuid) -> Result<Vec<PersonalRecord>> {
+        let records = sqlx::query_as!(
+            PersonalRecord,
+            r#"
+            SELECT id, user_id, exercise_id, record_type AS "record_type: RecordType", value::FLOAT8 AS "value!",
+                   weight::FLOAT8 AS "weight", reps, previous_value::FLOAT8 AS "previous_value",
+                   workout_id, workout_set_id, achieved_at, created_at
+            FROM personal_records
+            WHERE workout_id = $1
+            ORDER BY exercise_id, record_type, weight
+            "#,
+            workout_id
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(records)
+    }
+
+    /// Recompute the record history of the given exercises for a user.
+    ///
+    /// Called by the workout service inside its own transactions whenever sets
+    /// are written or removed, so backdated and edited workouts are handled too.
+    /// Records that are still valid keep their row.
+    pub async fn refresh_records(&self, conn: &mut PgConnection, user_id: Uuid, exercise_ids: &[Uuid]) -> Result<()> {
+        if exercise_ids.is_empty() {
+            return Ok(());
+        }
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT we.exercise_id, w.id AS workout_id, w.date, ws.id AS workout_set_id, ws.reps,
+                   ws.weight::FLOAT8 AS "weight", ws.duration, ws.distance::FLOAT8 AS "distance"
+            FROM workout_sets ws
+            JOIN workout_exercises we ON we.id = ws.workout_exercise_id
+            JOIN workouts w ON w.id = we.workout_id
+            WHERE w.user_id = $1
+              AND we.exercise_id = ANY($2)
+              AND ws.completed
+              AND ws.set_type <> 'warmup'
+            ORDER BY we.exercise_id, w.date, w.id, we.position, ws.set_index
+            "#,
+            user_id,
+            exercise_ids
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        // Rows are ordered by exercise, so each exercise's sets are contiguous
+        let mut sets_by_exercise: Vec<(Uuid, Vec<RecordSet>)> = Vec::new();
+        for row in rows {
+            let set = RecordSet {
+                workout_id: row.workout_id,
+                workout_set_id: row.workout_set_id,
+                date: row.date,
+                reps: row.reps,
+                weight: row.weight,
+                duration: row.duration,
+                distance: row.distance,
+            };
+
+            match sets_by_exercise.last_mut() {
+                Some((exercise_id, sets)) if *exercise_id == row.exercise_id => sets.push(set),
+                _ => sets_by_exercise.push((row.exercise_id, vec![set])),
+            }
+        }
+
+        let mut detected = Vec::new();
+        for (exercise_id, sets) in sets_by_exercise {
+            detected.extend(detect_records(&sets).into_iter().map(|record| (exercise_id, record)));
+        }
+
+        let mut existing = sqlx::query_as!(
+            PersonalRecord,
+            r#"
+            SELECT id, user_id, exercise_id, record_type AS "record_type: RecordType", value::FLOAT8 AS "value!",
+                   weight::FLOAT8 AS "weight", reps, previous_value::FLOAT8 AS "previous_value",
+                   workout_id, workout_set_id, achieved_at, created_at
+            FROM personal_records
+            WHERE user_id = $1 AND exercise_id = ANY($2)
+            "#,
+            user_id,
+            exercise_ids
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        for (exercise_id, record) in detected {
+            // Keep rows that are unchanged
+            let unchanged = existing.iter().position(|row| {
+                row.exercise_id == exercise_id
+                    && row.record_type == record.record_type
+                    && row.value == record.value
+                    && row.weight == record.weight
+                    && row.reps == record.reps
+                    && row.previous_value == record.previous_value
+                    && row.workout_id == record.workout_id
+                    && row.workout_set_id == record.workout_set_id
+                    && row.achieved_at == record.achieved_at
+            });
+
+            if let Some(index) = unchanged {
+                existing.swap_remove(index);
+                continue;
+            }
+
+            sqlx::query!(
+                r#"
+                INSERT INTO personal_records (id, user_id, exercise_id, record_type, value, weight, reps, previous_value,
+                                              workout_id, workout_set_id, achieved_at)
+                VALUES ($1, $2, $3, $4, $5::FLOAT8, $6::FLOAT8, $7, $8::FLOAT8, $9, $10, $11)
+                "#,
+                Uuid::new_v4(),
+                user_id,
+                exercise_id,
+                record.record_type as _,
+                record.value,
+                record.weight,
+                record.reps,
+                record.previous_value,
+                record.workout_id,
+                record.workout_set_id,
+                record.achieved_at
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        // Whatever is left is no longer a record
+        let stale_ids: Vec<Uuid> = existing.iter().map(|row| row.id).collect();
+
+        sqlx::query!("DELETE FROM personal_records WHERE id = ANY($1)", &stale_ids)
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Latest record per exercise, type and weight. Each record beats the one
+    /// before it, so the latest is the current one.
+    async fn current_records(
+        &self,
+        user_id: Uuid,
+        exercise_id: Option<Uuid>,
+        record_type: Option<RecordType>,
+    ) -> Result<Vec<PersonalRecord>> {
+        let records = sqlx::query_as!(
+            PersonalRecord,
+            r#"
+            SELECT DISTINCT ON (exercise_id, record_type, weight_key)
+                   id, user_id, exercise_id, record_type AS "record_type: RecordType", value::FLOAT8 AS "value!",
+                   weight::FLOAT8 AS "weight", reps, previous_value::FLOAT8 AS "previous_value",
+                   workout_id, workout_set_id, achieved_at, created_at
+            FROM (
+                SELECT *, CASE WHEN record_type = 'max_reps_at_weight' THEN weight END AS weight_key
+                FROM personal_records
+                WHERE user_id = $1
+                  AND ($2::UUID IS NULL OR exercise_id = $2)
+                  AND ($3::VARCHAR IS NULL OR record_type = $3)
+            ) records
+            ORDER BY exercise_id, record_type, weight_key, achieved_at DESC,
+                     CASE WHEN record_type = 'fastest_pace' THEN value ELSE -value END
+            "#,
+            user_id,
+            exercise_id,
+            record_type as _
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(records)
+    }
+}
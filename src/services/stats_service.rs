@@ -0,0 +1,345 @@
+use crate::db::DbPool;
+use crate::models::{
+    Bucket, UnitSystem, ProgressionFilter, ExerciseProgressionResponse,
+    SummaryFilter, StatsSummary, CategoryBreakdown, MuscleGroupBreakdown,
+    StreakFilter, StreakStats, ActivityFilter, ActivityCalendar, ActivityDay,
+};
+use crate::models::stats::{
+    progression_points, sessions_per_week, daily_streak, weekly_streak, ProgressionSet, MAX_ACTIVITY_DAYS,
+};
+use crate::services::PreferencesService;
+use crate::error::{AppError, Result};
+use chrono::{Duration, NaiveDate, Utc};
+use uuid::Uuid;
+
+/// Service for training statistics computed from workout history
+#[derive(Clone)]
+pub struct StatsService {
+    db_pool: DbPool,
+    preferences_service: PreferencesService,
+}
+
+impl StatsService {
+    /// Create a new StatsService instance
+    pub fn new(db_pool: DbPool, preferences_service: PreferencesService) -> Self {
+        Self { db_pool, preferences_service }
+    }
+
+    /// Estimated one-rep max, top set and volume of an exercise per period
+    pub async fn exercise_progression(
+        &self,
+        user_id: Uuid,
+        exercise_id: Uuid,
+        filter: ProgressionFilter,
+    ) -> Result<ExerciseProgressionResponse> {
+        let preferences = self.preferences_service.get(user_id).await?;
+        let formula = filter.formula.unwrap_or_default();
+        let bucket = filter.bucket.unwrap_or_default();
+        let timezone = filter.timezone.unwrap_or(preferences.timezone);
+        let units = filter.units.map_or(UnitSystem::Metric, |units| units.resolve(preferences.unit_system));
+
+        if matches!((filter.from, filter.to), (Some(from), Some(to)) if from > to) {
+            return Err(AppError::BadRequest("Invalid range: from must not be after to".to_string()));
+        }
+
+        // date_trunc weeks start on Monday, so other week starts are shifted there and back
+        let week_shift = match bucket {
+            Bucket::Week => preferences.week_start.days_to_monday(),
+            _ => 0,
+        };
+
+        self.preferences_service.ensure_timezone(&timezone).await?;
+
+        sqlx::query!(
+            r#"
+            SELECT id
+            FROM exercises
+            WHERE id = $1 AND (owner_id IS NULL OR owner_id = $2)
+            "#,
+            exercise_id,
+            user_id
+        )
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Exercise not found".to_string()))?;
+
+        // Periods are computed from the workout's local date in the requested timezone
+        let rows = sqlx::query!(
+            r#"
+            SELECT (date_trunc($3, (w.date AT TIME ZONE $4) + make_interval(days => $7))
+                    - make_interval(days => $7))::DATE AS "period_start!",
+                   w.id AS workout_id,
+                   ws.reps, ws.weight::FLOAT8 AS "weight"
+            FROM workout_sets ws
+            JOIN workout_exercises we ON we.id = ws.workout_exercise_id
+            JOIN workouts w ON w.id = we.workout_id
+            WHERE w.user_id = $1
+              AND we.exercise_id = $2
+              AND ws.completed
+              AND ws.set_type <> 'warmup'
+              AND ($5::TIMESTAMPTZ IS NULL OR w.date >= $5)
+              AND ($6::TIMESTAMPTZ IS NULL OR w.date <= $6)
+            ORDER BY 1
+            "#,
+            user_id,
+            exercise_id,
+            bucket.as_str(),
+            timezone,
+            filter.from,
+            filter.to,
+            week_shift
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let sets: Vec<ProgressionSet> = rows
+            .into_iter()
+            .map(|row| ProgressionSet {
+                period_start: row.period_start,
+                workout_id: row.workout_id,
+                reps: row.reps,
+                weight: row.weight,
+            })
+            .collect();
+
+        let mut progression = ExerciseProgressionResponse {
+            exercise_id,
+            formula,
+            bucket,
+            timezone,
+            points: progression_points(&sets, formula),
+        };
+        progression.convert_units(units);
+
+        Ok(progression)
+    }
+
+    /// Training volume and frequency over a period, with breakdowns by
+    /// exercise category and muscle group
+    pub async fn summary(&self, user_id: Uuid, filter: SummaryFilter) -> Result<StatsSummary> {
+        let to = filter.to.unwrap_or_else(Utc::now);
+        if filter.from.is_some_and(|from| from > to) {
+            return Err(AppError::BadRequest("Invalid range: from must not be after to".to_string()));
+        }
+        
+        let units = self.preferences_service.units(user_id, filter.units).await?;
+        
+        let totals = sqlx::query!(
+            r#"
+            SELECT COUNT(*) AS "workouts!",
+                   COALESCE(SUM(duration), 0)::BIGINT AS "duration!",
+                   COALESCE(SUM(calories_burned), 0)::BIGINT AS "calories!",
+                   MIN(date) AS first_workout
+            FROM workouts
+            WHERE user_id = $1
+              AND ($2::TIMESTAMPTZ IS NULL OR date >= $2)
+              AND date <= $3
+            "#,
+            user_id,
+            filter.from,
+            to
+        )
+        .fetch_one(&self.db_pool)
+        .await?;
+        
+        // Only completed working sets count towards volume
+        let by_category = sqlx::query_as!(
+            CategoryBreakdown,
+            r#"
+            SELECT e.category,
+                   COUNT(DISTINCT w.id) AS "workouts!",
+                   COUNT(ws.id) AS "sets!",
+                   COALESCE(SUM(ws.reps * ws.weight), 0)::FLOAT8 AS "tonnage!",
+                   COALESCE(SUM(ws.distance), 0)::FLOAT8 AS "distance!"
+            FROM workouts w
+            JOIN workout_exercises we ON we.workout_id = w.id
+            JOIN exercises e ON e.id = we.exercise_id
+            LEFT JOIN workout_sets ws
+                   ON ws.workout_exercise_id = we.id AND ws.completed AND ws.set_type <> 'warmup'
+            WHERE w.user_id = $1
+              AND ($2::TIMESTAMPTZ IS NULL OR w.date >= $2)
+              AND w.date <= $3
+            GROUP BY e.category
+            ORDER BY 3 DESC, e.category
+            "#,
+            user_id,
+            filter.from,
+            to
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+        
+        let by_muscle_group = sqlx::query_as!(
+            MuscleGroupBreakdown,
+            r#"
+            SELECT muscle.name AS "muscle_group!",
+                   COUNT(DISTINCT w.id) AS "workouts!",
+                   COUNT(ws.id) AS "sets!",
+                   COALESCE(SUM(ws.reps * ws.weight), 0)::FLOAT8 AS "tonnage!"
+            FROM workouts w
+            JOIN workout_exercises we ON we.workout_id = w.id
+            JOIN exercises e ON e.id = we.exercise_id
+            CROSS JOIN LATERAL UNNEST(e.primary_muscles) AS muscle(name)
+            LEFT JOIN workout_sets ws
+                   ON ws.workout_exercise_id = we.id AND ws.completed AND ws.set_type <> 'warmup'
+            WHERE w.user_id = $1
+              AND ($2::TIMESTAMPTZ IS NULL OR w.date >= $2)
+              AND w.date <= $3
+            GROUP BY muscle.name
+            ORDER BY 3 DESC, muscle.name
+            "#,
+            user_id,
+            filter.from,
+            to
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+        
+        let tonnage = by_category.iter().map(|category| category.tonnage).sum();
+        let total_distance = by_category.iter().map(|category| category.distance).sum();
+        
+        let from = filter.from.or(totals.first_workout);
+        let sessions_per_week = match from {
+            Some(from) => sessions_per_week(totals.workouts, from, to),
+            None => 0.0,
+        };
+        
+        let mut summary = StatsSummary {
+            from,
+            to,
+            total_workouts: totals.workouts,
+            total_duration: totals.duration,
+            total_calories: totals.calories,
+            tonnage,
+            total_distance,
+            sessions_per_week,
+            by_category,
+            by_muscle_group,
+        };
+        summary.convert_units(units);
+        
+        Ok(summary)
+    }
+
+    /// Current and longest daily and weekly workout streaks, with days in the given timezone
+    pub async fn streaks(&self, user_id: Uuid, filter: StreakFilter) -> Result<StreakStats> {
+        let preferences = self.preferences_service.get(user_id).await?;
+        let timezone = filter.timezone.unwrap_or(preferences.timezone);
+        let rest_days = filter.rest_days.unwrap_or(0);
+
+        self.preferences_service.ensure_timezone(&timezone).await?;
+
+        let today = self.today(&timezone).await?;
+
+        let days = sqlx::query_scalar!(
+            r#"
+            SELECT DISTINCT (date AT TIME ZONE $2)::DATE AS "day!"
+            FROM workouts
+            WHERE user_id = $1
+            ORDER BY 1
+            "#,
+            user_id,
+            timezone
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let daily = daily_streak(&days, today, rest_days);
+        let weekly = weekly_streak(&days, today, preferences.week_start.weekday());
+
+        Ok(StreakStats {
+            timezone,
+            rest_days,
+            current_daily_streak: daily.current,
+            longest_daily_streak: daily.longest,
+            current_weekly_streak: weekly.current,
+            longest_weekly_streak: weekly.longest,
+            last_workout_date: days.last().copied(),
+        })
+    }
+
+    /// Workouts, duration and volume for every day in a range, for activity heatmaps
+    pub async fn activity(&self, user_id: Uuid, filter: ActivityFilter) -> Result<ActivityCalendar> {
+        let preferences = self.preferences_service.get(user_id).await?;
+        let timezone = filter.timezone.unwrap_or(preferences.timezone);
+        let units = filter.units.map_or(UnitSystem::Metric, |units| units.resolve(preferences.unit_system));
+
+        self.preferences_service.ensure_timezone(&timezone).await?;
+
+        let to = match filter.to {
+            Some(to) => to,
+            None => self.today(&timezone).await?,
+        };
+        let from = filter.from.unwrap_or(to - Duration::days(364));
+
+        if from > to {
+            return Err(AppError::BadRequest("Invalid range: from must not be after to".to_string()));
+        }
+
+        if (to - from).num_days() >= MAX_ACTIVITY_DAYS {
+            return Err(AppError::BadRequest(format!("Invalid range: at most {} days can be requested", MAX_ACTIVITY_DAYS)));
+        }
+
+        let active_days = sqlx::query_as!(
+            ActivityDay,
+            r#"
+            SELECT (w.date AT TIME ZONE $2)::DATE AS "date!",
+                   COUNT(*) AS "workouts!",
+                   COALESCE(SUM(w.duration), 0)::BIGINT AS "duration!",
+                   COALESCE(SUM(volume.total), 0)::FLOAT8 AS "volume!"
+            FROM workouts w
+            LEFT JOIN LATERAL (
+                SELECT SUM(ws.reps * ws.weight) AS total
+                FROM workout_sets ws
+                JOIN workout_exercises we ON we.id = ws.workout_exercise_id
+                WHERE we.workout_id = w.id AND ws.completed AND ws.set_type <> 'warmup'
+            ) volume ON TRUE
+            WHERE w.user_id = $1
+              AND (w.date AT TIME ZONE $2)::DATE BETWEEN $3 AND $4
+            GROUP BY 1
+            ORDER BY 1
+            "#,
+            user_id,
+            timezone,
+            from,
+            to
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        // Fill in the days without workouts
+        let mut active_days = active_days.into_iter().peekable();
+        let days = from
+            .iter_days()
+            .take_while(|date| *date <= to)
+            .map(|date| match active_days.next_if(|day| day.date == date) {
+                Some(day) => day,
+                None => ActivityDay {
+                    date,
+                    workouts: 0,
+                    duration: 0,
+                    volume: 0.0,
+                },
+            })
+            .collect();
+
+        let mut calendar = ActivityCalendar {
+            timezone,
+            from,
+            to,
+            days,
+        };
+        calendar.convert_units(units);
+
+        Ok(calendar)
+    }
+
+    /// Current date in a timezone
+    async fn today(&self, timezone: &str) -> Result<NaiveDate> {
+        let today = sqlx::query_scalar!(r#"SELECT (NOW() AT TIME ZONE $1)::DATE AS "today!""#, timezone)
+            .fetch_one(&self.db_pool)
+            .await?;
+
+        Ok(today)
+    }
+}
@@ -1,25 +1,71 @@
 use crate::db::DbPool;
-use crate::models::{User, UserRegisterRequest, UserLoginRequest, UserProfileResponse};
-use crate::utils::{hash_password, verify_password, generate_token};
-use anyhow::{Result, anyhow};
-use chrono::Utc;
-use sqlx::query_as;
+use crate::mailer::{templates, Mailer};
+use crate::models::user::lockout_duration;
+use crate::models::{
+    User, UserRegisterRequest, UserLoginRequest, UserProfileResponse, RevokeReason, LoginResponse, LoginResult,
+    MfaVerifyRequest,
+    ForgotPasswordRequest, ResetPasswordRequest, VerifyEmailRequest, EmailTokenPurpose,
+    UpdateProfileRequest, ChangePasswordRequest, DeleteAccountRequest,
+};
+use crate::rate_limit::RateLimiter;
+use crate::services::{AuthService, MfaService};
+use crate::utils::{hash_password, verify_password, generate_opaque_token, hash_token};
+use crate::error::{AppError, Result};
+use actix_web::rt;
+use chrono::{Duration, Utc};
+use log::error;
+use sqlx::PgConnection;
+use std::sync::{Arc, OnceLock};
 use uuid::Uuid;
 
+/// How long a password reset link stays valid
+const PASSWORD_RESET_EXPIRATION_MINUTES: i64 = 60;
+
+/// How long an email verification link stays valid
+const EMAIL_VERIFICATION_EXPIRATION_HOURS: i64 = 48;
+
+/// The error for every rejected login, so it doesn't reveal which accounts exist or are locked
+fn invalid_credentials() -> AppError {
+    AppError::Unauthorized("Invalid email or password".to_string())
+}
+
+/// A hash to check passwords against when there is no account
+fn dummy_password_hash() -> &'static str {
+    static HASH: OnceLock<String> = OnceLock::new();
+    
+    HASH.get_or_init(|| hash_password(&generate_opaque_token()).expect("Failed to hash dummy password"))
+}
+
 /// Service for handling user-related operations
+#[derive(Clone)]
 pub struct UserService {
     db_pool: DbPool,
-    jwt_secret: String,
-    jwt_expiration: u64,
+    auth_service: AuthService,
+    mfa_service: MfaService,
+    /// Limits login attempts per email address
+    login_limiter: RateLimiter,
+    mailer: Arc<dyn Mailer>,
+    /// Base URL of the app, used for links in emails
+    app_url: String,
 }
 
 impl UserService {
     /// Create a new UserService instance
-    pub fn new(db_pool: DbPool, jwt_secret: String, jwt_expiration: u64) -> Self {
+    pub fn new(
+        db_pool: DbPool,
+        auth_service: AuthService,
+        mfa_service: MfaService,
+        login_limiter: RateLimiter,
+        mailer: Arc<dyn Mailer>,
+        app_url: String,
+    ) -> Self {
         Self {
             db_pool,
-            jwt_secret,
-            jwt_expiration,
+            auth_service,
+            mfa_service,
+            login_limiter,
+            mailer,
+            app_url,
         }
     }
     
@@ -34,7 +80,7 @@ impl UserService {
         .await?;
         
         if existing_user.is_some() {
-            return Err(anyhow!("User with this email already exists"));
+            return Err(AppError::Conflict("User with this email already exists".to_string()));
         }
         
         // Check if username is taken
@@ -46,7 +92,7 @@ impl UserService {
         .await?;
         
         if existing_username.is_some() {
-            return Err(anyhow!("Username is already taken"));
+            return Err(AppError::Conflict("Username is already taken".to_string()));
         }
         
         // Hash the password
@@ -58,7 +104,7 @@ impl UserService {
             r#"
             INSERT INTO users (id, email, username, password_hash, first_name, last_name, created_at, updated_at)
             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            RETURNING id, email, username, password_hash, first_name, last_name, created_at, updated_at
+            RETURNING id, email, username, password_hash, first_name, last_name, email_verified, mfa_enabled, created_at, updated_at
             "#,
             Uuid::new_v4(),
             req.email,
@@ -72,36 +118,91 @@ impl UserService {
         .fetch_one(&self.db_pool)
         .await?;
         
+        self.send_email_token(&user, EmailTokenPurpose::EmailVerification);
+        
         Ok(user.into())
     }
     
-    /// Login a user
-    pub async fn login(&self, req: UserLoginRequest) -> Result<(UserProfileResponse, String)> {
+    /// Login a user. Users with two-factor authentication get a challenge to
+    /// complete with `verify_mfa` instead of tokens.
+    pub async fn login(&self, req: UserLoginRequest) -> Result<LoginResult> {
+        // Limit guesses per account, whether or not it exists, before hashing anything
+        self.login_limiter.check(&format!("email:{}", req.email.to_lowercase())).await?;
+        
         // Find user by email
         let user = sqlx::query_as!(
             User,
             r#"
-            SELECT id, email, username, password_hash, first_name, last_name, created_at, updated_at
+            SELECT id, email, username, password_hash, first_name, last_name, email_verified, mfa_enabled, created_at, updated_at
             FROM users
             WHERE email = $1
             "#,
             req.email
         )
         .fetch_optional(&self.db_pool)
-        .await?
-        .ok_or_else(|| anyhow!("Invalid email or password"))?;
+        .await?;
+        
+        // Unknown emails still cost a password check, so timing doesn't reveal accounts
+        let Some(user) = user else {
+            verify_password(&req.password, dummy_password_hash())?;
+            
+            return Err(invalid_credentials());
+        };
+        
+        let locked = self.is_locked(user.id).await?;
         
         // Verify password
         let is_valid = verify_password(&req.password, &user.password_hash)?;
         
+        // Locked accounts fail like a wrong password, and don't extend their lock
+        if locked {
+            return Err(invalid_credentials());
+        }
+        
         if !is_valid {
-            return Err(anyhow!("Invalid email or password"));
+            self.record_failed_login(user.id).await?;
+            
+            return Err(invalid_credentials());
+        }
+        
+        if user.mfa_enabled {
+            let challenge = self.mfa_service.create_challenge(user.id).await?;
+            
+            return Ok(LoginResult::MfaRequired(challenge));
+        }
+        
+        self.reset_failed_logins(user.id).await?;
+        
+        // Start a session with an access token and a refresh token
+        let tokens = self.auth_service.create_session(user.id).await?;
+        
+        Ok(LoginResult::LoggedIn(LoginResponse { user: user.into(), tokens }))
+    }
+    
+    /// Complete a login with the second factor
+    pub async fn verify_mfa(&self, req: MfaVerifyRequest) -> Result<LoginResponse> {
+        let (user_id, accepted) = self.mfa_service.verify_challenge(&req.challenge_token, &req.code).await?;
+        
+        // Wrong codes count toward the lockout like wrong passwords
+        if !accepted {
+            self.record_failed_login(user_id).await?;
+            
+            return Err(AppError::Unauthorized("Invalid two-factor code".to_string()));
+        }
+        
+        // The account may have been locked since the challenge was created
+        let locked = self.is_locked(user_id).await?;
+        
+        if locked {
+            return Err(AppError::Unauthorized("Invalid or expired MFA challenge".to_string()));
         }
         
-        // Generate JWT token
-        let token = generate_token(user.id, &self.jwt_secret, self.jwt_expiration)?;
+        self.reset_failed_logins(user_id).await?;
+        
+        let tokens = self.auth_service.create_session(user_id).await?;
+        let user = self.get_profile(user_id).await?;
         
-        Ok((user.into(), token))
+        Ok(LoginResponse { user, tokens })
     }
     
     /// Get user profile by ID
@@ -109,7 +210,7 @@ impl UserService {
         let user = sqlx::query_as!(
             User,
             r#"
-            SELECT id, email, username, password_hash, first_name, last_name, created_at, updated_at
+            SELECT id, email, username, password_hash, first_name, last_name, email_verified, mfa_enabled, created_at, updated_at
             FROM users
             WHERE id = $1
             "#,
@@ -117,8 +218,387 @@ impl UserService {
         )
         .fetch_optional(&self.db_pool)
         .await?
-        .ok_or_else(|| anyhow!("User not found"))?;
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+        
+        Ok(user.into())
+    }
+    
+    /// Update the user's profile. A new email address has to be verified again.
+    pub async fn update_profile(&self, user_id: Uuid, req: UpdateProfileRequest) -> Result<UserProfileResponse> {
+        let mut tx = self.db_pool.begin().await?;
+        
+        let current = sqlx::query!(
+            "SELECT email, username FROM users WHERE id = $1 FOR UPDATE",
+            user_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+        
+        let email_changed = req.email.as_ref().is_some_and(|email| *email != current.email);
+        let username_changed = req.username.as_ref().is_some_and(|username| *username != current.username);
+        
+        if email_changed {
+            let existing_user = sqlx::query!(
+                "SELECT id FROM users WHERE email = $1",
+                req.email
+            )
+            .fetch_optional(&mut *tx)
+            .await?;
+            
+            if existing_user.is_some() {
+                return Err(AppError::Conflict("User with this email already exists".to_string()));
+            }
+            
+            // Links sent to the old address must not work anymore
+            sqlx::query!("DELETE FROM email_tokens WHERE user_id = $1", user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        
+        if username_changed {
+            let existing_username = sqlx::query!(
+                "SELECT id FROM users WHERE username = $1",
+                req.username
+            )
+            .fetch_optional(&mut *tx)
+            .await?;
+            
+            if existing_username.is_some() {
+                return Err(AppError::Conflict("Username is already taken".to_string()));
+            }
+        }
+        
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            UPDATE users
+            SET email = COALESCE($2, email),
+                username = COALESCE($3, username),
+                first_name = COALESCE($4, first_name),
+                last_name = COALESCE($5, last_name),
+                email_verified = email_verified AND NOT $6,
+                updated_at = $7
+            WHERE id = $1
+            RETURNING id, email, username, password_hash, first_name, last_name, email_verified, mfa_enabled, created_at, updated_at
+            "#,
+            user_id,
+            req.email,
+            req.username,
+            req.first_name,
+            req.last_name,
+            email_changed,
+            Utc::now()
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        
+        tx.commit().await?;
+        
+        if email_changed {
+            self.send_email_token(&user, EmailTokenPurpose::EmailVerification);
+        }
         
         Ok(user.into())
     }
+    
+    /// Change the password of a logged in user. Every other session is revoked,
+    /// the current one stays logged in.
+    pub async fn change_password(&self, user_id: Uuid, session_id: Uuid, req: ChangePasswordRequest) -> Result<()> {
+        let password_hash = sqlx::query_scalar!(
+            "SELECT password_hash FROM users WHERE id = $1",
+            user_id
+        )
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+        
+        if !verify_password(&req.current_password, &password_hash)? {
+            return Err(AppError::BadRequest("Invalid password".to_string()));
+        }
+        
+        let new_password_hash = hash_password(&req.new_password)?;
+        
+        let mut tx = self.db_pool.begin().await?;
+        
+        sqlx::query!(
+            "UPDATE users SET password_hash = $2, updated_at = $3 WHERE id = $1",
+            user_id,
+            new_password_hash,
+            Utc::now()
+        )
+        .execute(&mut *tx)
+        .await?;
+        
+        // Reset links were for the old password
+        sqlx::query!(
+            "DELETE FROM email_tokens WHERE user_id = $1 AND purpose = $2",
+            user_id,
+            EmailTokenPurpose::PasswordReset as _
+        )
+        .execute(&mut *tx)
+        .await?;
+        
+        AuthService::revoke_user_sessions(&mut tx, user_id, Some(session_id), RevokeReason::PasswordChange).await?;
+        
+        tx.commit().await?;
+        
+        Ok(())
+    }
+    
+    /// Delete the account after checking the password, and a two-factor code
+    /// when 2FA is enabled. Workouts, goals, measurements, custom exercises and
+    /// everything else owned by the user is deleted with it.
+    pub async fn delete_account(&self, user_id: Uuid, req: DeleteAccountRequest) -> Result<()> {
+        let user = sqlx::query!(
+            "SELECT password_hash, mfa_enabled FROM users WHERE id = $1",
+            user_id
+        )
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+        
+        if !verify_password(&req.password, &user.password_hash)? {
+            return Err(AppError::BadRequest("Invalid password".to_string()));
+        }
+        
+        if user.mfa_enabled {
+            let code = req.code.as_deref()
+                .ok_or_else(|| AppError::BadRequest("Two-factor code is required".to_string()))?;
+            
+            if !self.mfa_service.verify_code(user_id, code).await? {
+                return Err(AppError::BadRequest("Invalid two-factor code".to_string()));
+            }
+        }
+        
+        // Foreign keys cascade from users to all of the user's data
+        sqlx::query!("DELETE FROM users WHERE id = $1", user_id)
+            .execute(&self.db_pool)
+            .await?;
+        
+        Ok(())
+    }
+    
+    /// Email a password reset link. Unknown addresses are silently ignored so
+    /// that the response does not reveal which emails have an account.
+    pub async fn forgot_password(&self, req: ForgotPasswordRequest) -> Result<()> {
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            SELECT id, email, username, password_hash, first_name, last_name, email_verified, mfa_enabled, created_at, updated_at
+            FROM users
+            WHERE email = $1
+            "#,
+            req.email
+        )
+        .fetch_optional(&self.db_pool)
+        .await?;
+        
+        if let Some(user) = user {
+            self.send_email_token(&user, EmailTokenPurpose::PasswordReset);
+        }
+        
+        Ok(())
+    }
+    
+    /// Set a new password with the token from a password reset email. All
+    /// sessions are revoked, so whoever knew the old password is logged out.
+    pub async fn reset_password(&self, req: ResetPasswordRequest) -> Result<()> {
+        let password_hash = hash_password(&req.password)?;
+        
+        let mut tx = self.db_pool.begin().await?;
+        
+        let user_id = Self::consume_email_token(&mut tx, &req.token, EmailTokenPurpose::PasswordReset).await?;
+        
+        // The reset link was delivered to the address, which proves the user owns it
+        sqlx::query!(
+            r#"
+            UPDATE users
+            SET password_hash = $2, email_verified = TRUE, failed_login_attempts = 0, locked_until = NULL,
+                updated_at = $3
+            WHERE id = $1
+            "#,
+            user_id,
+            password_hash,
+            Utc::now()
+        )
+        .execute(&mut *tx)
+        .await?;
+        
+        AuthService::revoke_user_sessions(&mut tx, user_id, None, RevokeReason::PasswordReset).await?;
+        
+        tx.commit().await?;
+        
+        Ok(())
+    }
+    
+    /// Mark the email address as verified with the token from a verification email
+    pub async fn verify_email(&self, req: VerifyEmailRequest) -> Result<()> {
+        let mut tx = self.db_pool.begin().await?;
+        
+        let user_id = Self::consume_email_token(&mut tx, &req.token, EmailTokenPurpose::EmailVerification).await?;
+        
+        sqlx::query!(
+            "UPDATE users SET email_verified = TRUE, updated_at = $2 WHERE id = $1",
+            user_id,
+            Utc::now()
+        )
+        .execute(&mut *tx)
+        .await?;
+        
+        tx.commit().await?;
+        
+        Ok(())
+    }
+    
+    /// Whether the account is locked after too many failed logins
+    async fn is_locked(&self, user_id: Uuid) -> Result<bool> {
+        let locked = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM users WHERE id = $1 AND locked_until > NOW()) AS "exists!""#,
+            user_id
+        )
+        .fetch_one(&self.db_pool)
+        .await?;
+        
+        Ok(locked)
+    }
+    
+    /// Clear failed logins once a login has fully succeeded
+    async fn reset_failed_logins(&self, user_id: Uuid) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE users
+            SET failed_login_attempts = 0, locked_until = NULL
+            WHERE id = $1 AND failed_login_attempts > 0
+            "#,
+            user_id
+        )
+        .execute(&self.db_pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    /// Count a failed login and lock the account after too many in a row
+    async fn record_failed_login(&self, user_id: Uuid) -> Result<()> {
+        let failed_attempts = sqlx::query_scalar!(
+            r#"
+            UPDATE users
+            SET failed_login_attempts = failed_login_attempts + 1
+            WHERE id = $1
+            RETURNING failed_login_attempts
+            "#,
+            user_id
+        )
+        .fetch_one(&self.db_pool)
+        .await?;
+        
+        if let Some(duration) = lockout_duration(failed_attempts) {
+            sqlx::query!(
+                "UPDATE users SET locked_until = $2 WHERE id = $1",
+                user_id,
+                Utc::now() + duration
+            )
+            .execute(&self.db_pool)
+            .await?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Create a token for `purpose` and email its link to the user in the background.
+    /// Failures are only logged, and the request takes as long whether or not an
+    /// email is sent, so neither reveals which addresses have accounts.
+    fn send_email_token(&self, user: &User, purpose: EmailTokenPurpose) {
+        let service = self.clone();
+        let (user_id, address) = (user.id, user.email.clone());
+        
+        rt::spawn(async move {
+            let result = async {
+                let email = match purpose {
+                    EmailTokenPurpose::PasswordReset => {
+                        let valid_for = Duration::minutes(PASSWORD_RESET_EXPIRATION_MINUTES);
+                        let token = service.create_email_token(user_id, purpose, valid_for).await?;
+                        templates::password_reset(&address, &service.app_url, &token, PASSWORD_RESET_EXPIRATION_MINUTES)
+                    }
+                    EmailTokenPurpose::EmailVerification => {
+                        let valid_for = Duration::hours(EMAIL_VERIFICATION_EXPIRATION_HOURS);
+                        let token = service.create_email_token(user_id, purpose, valid_for).await?;
+                        templates::email_verification(&address, &service.app_url, &token, EMAIL_VERIFICATION_EXPIRATION_HOURS)
+                    }
+                };
+                
+                service.mailer.send(&email).await?;
+                
+                Ok::<_, AppError>(())
+            }
+            .await;
+            
+            if let Err(e) = result {
+                error!("Failed to send {:?} email to user {}: {:#}", purpose, user_id, e);
+            }
+        });
+    }
+    
+    /// Store a new token for `purpose`, replacing any earlier ones so that only
+    /// the latest emailed link works
+    async fn create_email_token(&self, user_id: Uuid, purpose: EmailTokenPurpose, valid_for: Duration) -> Result<String> {
+        let token = generate_opaque_token();
+        
+        let mut tx = self.db_pool.begin().await?;
+        
+        sqlx::query!(
+            "DELETE FROM email_tokens WHERE user_id = $1 AND purpose = $2",
+            user_id,
+            purpose as _
+        )
+        .execute(&mut *tx)
+        .await?;
+        
+        sqlx::query!(
+            r#"
+            INSERT INTO email_tokens (id, user_id, purpose, token_hash, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            Uuid::new_v4(),
+            user_id,
+            purpose as _,
+            hash_token(&token),
+            Utc::now() + valid_for
+        )
+        .execute(&mut *tx)
+        .await?;
+        
+        tx.commit().await?;
+        
+        Ok(token)
+    }
+    
+    /// Mark an emailed token as used and return its user. Unknown, used and
+    /// expired tokens are rejected with the same error.
+    async fn consume_email_token(conn: &mut PgConnection, token: &str, purpose: EmailTokenPurpose) -> Result<Uuid> {
+        let token = sqlx::query!(
+            r#"
+            SELECT id, user_id, expires_at, used_at
+            FROM email_tokens
+            WHERE token_hash = $1 AND purpose = $2
+            FOR UPDATE
+            "#,
+            hash_token(token),
+            purpose as _
+        )
+        .fetch_optional(&mut *conn)
+        .await?
+        .filter(|token| token.used_at.is_none() && token.expires_at > Utc::now())
+        .ok_or_else(|| AppError::BadRequest("Invalid or expired token".to_string()))?;
+        
+        sqlx::query!(
+            "UPDATE email_tokens SET used_at = NOW() WHERE id = $1",
+            token.id
+        )
+        .execute(&mut *conn)
+        .await?;
+        
+        Ok(token.user_id)
+    }
 }
@@ -1,196 +1,1122 @@
 use crate::db::DbPool;
+use crate::error::{AppError, Result};
+use crate::services::{GoalService, RecordService};
+use crate::utils::{like_pattern, route};
 use crate::models::{
-    Workout, Exercise, WorkoutExercise,
-    CreateWorkoutRequest, WorkoutDetailsResponse, WorkoutExerciseDetails,
+    PersonalRecord, Workout, Exercise, WorkoutSet, SetType, SetAggregate, Mechanics, MeasurementType,
+    CreateWorkoutRequest, WorkoutExerciseInput, WorkoutSetInput, WorkoutDetailsResponse, WorkoutExerciseDetails,
+    PatchWorkoutRequest, UpdateWorkoutExerciseRequest, WorkoutFilter, WorkoutSort, WorkoutCursor, WorkoutPage,
+    WorkoutInclude, WorkoutList, RouteFeature, RoutePoint, RouteTotals, UnitSystem, WorkoutLap, WorkoutSample,
 };
-use anyhow::{Result, anyhow};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use sqlx::PgConnection;
 use sqlx::postgres::PgQueryResult;
+use std::collections::HashMap;
 use uuid::Uuid;
 
+/// Number of workouts whose details are loaded per query when getting all of them
+const WORKOUT_BATCH_SIZE: usize = 500;
+
 /// Service for handling workout-related operations
+#[derive(Clone)]
 pub struct WorkoutService {
     db_pool: DbPool,
+    goal_service: GoalService,
+    record_service: RecordService,
 }
 
 impl WorkoutService {
     /// Create a new WorkoutService instance
-    pub fn new(db_pool: DbPool) -> Self {
-        Self { db_pool }
+    pub fn new(db_pool: DbPool, goal_service: GoalService, record_service: RecordService) -> Self {
+        Self { db_pool, goal_service, record_service }
     }
     
-    /// Create a new workout
-    pub async fn create_workout(&self, user_id: Uuid, req: CreateWorkoutRequest) -> Result<Uuid> {
+    /// Create a new workout, returning its ID and the personal records set in it
+    pub async fn create_workout(&self, user_id: Uuid, req: CreateWorkoutRequest) -> Result<(Uuid, Vec<PersonalRecord>)> {
         // Start a transaction
         let mut tx = self.db_pool.begin().await?;
         
-        // Create the workout
-        let workout_id = Uuid::new_v4();
+        // Only catalog exercises and the user's own custom exercises may be used
+        let exercise_ids: Vec<Uuid> = req.exercises.iter().map(|e| e.exercise_id).collect();
+        Self::ensure_exercises_visible(&mut tx, user_id, &exercise_ids).await?;
+        
+        // Create the workout with its exercises
+        let workout_id = Self::insert_workout(&mut tx, user_id, &req, Utc::now()).await?;
+        
+        // Detect new personal records
+        self.record_service.refresh_records(&mut tx, user_id, &exercise_ids).await?;
+        let records = self.record_service.get_workout_records(&mut tx, workout_id).await?;
+        
+        // Update progress of goals tracked from workouts
+        self.goal_service.refresh_tracked_goals(&mut tx, user_id).await?;
+        
+        // Commit the transaction
+        tx.commit().await?;
+        
+        Ok((workout_id, records))
+    }
+    
+    /// Create many workouts at once within the caller's transaction, e.g. when
+    /// importing history. Records and goals are refreshed once for all of them.
+    pub async fn import_workouts(
+        &self,
+        conn: &mut PgConnection,
+        user_id: Uuid,
+        workouts: &[CreateWorkoutRequest],
+    ) -> Result<Vec<Uuid>> {
+        let exercise_ids: Vec<Uuid> = workouts
+            .iter()
+            .flat_map(|workout| workout.exercises.iter().map(|e| e.exercise_id))
+            .collect();
+        Self::ensure_exercises_visible(conn, user_id, &exercise_ids).await?;
+        
         let now = Utc::now();
+        let mut workout_ids = Vec::with_capacity(workouts.len());
+        for workout in workouts {
+            workout_ids.push(Self::insert_workout(conn, user_id, workout, now).await?);
+        }
         
+        self.record_service.refresh_records(conn, user_id, &exercise_ids).await?;
+        self.goal_service.refresh_tracked_goals(conn, user_id).await?;
+        
+        Ok(workout_ids)
+    }
+    
+    /// Store the GPS route of a new workout within the caller's transaction,
+    /// linked to its first exercise entry
+    pub async fn insert_route(
+        conn: &mut PgConnection,
+        workout_id: Uuid,
+        totals: &RouteTotals,
+        points: &[RoutePoint],
+    ) -> Result<()> {
         sqlx::query!(
             r#"
-            INSERT INTO workouts (id, user_id, name, description, date, duration, calories_burned, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            INSERT INTO workout_routes (workout_id, workout_exercise_id, distance, moving_time, elapsed_time, elevation_gain)
+            VALUES (
+                $1,
+                (SELECT id FROM workout_exercises WHERE workout_id = $1 ORDER BY position LIMIT 1),
+                $2::FLOAT8, $3, $4, $5::FLOAT8
+            )
+            "#,
+            workout_id,
+            totals.distance,
+            totals.moving_time,
+            totals.elapsed_time,
+            totals.elevation_gain
+        )
+        .execute(&mut *conn)
+        .await?;
+        
+        let latitudes: Vec<f64> = points.iter().map(|point| point.latitude).collect();
+        let longitudes: Vec<f64> = points.iter().map(|point| point.longitude).collect();
+        let elevations: Vec<Option<f64>> = points.iter().map(|point| point.elevation).collect();
+        let times: Vec<Option<DateTime<Utc>>> = points.iter().map(|point| point.time).collect();
+        
+        sqlx::query!(
+            r#"
+            INSERT INTO route_points (workout_id, point_index, latitude, longitude, elevation, recorded_at)
+            SELECT $1, (p.i - 1)::INTEGER, p.latitude, p.longitude, p.elevation, p.recorded_at
+            FROM UNNEST($2::FLOAT8[], $3::FLOAT8[], $4::FLOAT8[], $5::TIMESTAMPTZ[])
+                WITH ORDINALITY AS p(latitude, longitude, elevation, recorded_at, i)
+            "#,
+            workout_id,
+            &latitudes,
+            &longitudes,
+            &elevations as &[Option<f64>],
+            &times as &[Option<DateTime<Utc>>]
+        )
+        .execute(&mut *conn)
+        .await?;
+        
+        Ok(())
+    }
+    
+    /// Get the GPS route of a workout as a GeoJSON feature, with splits per km
+    /// or, in imperial units, per mile
+    pub async fn get_route(&self, user_id: Uuid, workout_id: Uuid, units: UnitSystem) -> Result<RouteFeature> {
+        let totals = sqlx::query_as!(
+            RouteTotals,
+            r#"
+            SELECT wr.distance::FLOAT8 AS "distance!", wr.moving_time, wr.elapsed_time,
+                   wr.elevation_gain::FLOAT8 AS "elevation_gain"
+            FROM workout_routes wr
+            JOIN workouts w ON w.id = wr.workout_id
+            WHERE wr.workout_id = $1 AND w.user_id = $2
+            "#,
+            workout_id,
+            user_id
+        )
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Route not found".to_string()))?;
+        
+        let points = sqlx::query_as!(
+            RoutePoint,
+            r#"
+            SELECT latitude, longitude, elevation, recorded_at AS "time"
+            FROM route_points
+            WHERE workout_id = $1
+            ORDER BY point_index
+            "#,
+            workout_id
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+        
+        let splits = route::splits(&points, units);
+        
+        Ok(RouteFeature::new(workout_id, &points, totals.summary(splits, units)))
+    }
+    
+    /// Store the laps recorded by a device for a new workout within the
+    /// caller's transaction
+    pub async fn insert_laps(conn: &mut PgConnection, workout_id: Uuid, laps: &[WorkoutLap]) -> Result<()> {
+        if laps.is_empty() {
+            return Ok(());
+        }
+        
+        let indexes: Vec<i32> = laps.iter().map(|lap| lap.lap).collect();
+        let start_times: Vec<DateTime<Utc>> = laps.iter().map(|lap| lap.start_time).collect();
+        let elapsed_times: Vec<i32> = laps.iter().map(|lap| lap.elapsed_time).collect();
+        let moving_times: Vec<i32> = laps.iter().map(|lap| lap.moving_time).collect();
+        let distances: Vec<Option<f64>> = laps.iter().map(|lap| lap.distance).collect();
+        let average_heart_rates: Vec<Option<i32>> = laps.iter().map(|lap| lap.average_heart_rate).collect();
+        let max_heart_rates: Vec<Option<i32>> = laps.iter().map(|lap| lap.max_heart_rate).collect();
+        let average_powers: Vec<Option<i32>> = laps.iter().map(|lap| lap.average_power).collect();
+        let max_powers: Vec<Option<i32>> = laps.iter().map(|lap| lap.max_power).collect();
+        let average_cadences: Vec<Option<i32>> = laps.iter().map(|lap| lap.average_cadence).collect();
+        let calories: Vec<Option<i32>> = laps.iter().map(|lap| lap.calories).collect();
+        let elevation_gains: Vec<Option<f64>> = laps.iter().map(|lap| lap.elevation_gain).collect();
+        
+        sqlx::query!(
+            r#"
+            INSERT INTO workout_laps (
+                workout_id, lap_index, start_time, elapsed_time, moving_time, distance,
+                average_heart_rate, max_heart_rate, average_power, max_power, average_cadence,
+                calories, elevation_gain
+            )
+            SELECT $1, * FROM UNNEST(
+                $2::INTEGER[], $3::TIMESTAMPTZ[], $4::INTEGER[], $5::INTEGER[], $6::FLOAT8[],
+                $7::INTEGER[], $8::INTEGER[], $9::INTEGER[], $10::INTEGER[], $11::INTEGER[],
+                $12::INTEGER[], $13::FLOAT8[]
+            )
+            "#,
+            workout_id,
+            &indexes,
+            &start_times,
+            &elapsed_times,
+            &moving_times,
+            &distances as &[Option<f64>],
+            &average_heart_rates as &[Option<i32>],
+            &max_heart_rates as &[Option<i32>],
+            &average_powers as &[Option<i32>],
+            &max_powers as &[Option<i32>],
+            &average_cadences as &[Option<i32>],
+            &calories as &[Option<i32>],
+            &elevation_gains as &[Option<f64>]
+        )
+        .execute(&mut *conn)
+        .await?;
+        
+        Ok(())
+    }
+    
+    /// Store the heart rate, power and cadence samples recorded by a device
+    /// for a new workout within the caller's transaction
+    pub async fn insert_samples(conn: &mut PgConnection, workout_id: Uuid, samples: &[WorkoutSample]) -> Result<()> {
+        if samples.is_empty() {
+            return Ok(());
+        }
+        
+        let times: Vec<DateTime<Utc>> = samples.iter().map(|sample| sample.time).collect();
+        let heart_rates: Vec<Option<i32>> = samples.iter().map(|sample| sample.heart_rate).collect();
+        let powers: Vec<Option<i32>> = samples.iter().map(|sample| sample.power).collect();
+        let cadences: Vec<Option<i32>> = samples.iter().map(|sample| sample.cadence).collect();
+        
+        sqlx::query!(
+            r#"
+            INSERT INTO workout_samples (workout_id, sample_index, recorded_at, heart_rate, power, cadence)
+            SELECT $1, (s.i - 1)::INTEGER, s.recorded_at, s.heart_rate, s.power, s.cadence
+            FROM UNNEST($2::TIMESTAMPTZ[], $3::INTEGER[], $4::INTEGER[], $5::INTEGER[])
+                WITH ORDINALITY AS s(recorded_at, heart_rate, power, cadence, i)
             "#,
             workout_id,
+            &times,
+            &heart_rates as &[Option<i32>],
+            &powers as &[Option<i32>],
+            &cadences as &[Option<i32>]
+        )
+        .execute(&mut *conn)
+        .await?;
+        
+        Ok(())
+    }
+    
+    /// Get the laps of a workout, with distances and paces per km or, in
+    /// imperial units, per mile
+    pub async fn get_laps(&self, user_id: Uuid, workout_id: Uuid, units: UnitSystem) -> Result<Vec<WorkoutLap>> {
+        self.ensure_workout_exists(user_id, workout_id).await?;
+        
+        let mut laps = sqlx::query_as!(
+            WorkoutLap,
+            r#"
+            SELECT lap_index AS "lap", start_time, elapsed_time, moving_time,
+                   distance::FLOAT8 AS "distance", NULL::INTEGER AS "average_pace",
+                   average_heart_rate, max_heart_rate, average_power, max_power, average_cadence,
+                   calories, elevation_gain::FLOAT8 AS "elevation_gain"
+            FROM workout_laps
+            WHERE workout_id = $1
+            ORDER BY lap_index
+            "#,
+            workout_id
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+        
+        for lap in &mut laps {
+            lap.convert_units(units);
+        }
+        
+        Ok(laps)
+    }
+    
+    /// Get the heart rate, power and cadence samples of a workout in time order
+    pub async fn get_samples(&self, user_id: Uuid, workout_id: Uuid) -> Result<Vec<WorkoutSample>> {
+        self.ensure_workout_exists(user_id, workout_id).await?;
+        
+        let samples = sqlx::query_as!(
+            WorkoutSample,
+            r#"
+            SELECT recorded_at AS "time", heart_rate, power, cadence
+            FROM workout_samples
+            WHERE workout_id = $1
+            ORDER BY sample_index
+            "#,
+            workout_id
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+        
+        Ok(samples)
+    }
+    
+    /// Get workout details by ID
+    pub async fn get_workout(&self, user_id: Uuid, workout_id: Uuid) -> Result<WorkoutDetailsResponse> {
+        self.get_workouts_with_details(user_id, &[workout_id])
+            .await?
+            .pop()
+            .ok_or_else(|| AppError::NotFound("Workout not found".to_string()))
+    }
+    
+    /// Get the details of several workouts with a fixed number of queries.
+    ///
+    /// Workouts are returned in the order of `workout_ids`; IDs that do not
+    /// exist or belong to another user are skipped.
+    pub async fn get_workouts_with_details(
+        &self,
+        user_id: Uuid,
+        workout_ids: &[Uuid],
+    ) -> Result<Vec<WorkoutDetailsResponse>> {
+        if workout_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        let workouts = sqlx::query_as!(
+            Workout,
+            r#"
+            SELECT id, user_id, name, description, date, duration, calories_burned, created_at, updated_at
+            FROM workouts
+            WHERE id = ANY($1) AND user_id = $2
+            "#,
+            workout_ids,
+            user_id
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+        
+        let found_ids: Vec<Uuid> = workouts.iter().map(|w| w.id).collect();
+        
+        // Get the entries of all workouts together with their exercises
+        let entries = sqlx::query!(
+            r#"
+            SELECT we.id, we.workout_id, we.sets, we.reps, we.weight::FLOAT8 AS "weight", we.duration,
+                   we.distance::FLOAT8 AS "distance", we.notes, we.position,
+                   e.id AS exercise_id, e.name, e.description, e.category, e.owner_id, e.slug,
+                   e.primary_muscles, e.secondary_muscles, e.equipment,
+                   e.mechanics AS "mechanics: Mechanics", e.measurement_type AS "measurement_type: MeasurementType",
+                   e.created_at, e.updated_at
+            FROM workout_exercises we
+            JOIN exercises e ON e.id = we.exercise_id
+            WHERE we.workout_id = ANY($1)
+            ORDER BY we.workout_id, we.position, we.created_at
+            "#,
+            &found_ids
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+        
+        // Get the sets of all entries at once
+        let workout_sets = sqlx::query_as!(
+            WorkoutSet,
+            r#"
+            SELECT ws.id, ws.workout_exercise_id, ws.set_index, ws.reps, ws.weight::FLOAT8 AS "weight", ws.duration,
+                   ws.distance::FLOAT8 AS "distance", ws.rpe::FLOAT8 AS "rpe", ws.rir,
+                   ws.set_type AS "set_type: SetType", ws.completed, ws.created_at, ws.updated_at
+            FROM workout_sets ws
+            JOIN workout_exercises we ON we.id = ws.workout_exercise_id
+            WHERE we.workout_id = ANY($1)
+            ORDER BY ws.set_index
+            "#,
+            &found_ids
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+        
+        let mut sets_by_entry: HashMap<Uuid, Vec<WorkoutSet>> = HashMap::new();
+        for set in workout_sets {
+            sets_by_entry.entry(set.workout_exercise_id).or_default().push(set);
+        }
+        
+        let mut exercises_by_workout: HashMap<Uuid, Vec<WorkoutExerciseDetails>> = HashMap::new();
+        for entry in entries {
+            let exercise = Exercise {
+                id: entry.exercise_id,
+                name: entry.name,
+                description: entry.description,
+                category: entry.category,
+                owner_id: entry.owner_id,
+                slug: entry.slug,
+                primary_muscles: entry.primary_muscles,
+                secondary_muscles: entry.secondary_muscles,
+                equipment: entry.equipment,
+                mechanics: entry.mechanics,
+                measurement_type: entry.measurement_type,
+                created_at: entry.created_at,
+                updated_at: entry.updated_at,
+            };
+            
+            exercises_by_workout.entry(entry.workout_id).or_default().push(WorkoutExerciseDetails {
+                id: entry.id,
+                exercise,
+                sets: entry.sets,
+                reps: entry.reps,
+                weight: entry.weight,
+                duration: entry.duration,
+                distance: entry.distance,
+                notes: entry.notes,
+                position: entry.position,
+                workout_sets: sets_by_entry.remove(&entry.id).unwrap_or_default(),
+            });
+        }
+        
+        let mut workouts_by_id: HashMap<Uuid, Workout> = workouts.into_iter().map(|w| (w.id, w)).collect();
+        
+        let details = workout_ids
+            .iter()
+            .filter_map(|id| workouts_by_id.remove(id))
+            .map(|workout| WorkoutDetailsResponse {
+                exercises: exercises_by_workout.remove(&workout.id).unwrap_or_default(),
+                id: workout.id,
+                name: workout.name,
+                description: workout.description,
+                date: workout.date,
+                duration: workout.duration,
+                calories_burned: workout.calories_burned,
+                updated_at: workout.updated_at,
+            })
+            .collect();
+        
+        Ok(details)
+    }
+    
+    /// Get the details of all of a user's workouts, oldest first
+    pub async fn get_all_workouts(&self, user_id: Uuid) -> Result<Vec<WorkoutDetailsResponse>> {
+        let workout_ids = sqlx::query_scalar!(
+            "SELECT id FROM workouts WHERE user_id = $1 ORDER BY date ASC, id ASC",
+            user_id
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+        
+        let mut workouts = Vec::with_capacity(workout_ids.len());
+        for ids in workout_ids.chunks(WORKOUT_BATCH_SIZE) {
+            workouts.extend(self.get_workouts_with_details(user_id, ids).await?);
+        }
+        
+        Ok(workouts)
+    }
+    
+    /// Get a page of a user's workouts matching the filter
+    pub async fn get_workouts(&self, user_id: Uuid, filter: WorkoutFilter) -> Result<WorkoutPage> {
+        let limit = filter.limit();
+        let name = filter.name.as_deref().map(like_pattern);
+        let sort = filter.sort.unwrap_or_default();
+        let cursor = match filter.cursor.as_deref() {
+            Some(cursor) => Some(
+                WorkoutCursor::decode(cursor)
+                    .filter(|cursor| cursor.sort == sort)
+                    .ok_or_else(|| AppError::BadRequest("Invalid cursor".to_string()))?,
+            ),
+            None => None,
+        };
+        
+        // Count all matches and fetch the page in one go, with one extra row to
+        // know whether there is a next page. The count always returns a row, so
+        // the total is known even when the page is empty.
+        let rows = sqlx::query!(
+            r#"
+            WITH matching AS (
+                SELECT w.id, w.user_id, w.name, w.description, w.date, w.duration, w.calories_burned,
+                       w.created_at, w.updated_at
+                FROM workouts w
+                WHERE w.user_id = $1
+                  AND ($2::TIMESTAMPTZ IS NULL OR w.date >= $2)
+                  AND ($3::TIMESTAMPTZ IS NULL OR w.date <= $3)
+                  AND ($4::VARCHAR IS NULL OR w.name ILIKE $4)
+                  AND ($5::INTEGER IS NULL OR w.duration >= $5)
+                  AND ($6::INTEGER IS NULL OR w.duration <= $6)
+                  AND ($7::UUID IS NULL OR EXISTS (
+                      SELECT 1 FROM workout_exercises we
+                      WHERE we.workout_id = w.id AND we.exercise_id = $7
+                  ))
+                  AND ($8::VARCHAR IS NULL OR EXISTS (
+                      SELECT 1 FROM workout_exercises we
+                      JOIN exercises e ON e.id = we.exercise_id
+                      WHERE we.workout_id = w.id AND LOWER(e.category) = LOWER($8)
+                  ))
+            )
+            SELECT total.count AS "total!",
+                   page.id AS "id?", page.user_id AS "user_id?", page.name AS "name?", page.description,
+                   page.date AS "date?", page.duration, page.calories_burned,
+                   page.created_at AS "created_at?", page.updated_at AS "updated_at?"
+            FROM (SELECT COUNT(*) AS count FROM matching) total
+            LEFT JOIN LATERAL (
+                SELECT *
+                FROM matching m
+                WHERE $9::TIMESTAMPTZ IS NULL
+                   OR ($11 AND (m.date, m.id) > ($9, $10::UUID))
+                   OR (NOT $11 AND (m.date, m.id) < ($9, $10::UUID))
+                ORDER BY CASE WHEN $11 THEN m.date END ASC, CASE WHEN $11 THEN m.id END ASC,
+                         m.date DESC, m.id DESC
+                LIMIT $12
+            ) page ON TRUE
+            ORDER BY CASE WHEN $11 THEN page.date END ASC, CASE WHEN $11 THEN page.id END ASC,
+                     page.date DESC, page.id DESC
+            "#,
             user_id,
+            filter.from,
+            filter.to,
+            name,
+            filter.min_duration,
+            filter.max_duration,
+            filter.exercise_id,
+            filter.category,
+            cursor.map(|c| c.date),
+            cursor.map(|c| c.id),
+            sort == WorkoutSort::DateAsc,
+            limit + 1
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+        
+        let total = rows.first().map_or(0, |row| row.total);
+        let mut workouts: Vec<Workout> = rows
+            .into_iter()
+            .filter_map(|row| {
+                Some(Workout {
+                    id: row.id?,
+                    user_id: row.user_id?,
+                    name: row.name?,
+                    description: row.description,
+                    date: row.date?,
+                    duration: row.duration,
+                    calories_burned: row.calories_burned,
+                    created_at: row.created_at?,
+                    updated_at: row.updated_at?,
+                })
+            })
+            .collect();
+        
+        let next_cursor = if workouts.len() as i64 > limit {
+            workouts.truncate(limit as usize);
+            workouts.last().map(|w| WorkoutCursor { date: w.date, id: w.id, sort }.encode())
+        } else {
+            None
+        };
+        
+        let workouts = match filter.include {
+            Some(WorkoutInclude::Exercises) => {
+                let ids: Vec<Uuid> = workouts.iter().map(|w| w.id).collect();
+                WorkoutList::Details(self.get_workouts_with_details(user_id, &ids).await?)
+            }
+            None => WorkoutList::Workouts(workouts),
+        };
+        
+        Ok(WorkoutPage {
+            workouts,
+            total,
+            next_cursor,
+        })
+    }
+    
+    /// Replace a workout and all of its exercise entries
+    pub async fn update_workout(
+        &self,
+        user_id: Uuid,
+        workout_id: Uuid,
+        req: CreateWorkoutRequest,
+    ) -> Result<WorkoutDetailsResponse> {
+        let mut tx = self.db_pool.begin().await?;
+        
+        Self::lock_workout(&mut tx, user_id, workout_id).await?;
+        
+        let exercise_ids: Vec<Uuid> = req.exercises.iter().map(|e| e.exercise_id).collect();
+        Self::ensure_exercises_visible(&mut tx, user_id, &exercise_ids).await?;
+        
+        // Records of exercises dropped from the workout need recomputing too
+        let mut affected_exercise_ids = Self::workout_exercise_ids(&mut tx, workout_id).await?;
+        affected_exercise_ids.extend(&exercise_ids);
+        
+        let now = Utc::now();
+        
+        sqlx::query!(
+            r#"
+            UPDATE workouts
+            SET name = $2, description = $3, date = $4, duration = $5, calories_burned = $6, updated_at = $7
+            WHERE id = $1
+            "#,
+            workout_id,
             req.name,
             req.description,
             req.date,
             req.duration,
             req.calories_burned,
-            now,
             now
         )
         .execute(&mut *tx)
         .await?;
         
-        // Create workout exercises
-        for exercise in req.exercises {
-            let workout_exercise_id = Uuid::new_v4();
-            
-            sqlx::query!(
-                r#"
-                INSERT INTO workout_exercises (id, workout_id, exercise_id, sets, reps, weight, duration, distance, notes, created_at, updated_at)
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
-                "#,
-                workout_exercise_id,
-                workout_id,
-                exercise.exercise_id,
-                exercise.sets,
-                exercise.reps,
-                exercise.weight,
-                exercise.duration,
-                exercise.distance,
-                exercise.notes,
-                now,
-                now
-            )
+        // Replace the exercise entries
+        sqlx::query!("DELETE FROM workout_exercises WHERE workout_id = $1", workout_id)
             .execute(&mut *tx)
             .await?;
+        
+        for (position, exercise) in req.exercises.iter().enumerate() {
+            Self::insert_workout_exercise(&mut tx, workout_id, position as i32, exercise, now).await?;
         }
         
-        // Commit the transaction
+        self.record_service.refresh_records(&mut tx, user_id, &affected_exercise_ids).await?;
+        self.goal_service.refresh_tracked_goals(&mut tx, user_id).await?;
+        
         tx.commit().await?;
         
-        Ok(workout_id)
+        self.get_workout(user_id, workout_id).await
     }
     
-    /// Get workout details by ID
-    pub async fn get_workout(&self, user_id: Uuid, workout_id: Uuid) -> Result<WorkoutDetailsResponse> {
-        // Get the workout
-        let workout = sqlx::query_as!(
-            Workout,
+    /// Update some of a workout's own fields, leaving its exercise entries untouched
+    pub async fn patch_workout(
+        &self,
+        user_id: Uuid,
+        workout_id: Uuid,
+        req: PatchWorkoutRequest,
+    ) -> Result<WorkoutDetailsResponse> {
+        let mut tx = self.db_pool.begin().await?;
+        
+        let result = sqlx::query!(
             r#"
-            SELECT id, user_id, name, description, date, duration, calories_burned, created_at, updated_at
-            FROM workouts
+            UPDATE workouts
+            SET name = COALESCE($3, name),
+                description = COALESCE($4, description),
+                date = COALESCE($5, date),
+                duration = COALESCE($6, duration),
+                calories_burned = COALESCE($7, calories_burned),
+                updated_at = $8
             WHERE id = $1 AND user_id = $2
             "#,
             workout_id,
-            user_id
+            user_id,
+            req.name,
+            req.description,
+            req.date,
+            req.duration,
+            req.calories_burned,
+            Utc::now()
         )
-        .fetch_optional(&self.db_pool)
-        .await?
-        .ok_or_else(|| anyhow!("Workout not found"))?;
+        .execute(&mut *tx)
+        .await?;
         
-        // Get the workout exercises
-        let mut exercises = Vec::new();
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Workout not found".to_string()));
+        }
+        
+        // A new date changes which sets came first
+        if req.date.is_some() {
+            let exercise_ids = Self::workout_exercise_ids(&mut tx, workout_id).await?;
+            self.record_service.refresh_records(&mut tx, user_id, &exercise_ids).await?;
+        }
+        
+        // A new date can move the workout in or out of a goal's window
+        self.goal_service.refresh_tracked_goals(&mut tx, user_id).await?;
         
-        let workout_exercises = sqlx::query_as!(
-            WorkoutExercise,
+        tx.commit().await?;
+        
+        self.get_workout(user_id, workout_id).await
+    }
+    
+    /// Append an exercise entry to a workout
+    pub async fn add_workout_exercise(
+        &self,
+        user_id: Uuid,
+        workout_id: Uuid,
+        input: WorkoutExerciseInput,
+    ) -> Result<WorkoutDetailsResponse> {
+        let mut tx = self.db_pool.begin().await?;
+        
+        Self::lock_workout(&mut tx, user_id, workout_id).await?;
+        Self::ensure_exercises_visible(&mut tx, user_id, &[input.exercise_id]).await?;
+        
+        let position = sqlx::query_scalar!(
             r#"
-            SELECT we.id, we.workout_id, we.exercise_id, we.sets, we.reps, we.weight, we.duration, we.distance, we.notes, we.created_at, we.updated_at
-            FROM workout_exercises we
-            WHERE we.workout_id = $1
+            SELECT COALESCE(MAX(position) + 1, 0) AS "position!"
+            FROM workout_exercises
+            WHERE workout_id = $1
             "#,
             workout_id
         )
-        .fetch_all(&self.db_pool)
+        .fetch_one(&mut *tx)
         .await?;
         
-        for we in workout_exercises {
-            // Get the exercise details
-            let exercise = sqlx::query_as!(
-                Exercise,
-                r#"
-                SELECT id, name, description, category, created_at, updated_at
-                FROM exercises
-                WHERE id = $1
-                "#,
-                we.exercise_id
-            )
-            .fetch_one(&self.db_pool)
+        let now = Utc::now();
+        
+        Self::insert_workout_exercise(&mut tx, workout_id, position, &input, now).await?;
+        Self::touch_workout(&mut tx, workout_id, now).await?;
+        
+        self.record_service.refresh_records(&mut tx, user_id, &[input.exercise_id]).await?;
+        self.goal_service.refresh_tracked_goals(&mut tx, user_id).await?;
+        
+        tx.commit().await?;
+        
+        self.get_workout(user_id, workout_id).await
+    }
+    
+    /// Update an exercise entry of a workout
+    pub async fn update_workout_exercise(
+        &self,
+        user_id: Uuid,
+        workout_id: Uuid,
+        workout_exercise_id: Uuid,
+        req: UpdateWorkoutExerciseRequest,
+    ) -> Result<WorkoutDetailsResponse> {
+        let mut tx = self.db_pool.begin().await?;
+        
+        Self::lock_workout(&mut tx, user_id, workout_id).await?;
+        
+        if let Some(exercise_id) = req.exercise_id {
+            Self::ensure_exercises_visible(&mut tx, user_id, &[exercise_id]).await?;
+        }
+        
+        let previous_exercise_id = sqlx::query_scalar!(
+            "SELECT exercise_id FROM workout_exercises WHERE id = $1 AND workout_id = $2",
+            workout_exercise_id,
+            workout_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Workout exercise not found".to_string()))?;
+        
+        let now = Utc::now();
+        
+        // Individual sets take precedence over the aggregate fields
+        let aggregate = match &req.workout_sets {
+            Some(workout_sets) => SetAggregate::from_sets(workout_sets),
+            None => SetAggregate {
+                sets: req.sets,
+                reps: req.reps,
+                weight: req.weight,
+                duration: req.duration,
+                distance: req.distance,
+            },
+        };
+        let replace_aggregate = req.workout_sets.is_some();
+        
+        let updated = sqlx::query!(
+            r#"
+            UPDATE workout_exercises
+            SET exercise_id = COALESCE($3, exercise_id),
+                sets = CASE WHEN $4 THEN $5 ELSE COALESCE($5, sets) END,
+                reps = CASE WHEN $4 THEN $6 ELSE COALESCE($6, reps) END,
+                weight = CASE WHEN $4 THEN $7::FLOAT8 ELSE COALESCE($7::FLOAT8, weight) END,
+                duration = CASE WHEN $4 THEN $8 ELSE COALESCE($8, duration) END,
+                distance = CASE WHEN $4 THEN $9::FLOAT8 ELSE COALESCE($9::FLOAT8, distance) END,
+                notes = COALESCE($10, notes),
+                updated_at = $11
+            WHERE id = $1 AND workout_id = $2
+            RETURNING exercise_id, sets, reps, weight::FLOAT8 AS "weight", duration, distance::FLOAT8 AS "distance"
+            "#,
+            workout_exercise_id,
+            workout_id,
+            req.exercise_id,
+            replace_aggregate,
+            aggregate.sets,
+            aggregate.reps,
+            aggregate.weight,
+            aggregate.duration,
+            aggregate.distance,
+            req.notes,
+            now
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        
+        match req.workout_sets {
+            Some(workout_sets) => {
+                Self::replace_workout_sets(&mut tx, workout_exercise_id, &workout_sets, now).await?;
+            }
+            // Older clients edit the aggregate fields, so rebuild the sets from them
+            None if aggregate != SetAggregate::default() => {
+                let aggregate = SetAggregate {
+                    sets: updated.sets,
+                    reps: updated.reps,
+                    weight: updated.weight,
+                    duration: updated.duration,
+                    distance: updated.distance,
+                };
+                Self::replace_workout_sets(&mut tx, workout_exercise_id, &aggregate.to_sets(), now).await?;
+            }
+            None => {}
+        }
+        
+        Self::touch_workout(&mut tx, workout_id, now).await?;
+        
+        self.record_service
+            .refresh_records(&mut tx, user_id, &[previous_exercise_id, updated.exercise_id])
             .await?;
-            
-            exercises.push(WorkoutExerciseDetails {
-                id: we.id,
-                exercise,
-                sets: we.sets,
-                reps: we.reps,
-                weight: we.weight,
-                duration: we.duration,
-                distance: we.distance,
-                notes: we.notes,
-            });
+        self.goal_service.refresh_tracked_goals(&mut tx, user_id).await?;
+        
+        tx.commit().await?;
+        
+        self.get_workout(user_id, workout_id).await
+    }
+    
+    /// Reorder the exercise entries of a workout.
+    /// `workout_exercise_ids` must contain every entry of the workout exactly once.
+    pub async fn reorder_workout_exercises(
+        &self,
+        user_id: Uuid,
+        workout_id: Uuid,
+        workout_exercise_ids: Vec<Uuid>,
+    ) -> Result<WorkoutDetailsResponse> {
+        let mut tx = self.db_pool.begin().await?;
+        
+        Self::lock_workout(&mut tx, user_id, workout_id).await?;
+        
+        let mut current_ids = sqlx::query_scalar!(
+            "SELECT id FROM workout_exercises WHERE workout_id = $1",
+            workout_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        
+        let mut requested_ids = workout_exercise_ids.clone();
+        current_ids.sort();
+        requested_ids.sort();
+        
+        if current_ids != requested_ids {
+            return Err(AppError::BadRequest("The new order must list every exercise of the workout exactly once".to_string()));
         }
         
-        Ok(WorkoutDetailsResponse {
-            id: workout.id,
-            name: workout.name,
-            description: workout.description,
-            date: workout.date,
-            duration: workout.duration,
-            calories_burned: workout.calories_burned,
-            exercises,
-        })
+        let now = Utc::now();
+        
+        // Positions are assigned from the index in the requested order
+        sqlx::query!(
+            r#"
+            UPDATE workout_exercises we
+            SET position = ordered.position - 1, updated_at = $3
+            FROM UNNEST($2::UUID[]) WITH ORDINALITY AS ordered(id, position)
+            WHERE we.id = ordered.id AND we.workout_id = $1
+            "#,
+            workout_id,
+            &workout_exercise_ids,
+            now
+        )
+        .execute(&mut *tx)
+        .await?;
+        
+        Self::touch_workout(&mut tx, workout_id, now).await?;
+        
+        tx.commit().await?;
+        
+        self.get_workout(user_id, workout_id).await
     }
     
-    /// Get all workouts for a user
-    pub async fn get_workouts(&self, user_id: Uuid) -> Result<Vec<Workout>> {
-        let workouts = sqlx::query_as!(
-            Workout,
+    /// Remove an exercise entry from a workout
+    pub async fn remove_workout_exercise(
+        &self,
+        user_id: Uuid,
+        workout_id: Uuid,
+        workout_exercise_id: Uuid,
+    ) -> Result<WorkoutDetailsResponse> {
+        let mut tx = self.db_pool.begin().await?;
+        
+        Self::lock_workout(&mut tx, user_id, workout_id).await?;
+        
+        let removed = sqlx::query!(
             r#"
-            SELECT id, user_id, name, description, date, duration, calories_burned, created_at, updated_at
-            FROM workouts
-            WHERE user_id = $1
-            ORDER BY date DESC
+            DELETE FROM workout_exercises
+            WHERE id = $1 AND workout_id = $2
+            RETURNING position, exercise_id
             "#,
-            user_id
+            workout_exercise_id,
+            workout_id
         )
-        .fetch_all(&self.db_pool)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Workout exercise not found".to_string()))?;
+        
+        let now = Utc::now();
+        
+        // Close the gap left by the removed entry
+        sqlx::query!(
+            r#"
+            UPDATE workout_exercises
+            SET position = position - 1, updated_at = $3
+            WHERE workout_id = $1 AND position > $2
+            "#,
+            workout_id,
+            removed.position,
+            now
+        )
+        .execute(&mut *tx)
         .await?;
         
-        Ok(workouts)
+        Self::touch_workout(&mut tx, workout_id, now).await?;
+        
+        self.record_service.refresh_records(&mut tx, user_id, &[removed.exercise_id]).await?;
+        self.goal_service.refresh_tracked_goals(&mut tx, user_id).await?;
+        
+        tx.commit().await?;
+        
+        self.get_workout(user_id, workout_id).await
     }
     
     /// Delete a workout
     pub async fn delete_workout(&self, user_id: Uuid, workout_id: Uuid) -> Result<PgQueryResult> {
+        let mut tx = self.db_pool.begin().await?;
+        
         // Check if the workout exists and belongs to the user
-        let workout = sqlx::query!(
+        Self::lock_workout(&mut tx, user_id, workout_id).await?;
+        
+        let exercise_ids = Self::workout_exercise_ids(&mut tx, workout_id).await?;
+        
+        // Delete the workout (cascade will delete related records)
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM workouts
+            WHERE id = $1
+            "#,
+            workout_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        
+        // Records set in this workout may have been beaten by later ones
+        self.record_service.refresh_records(&mut tx, user_id, &exercise_ids).await?;
+        
+        // Goals tracked from this workout no longer count it
+        self.goal_service.refresh_tracked_goals(&mut tx, user_id).await?;
+        
+        tx.commit().await?;
+        
+        Ok(result)
+    }
+    
+    /// Check that a workout exists and belongs to the user
+    async fn ensure_workout_exists(&self, user_id: Uuid, workout_id: Uuid) -> Result<()> {
+        sqlx::query!(
+            "SELECT id FROM workouts WHERE id = $1 AND user_id = $2",
+            workout_id,
+            user_id
+        )
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Workout not found".to_string()))?;
+        
+        Ok(())
+    }
+    
+    /// Check that a workout exists and belongs to the user, locking it for the rest of the transaction
+    async fn lock_workout(conn: &mut PgConnection, user_id: Uuid, workout_id: Uuid) -> Result<()> {
+        sqlx::query!(
             r#"
             SELECT id
             FROM workouts
             WHERE id = $1 AND user_id = $2
+            FOR UPDATE
             "#,
             workout_id,
             user_id
         )
-        .fetch_optional(&self.db_pool)
+        .fetch_optional(&mut *conn)
         .await?
-        .ok_or_else(|| anyhow!("Workout not found"))?;
+        .ok_or_else(|| AppError::NotFound("Workout not found".to_string()))?;
         
-        // Delete the workout (cascade will delete related records)
-        let result = sqlx::query!(
+        Ok(())
+    }
+    
+    /// Check that every exercise is either in the shared catalog or one of the user's custom exercises
+    async fn ensure_exercises_visible(conn: &mut PgConnection, user_id: Uuid, exercise_ids: &[Uuid]) -> Result<()> {
+        let mut exercise_ids = exercise_ids.to_vec();
+        exercise_ids.sort();
+        exercise_ids.dedup();
+        
+        let visible_exercises = sqlx::query_scalar!(
             r#"
-            DELETE FROM workouts
-            WHERE id = $1
+            SELECT COUNT(*) AS "count!"
+            FROM exercises
+            WHERE id = ANY($1) AND (owner_id IS NULL OR owner_id = $2)
             "#,
+            &exercise_ids,
+            user_id
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+        
+        if visible_exercises != exercise_ids.len() as i64 {
+            return Err(AppError::Unprocessable("Exercise not found".to_string()));
+        }
+        
+        Ok(())
+    }
+    
+    /// Insert a workout together with its exercise entries
+    async fn insert_workout(
+        conn: &mut PgConnection,
+        user_id: Uuid,
+        req: &CreateWorkoutRequest,
+        now: DateTime<Utc>,
+    ) -> Result<Uuid> {
+        let workout_id = Uuid::new_v4();
+        
+        sqlx::query!(
+            r#"
+            INSERT INTO workouts (id, user_id, name, description, date, duration, calories_burned, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+            workout_id,
+            user_id,
+            req.name,
+            req.description,
+            req.date,
+            req.duration,
+            req.calories_burned,
+            now,
+            now
+        )
+        .execute(&mut *conn)
+        .await?;
+        
+        for (position, exercise) in req.exercises.iter().enumerate() {
+            Self::insert_workout_exercise(conn, workout_id, position as i32, exercise, now).await?;
+        }
+        
+        Ok(workout_id)
+    }
+    
+    /// Insert a single exercise entry into a workout
+    async fn insert_workout_exercise(
+        conn: &mut PgConnection,
+        workout_id: Uuid,
+        position: i32,
+        exercise: &WorkoutExerciseInput,
+        now: DateTime<Utc>,
+    ) -> Result<Uuid> {
+        let workout_exercise_id = Uuid::new_v4();
+        let aggregate = exercise.aggregate();
+        
+        sqlx::query!(
+            r#"
+            INSERT INTO workout_exercises (id, workout_id, exercise_id, sets, reps, weight, duration, distance, notes, position, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6::FLOAT8, $7, $8::FLOAT8, $9, $10, $11, $12)
+            "#,
+            workout_exercise_id,
+            workout_id,
+            exercise.exercise_id,
+            aggregate.sets,
+            aggregate.reps,
+            aggregate.weight,
+            aggregate.duration,
+            aggregate.distance,
+            exercise.notes,
+            position,
+            now,
+            now
+        )
+        .execute(&mut *conn)
+        .await?;
+        
+        Self::replace_workout_sets(conn, workout_exercise_id, &exercise.resolved_sets(), now).await?;
+        
+        Ok(workout_exercise_id)
+    }
+    
+    /// Replace all sets of an exercise entry
+    async fn replace_workout_sets(
+        conn: &mut PgConnection,
+        workout_exercise_id: Uuid,
+        sets: &[WorkoutSetInput],
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query!("DELETE FROM workout_sets WHERE workout_exercise_id = $1", workout_exercise_id)
+            .execute(&mut *conn)
+            .await?;
+        
+        for (set_index, set) in sets.iter().enumerate() {
+            sqlx::query!(
+                r#"
+                INSERT INTO workout_sets (id, workout_exercise_id, set_index, reps, weight, duration, distance, rpe, rir,
+                                          set_type, completed, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5::FLOAT8, $6, $7::FLOAT8, $8::FLOAT8, $9, $10, $11, $12, $13)
+                "#,
+                Uuid::new_v4(),
+                workout_exercise_id,
+                set_index as i32,
+                set.reps,
+                set.weight,
+                set.duration,
+                set.distance,
+                set.rpe,
+                set.rir,
+                set.set_type.unwrap_or(SetType::Working) as _,
+                set.completed.unwrap_or(true),
+                now,
+                now
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        
+        Ok(())
+    }
+    
+    /// IDs of the exercises used in a workout
+    async fn workout_exercise_ids(conn: &mut PgConnection, workout_id: Uuid) -> Result<Vec<Uuid>> {
+        let exercise_ids = sqlx::query_scalar!(
+            "SELECT DISTINCT exercise_id FROM workout_exercises WHERE workout_id = $1",
             workout_id
         )
-        .execute(&self.db_pool)
+        .fetch_all(&mut *conn)
         .await?;
         
-        Ok(result)
+        Ok(exercise_ids)
+    }
+    
+    /// Mark a workout as modified
+    async fn touch_workout(conn: &mut PgConnection, workout_id: Uuid, now: DateTime<Utc>) -> Result<()> {
+        sqlx::query!("UPDATE workouts SET updated_at = $2 WHERE id = $1", workout_id, now)
+            .execute(&mut *conn)
+            .await?;
+        
+        Ok(())
     }
 }
@@ -2,7 +2,10 @@ use crate::models::Claims;
 use anyhow::{Result, anyhow};
 use bcrypt::{hash, verify, DEFAULT_COST};
 use chrono::{Utc, Duration};
+use hmac::{Hmac, Mac};
 use jsonwebtoken::{encode, decode, Header, Validation, EncodingKey, DecodingKey};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
 /// Hash a password using bcrypt
@@ -15,8 +18,8 @@ pub fn verify_password(password: &str, hash: &str) -> Result<bool> {
     verify(password, hash).map_err(|e| anyhow!("Failed to verify password: {}", e))
 }
 
-/// Generate a JWT token for a user
-pub fn generate_token(user_id: Uuid, secret: &str, expiration_seconds: u64) -> Result<String> {
+/// Generate a JWT access token for a user's session
+pub fn generate_token(user_id: Uuid, session_id: Uuid, secret: &str, expiration_seconds: u64) -> Result<String> {
     let now = Utc::now();
     let exp = (now + Duration::seconds(expiration_seconds as i64))
         .timestamp() as usize;
@@ -24,6 +27,8 @@ pub fn generate_token(user_id: Uuid, secret: &str, expiration_seconds: u64) -> R
     
     let claims = Claims {
         sub: user_id.to_string(),
+        sid: session_id.to_string(),
+        jti: Uuid::new_v4().to_string(),
         exp,
         iat,
     };
@@ -49,3 +54,57 @@ pub fn validate_token(token: &str, secret: &str) -> Result<Claims> {
     
     Ok(token_data.claims)
 }
+
+/// Generate a random opaque token, used for refresh tokens and emailed links
+pub fn generate_opaque_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    
+    to_hex(&bytes)
+}
+
+/// Hash an opaque token for storage. Tokens are random, so a fast hash is enough.
+pub fn hash_token(token: &str) -> String {
+    to_hex(&Sha256::digest(token.as_bytes()))
+}
+
+/// Sign a message with HMAC-SHA256, for links that must not be forged or altered
+pub fn sign(message: &str, secret: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(message.as_bytes());
+    
+    to_hex(&mac.finalize().into_bytes())
+}
+
+/// Derive a separate key for one purpose from a secret, so that purposes
+/// can't accept each other's signatures
+pub fn derive_secret(secret: &str, purpose: &str) -> String {
+    sign(purpose, secret)
+}
+
+/// Check a signature created by `sign`, in constant time
+pub fn verify_signature(message: &str, signature: &str, secret: &str) -> bool {
+    let Some(signature) = from_hex(signature) else {
+        return false;
+    };
+    
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(message.as_bytes());
+    
+    mac.verify_slice(&signature).is_ok()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 == 1 || !hex.is_ascii() {
+        return None;
+    }
+    
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
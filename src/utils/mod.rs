@@ -1,7 +1,17 @@
 // Export utility modules
 pub mod auth;
+pub mod route;
+pub mod search;
+pub mod totp;
 #[cfg(test)]
 mod tests;
 
 // Re-export common utility functions
-pub use auth::{hash_password, verify_password, generate_token, validate_token};
+pub use auth::{
+    hash_password, verify_password, generate_token, validate_token, generate_opaque_token, hash_token,
+    sign, verify_signature, derive_secret,
+};
+pub use search::like_pattern;
+pub use totp::{
+    generate_totp_secret, totp_uri, verify_totp, generate_recovery_codes, normalize_recovery_code,
+};
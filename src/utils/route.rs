@@ -0,0 +1,188 @@
+use crate::models::route::pace;
+use crate::models::{RoutePoint, RouteSplit, RouteTotals, UnitSystem};
+
+/// Mean radius of the earth, in km
+const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+/// Slowest speed that counts as moving, in m/s. Slower stretches are pauses or
+/// GPS drift while standing still.
+const MIN_MOVING_SPEED: f64 = 0.5;
+
+/// Rise in elevation that counts as climbing, in m. Smaller changes are noise.
+const ELEVATION_THRESHOLD: f64 = 2.0;
+
+/// Least distance between stored points, in km
+const MIN_POINT_SPACING_KM: f64 = 0.01;
+
+/// Most points stored per route
+const MAX_ROUTE_POINTS: usize = 2000;
+
+/// Great-circle distance between two points, in km
+pub fn haversine_km(a: &RoutePoint, b: &RoutePoint) -> f64 {
+    let (lat_a, lat_b) = (a.latitude.to_radians(), b.latitude.to_radians());
+    let d_lat = lat_b - lat_a;
+    let d_lon = (b.longitude - a.longitude).to_radians();
+
+    let h = (d_lat / 2.0).sin().powi(2) + lat_a.cos() * lat_b.cos() * (d_lon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * h.sqrt().min(1.0).asin()
+}
+
+/// Seconds between two points if they were covered while moving, otherwise 0
+fn moving_seconds(a: &RoutePoint, b: &RoutePoint, km: f64) -> f64 {
+    let (Some(from), Some(to)) = (a.time, b.time) else {
+        return 0.0;
+    };
+
+    let seconds = (to - from).num_milliseconds() as f64 / 1000.0;
+    if seconds > 0.0 && km * 1000.0 / seconds >= MIN_MOVING_SPEED {
+        seconds
+    } else {
+        0.0
+    }
+}
+
+/// Distance, moving and elapsed time and elevation gain of a track. Times
+/// saturate at `i32::MAX` seconds.
+pub fn totals(points: &[RoutePoint]) -> RouteTotals {
+    let mut distance = 0.0;
+    let mut moving_time = 0.0;
+    for pair in points.windows(2) {
+        let km = haversine_km(&pair[0], &pair[1]);
+        distance += km;
+        moving_time += moving_seconds(&pair[0], &pair[1], km);
+    }
+
+    let mut times = points.iter().filter_map(|point| point.time);
+    let elapsed_time = match (times.next(), times.next_back()) {
+        (Some(first), Some(last)) if last > first => (last - first).num_seconds(),
+        _ => 0,
+    };
+
+    RouteTotals {
+        distance: round(distance, 3),
+        moving_time: moving_time.round() as i32,
+        elapsed_time: i32::try_from(elapsed_time).unwrap_or(i32::MAX),
+        elevation_gain: elevation_gain(points),
+    }
+}
+
+/// Total climb, counting a rise once it exceeds [`ELEVATION_THRESHOLD`] above
+/// the lowest point since the last counted rise
+fn elevation_gain(points: &[RoutePoint]) -> Option<f64> {
+    let mut elevations = points.iter().filter_map(|point| point.elevation);
+    let mut base = elevations.next()?;
+
+    let mut gain = 0.0;
+    for elevation in elevations {
+        if elevation - base >= ELEVATION_THRESHOLD {
+            gain += elevation - base;
+            base = elevation;
+        } else if elevation < base {
+            base = elevation;
+        }
+    }
+
+    Some(round(gain, 1))
+}
+
+/// Splits of a track per km, or per mile in imperial units. Split boundaries
+/// fall between points, so their time and elevation are interpolated.
+pub fn splits(points: &[RoutePoint], units: UnitSystem) -> Vec<RouteSplit> {
+    let split_km = units.distance_to_km(1.0);
+    let mut splits = Vec::new();
+
+    // Distance and moving time into the current split
+    let mut km = 0.0;
+    let mut seconds = 0.0;
+    let mut start_elevation = points.first().and_then(|point| point.elevation);
+
+    for pair in points.windows(2) {
+        let (a, b) = (&pair[0], &pair[1]);
+        let length = haversine_km(a, b);
+        let moving = moving_seconds(a, b, length);
+
+        // Share of the segment that is not part of a split yet
+        let mut remaining = 1.0;
+        while length > 0.0 && km + length * remaining >= split_km {
+            let share = (split_km - km) / length;
+            seconds += moving * share;
+            remaining -= share;
+
+            let elevation = interpolate(a.elevation, b.elevation, 1.0 - remaining);
+            splits.push(split(splits.len(), split_km, seconds, start_elevation, elevation, units));
+            start_elevation = elevation;
+            km = 0.0;
+            seconds = 0.0;
+        }
+
+        km += length * remaining;
+        seconds += moving * remaining;
+    }
+
+    // The rest of the route, unless the last split ended right at the finish
+    if km >= MIN_POINT_SPACING_KM {
+        let end_elevation = points.last().and_then(|point| point.elevation);
+        splits.push(split(splits.len(), km, seconds, start_elevation, end_elevation, units));
+    }
+
+    splits
+}
+
+fn split(
+    index: usize,
+    km: f64,
+    seconds: f64,
+    start_elevation: Option<f64>,
+    end_elevation: Option<f64>,
+    units: UnitSystem,
+) -> RouteSplit {
+    RouteSplit {
+        split: index as i32 + 1,
+        distance: round(km / units.distance_to_km(1.0), 2),
+        duration: seconds.round() as i32,
+        pace: pace(seconds, km, units),
+        elevation_change: start_elevation
+            .zip(end_elevation)
+            .map(|(start, end)| round(end - start, 1)),
+    }
+}
+
+fn interpolate(a: Option<f64>, b: Option<f64>, share: f64) -> Option<f64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a + (b - a) * share),
+        _ => a.or(b),
+    }
+}
+
+/// Points of a track to store: at least [`MIN_POINT_SPACING_KM`] apart and at
+/// most [`MAX_ROUTE_POINTS`] of them, always keeping the start and finish
+pub fn downsample(points: &[RoutePoint]) -> Vec<RoutePoint> {
+    let Some((last, rest)) = points.split_last() else {
+        return Vec::new();
+    };
+
+    let mut kept: Vec<RoutePoint> = Vec::new();
+    for point in rest {
+        if !kept.last().is_some_and(|previous| haversine_km(previous, point) < MIN_POINT_SPACING_KM) {
+            kept.push(*point);
+        }
+    }
+    kept.push(*last);
+
+    thin_out(kept, MAX_ROUTE_POINTS)
+}
+
+/// At most `max` of the items, evenly spread and keeping the first and last
+pub fn thin_out<T: Copy>(items: Vec<T>, max: usize) -> Vec<T> {
+    if items.len() <= max || max < 2 {
+        return items;
+    }
+
+    let step = (items.len() - 1) as f64 / (max - 1) as f64;
+    (0..max).map(|i| items[(i as f64 * step).round() as usize]).collect()
+}
+
+fn round(value: f64, decimals: i32) -> f64 {
+    let factor = 10f64.powi(decimals);
+    (value * factor).round() / factor
+}
@@ -0,0 +1,9 @@
+/// Build an ILIKE pattern matching `search` anywhere, with wildcards in the input escaped
+pub fn like_pattern(search: &str) -> String {
+    let escaped = search
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+
+    format!("%{}%", escaped)
+}
@@ -1,6 +1,11 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
-    use crate::utils::auth::{hash_password, verify_password};
+    use crate::utils::auth::{hash_password, verify_password, sign, verify_signature, derive_secret};
+    use crate::utils::totp::{verify_totp, generate_recovery_codes, normalize_recovery_code};
+    use crate::utils::route::{haversine_km, totals, splits, downsample};
+    use crate::models::{RoutePoint, UnitSystem};
+    use chrono::{Duration, TimeZone, Utc};
     
     #[test]
     fn test_password_hashing_and_verification() {
@@ -20,4 +25,85 @@ mod tests {
         
         assert!(!is_invalid, "Password verification should fail with wrong password");
     }
+    
+    #[test]
+    fn test_verify_totp() {
+        // RFC 6238 SHA-1 test vector: "12345678901234567890" at T = 59 gives 94287082
+        let secret = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+        
+        assert_eq!(verify_totp(secret, "287082", 59, None).unwrap(), Some(1));
+        // One step of clock drift is allowed
+        assert_eq!(verify_totp(secret, "287082", 89, None).unwrap(), Some(1));
+        assert_eq!(verify_totp(secret, "287082", 119, None).unwrap(), None);
+        // A code cannot be used twice
+        assert_eq!(verify_totp(secret, "287082", 59, Some(1)).unwrap(), None);
+        assert_eq!(verify_totp(secret, "000000", 59, None).unwrap(), None);
+    }
+    
+    #[test]
+    fn test_recovery_codes() {
+        let codes = generate_recovery_codes(10);
+        
+        assert_eq!(codes.len(), 10);
+        assert!(codes.iter().all(|code| code.len() == 11 && code.as_bytes()[5] == b'-'));
+        assert_eq!(normalize_recovery_code(" ABCDE-fghjk "), "abcdefghjk");
+    }
+    
+    #[test]
+    fn test_signatures() {
+        let signature = sign("export:42", "secret");
+        
+        assert!(verify_signature("export:42", &signature, "secret"));
+        assert!(!verify_signature("export:43", &signature, "secret"));
+        assert!(!verify_signature("export:42", &signature, "other"));
+        assert!(!verify_signature("export:42", "not hex", "secret"));
+
+        let derived = derive_secret("secret", "data-export-links");
+        assert_ne!(derived, "secret");
+        assert_ne!(derived, derive_secret("secret", "other-links"));
+        assert!(!verify_signature("export:42", &signature, &derived));
+    }
+    
+    #[test]
+    fn test_route_totals_and_splits() {
+        let point = |latitude: f64, seconds: i64| RoutePoint {
+            latitude,
+            longitude: 13.4,
+            elevation: None,
+            time: Some(Utc.with_ymd_and_hms(2024, 5, 4, 7, 0, 0).unwrap() + Duration::seconds(seconds)),
+        };
+        
+        // A degree of latitude is about 111.2 km
+        assert!((haversine_km(&point(52.0, 0), &point(53.0, 0)) - 111.195).abs() < 0.001);
+        
+        // 0.9 km north at 6 m/s, standing still for two minutes, then 1.8 km at 3 m/s
+        let step = 0.9 / 111.19508;
+        let points = vec![
+            point(52.0, 0),
+            point(52.0 + step, 150),
+            point(52.0 + step, 270),
+            point(52.0 + 3.0 * step, 870),
+        ];
+        
+        let route = totals(&points);
+        assert!((route.distance - 2.7).abs() < 0.001);
+        assert_eq!(route.moving_time, 750);
+        assert_eq!(route.elapsed_time, 870);
+        assert_eq!(route.elevation_gain, None);
+        
+        let km: Vec<_> = splits(&points, UnitSystem::Metric)
+            .iter()
+            .map(|split| (split.distance, split.duration, split.pace))
+            .collect();
+        assert_eq!(km, vec![(1.0, 183, Some(183)), (1.0, 333, Some(333)), (0.7, 233, Some(333))]);
+        
+        let miles: Vec<_> = splits(&points, UnitSystem::Imperial)
+            .iter()
+            .map(|split| (split.distance, split.duration))
+            .collect();
+        assert_eq!(miles, vec![(1.0, 386), (0.68, 364)]);
+        
+        // The pause adds nothing to the stored route
+        assert_eq!(downsample(&points).len(), 3);
+    }
 }
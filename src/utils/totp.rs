@@ -0,0 +1,77 @@
+use anyhow::{Result, anyhow};
+use rand::{Rng, RngCore};
+use totp_rs::{Algorithm, Secret, TOTP};
+
+/// Name shown for the account in authenticator apps
+const ISSUER: &str = "Fitness Progress Tracker";
+
+/// Seconds per TOTP time step
+const STEP: u64 = 30;
+
+/// Characters of recovery codes, without ones that are easy to confuse like 0/o and 1/l
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+/// Generate a random 160-bit TOTP secret, base32 encoded
+pub fn generate_totp_secret() -> String {
+    let mut bytes = [0u8; 20];
+    rand::thread_rng().fill_bytes(&mut bytes);
+
+    Secret::Raw(bytes.to_vec()).to_encoded().to_string()
+}
+
+/// Build the `otpauth://` URI that authenticator apps import, usually from a QR code
+pub fn totp_uri(secret: &str, account_name: &str) -> Result<String> {
+    Ok(totp(secret, account_name)?.get_url())
+}
+
+/// Check a 6-digit code against the secret at `time` (Unix seconds), allowing
+/// one step of clock drift either way. Returns the time step the code belongs to.
+///
+/// Codes from `last_used_step` or earlier are rejected so that an intercepted
+/// code cannot be replayed.
+pub fn verify_totp(secret: &str, code: &str, time: u64, last_used_step: Option<i64>) -> Result<Option<i64>> {
+    let totp = totp(secret, "")?;
+    let current_step = time / STEP;
+
+    let step = (current_step.saturating_sub(1)..=current_step + 1)
+        .find(|step| totp.check(code, step * STEP))
+        .map(|step| step as i64)
+        .filter(|step| match last_used_step {
+            Some(last) => *step > last,
+            None => true,
+        });
+
+    Ok(step)
+}
+
+/// Generate single-use recovery codes in the form `xxxxx-xxxxx`
+pub fn generate_recovery_codes(count: usize) -> Vec<String> {
+    let mut rng = rand::thread_rng();
+
+    (0..count)
+        .map(|_| {
+            let chars: String = (0..10)
+                .map(|_| RECOVERY_CODE_ALPHABET[rng.gen_range(0..RECOVERY_CODE_ALPHABET.len())] as char)
+                .collect();
+            format!("{}-{}", &chars[..5], &chars[5..])
+        })
+        .collect()
+}
+
+/// Normalize a recovery code as typed by a user, so that case, spaces and dashes do not matter
+pub fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn totp(secret: &str, account_name: &str) -> Result<TOTP> {
+    let secret = Secret::Encoded(secret.to_string())
+        .to_bytes()
+        .map_err(|e| anyhow!("Invalid TOTP secret: {:?}", e))?;
+
+    // Authenticator apps only support SHA-1, 6 digits and 30 second steps reliably
+    TOTP::new(Algorithm::SHA1, 6, 0, STEP, secret, Some(ISSUER.to_string()), account_name.to_string())
+        .map_err(|e| anyhow!("Failed to create TOTP: {}", e))
+}
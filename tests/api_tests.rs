@@ -1,5 +1,7 @@
-use actix_web::{test, web, App, http::StatusCode};
-use fitness_progress_tracker::{api, config::AppConfig, models::UserLoginRequest, models::UserRegisterRequest};
+use actix_web::{test, web, App, HttpResponse, http::StatusCode};
+use fitness_progress_tracker::error::{json_config, path_config, query_config};
+use fitness_progress_tracker::models::{CreateGoalRequest, ExerciseFilter};
+use uuid::Uuid;
 
 #[actix_rt::test]
 async fn test_health_check() {
@@ -28,10 +30,55 @@ async fn test_health_check() {
     assert!(body_str.contains("\"status\":\"ok\""));
 }
 
+#[actix_rt::test]
+async fn test_malformed_requests_return_json_errors() {
+    async fn handler(
+        _path: web::Path<Uuid>,
+        _filter: web::Query<ExerciseFilter>,
+        _req: web::Json<CreateGoalRequest>,
+    ) -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+    
+    // Create test app with the extractor configs of the server
+    let app = test::init_service(
+        App::new()
+            .app_data(json_config())
+            .app_data(query_config())
+            .app_data(path_config())
+            .route("/goals/{goal_id}", web::post().to(handler))
+    ).await;
+    
+    let id = Uuid::new_v4();
+    let requests = [
+        (format!("/goals/{}", id), "{\"name\": "),
+        (format!("/goals/{}?page=abc", id), "{\"name\": \"Run\"}"),
+        ("/goals/not-a-uuid".to_string(), "{\"name\": \"Run\"}"),
+    ];
+    for (uri, body) in requests {
+        let req = test::TestRequest::post()
+            .uri(&uri)
+            .insert_header(("content-type", "application/json"))
+            .set_payload(body)
+            .to_request();
+        
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        
+        // Assert JSON error body
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["code"], "bad_request");
+        assert!(body["error"].is_string());
+    }
+}
+
 // Note: The following tests would require a database connection and are commented out until
 // we have a proper test database setup with migrations. They serve as examples.
 
 /*
+use actix_web::web;
+use fitness_progress_tracker::{api, config::AppConfig, models::UserLoginRequest, models::UserRegisterRequest};
+
 #[actix_rt::test]
 async fn test_user_registration() {
     // Create a test db pool and app